@@ -0,0 +1,28 @@
+fn main() {
+    #[cfg(feature = "napi")]
+    napi_build::setup();
+
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/little_censor.udl").expect("Failed to generate scaffolding");
+
+    #[cfg(feature = "ffi")]
+    {
+        // Generated into OUT_DIR so builds don't modify the source tree, the checked-in
+        // header is regenerated with scripts/generate-header.sh
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("Cargo sets manifest dir");
+        let out_dir = std::env::var("OUT_DIR").expect("Cargo sets out dir");
+        cbindgen::generate(&crate_dir)
+            .expect("Failed to generate C header")
+            .write_to_file(format!("{out_dir}/little_censor.h"));
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+    }
+
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("Vendored protoc exists");
+        std::env::set_var("PROTOC", protoc);
+        tonic_prost_build::compile_protos("proto/little_censor.proto")
+            .expect("Failed to compile protocol buffers");
+    }
+}
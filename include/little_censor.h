@@ -0,0 +1,106 @@
+#ifndef LITTLE_CENSOR_H
+#define LITTLE_CENSOR_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Masks links
+ */
+#define LC_LINK 1
+
+/**
+ * Masks IP addresses
+ */
+#define LC_IP (1 << 1)
+
+/**
+ * Masks email addresses
+ */
+#define LC_EMAIL (1 << 2)
+
+/**
+ * Masks matches of the custom pattern
+ */
+#define LC_CUSTOM (1 << 3)
+
+/**
+ * Outcome of a call
+ *
+ * Values of errors are the numbers of `Error::number`, they never change.
+ */
+typedef enum LcStatus {
+  LC_STATUS_OK = 0,
+  LC_STATUS_NULL_POINTER = 1,
+  LC_STATUS_INVALID_UTF8 = 2,
+  LC_STATUS_EMPTY_WORD = 3,
+  LC_STATUS_NO_ARGS = 4,
+  LC_STATUS_INVALID_REGEX = 5,
+  LC_STATUS_BUDGET_EXCEEDED = 6,
+  LC_STATUS_UNKNOWN_PROFILE = 8,
+  LC_STATUS_STORAGE = 9,
+  LC_STATUS_UNKNOWN_TYPE = 10,
+  LC_STATUS_IO = 11,
+  LC_STATUS_PARSE = 12,
+  LC_STATUS_PATTERN_TOO_LARGE = 13,
+  LC_STATUS_INVALID_ENTRY = 14,
+  LC_STATUS_DUPLICATE_WORD = 15,
+  LC_STATUS_REJECTED_WORD = 16,
+  LC_STATUS_CONTENT_REJECTED = 17,
+  LC_STATUS_INPUT_TOO_LONG = 18,
+} LcStatus;
+
+/**
+ * Result of `lc_censor`, has to be released with `lc_free_result`
+ */
+typedef struct LcResult {
+  enum LcStatus status;
+  /**
+   * Censored sentence, null unless the status is `Ok`
+   */
+  char *censored;
+  /**
+   * Whether nothing had to be censored
+   */
+  bool valid;
+  /**
+   * Bits of the type of vulgar words found
+   */
+  uint32_t typ;
+} LcResult;
+
+/**
+ * Censors `sentence` with additional types given as `LC_*` bits
+ *
+ * `arg` is the pattern for `LC_CUSTOM` and may be null otherwise.
+ *
+ * # Safety
+ *
+ * `sentence` and `arg` have to be null or valid NUL terminated strings.
+ */
+struct LcResult *lc_censor(const char *sentence, uint32_t types, const char *arg);
+
+/**
+ * Releases result of `lc_censor`
+ *
+ * # Safety
+ *
+ * `result` has to be null or returned by `lc_censor` and not released before.
+ */
+void lc_free_result(struct LcResult *result);
+
+/**
+ * Adds `len` words of the same type, `typ` of 0 means inappropriate words
+ *
+ * # Safety
+ *
+ * `words` has to point to `len` valid NUL terminated strings.
+ */
+enum LcStatus lc_add_words(const char *const *words, size_t len, uint32_t typ);
+
+#endif  /* LITTLE_CENSOR_H */
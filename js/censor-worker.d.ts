@@ -0,0 +1,30 @@
+import type { CensorTypes } from "../pkg/little_censor.js";
+
+export interface WorkerOptions {
+    /** Additional types of censoring */
+    types?: CensorTypes[];
+    /** Argument for `CensorTypes.Custom` */
+    arg?: string;
+}
+
+export interface WorkerSpan {
+    start: number;
+    end: number;
+    kind: string;
+}
+
+export interface WorkerCensored {
+    original: string;
+    censored: string;
+    valid: boolean;
+    truncated: boolean;
+    piiRedacted: boolean;
+    profanityCensored: boolean;
+    score: number;
+    types: string[];
+    spans: WorkerSpan[];
+    verdict: string;
+}
+
+export function censorInWorker(text: string, opts?: WorkerOptions): Promise<WorkerCensored>;
+export function stopCensorWorker(): void;
@@ -0,0 +1,50 @@
+// Censoring inside a Web Worker, so long messages don't block the page
+const pending = new Map();
+let worker;
+let nextId = 0;
+
+function start() {
+    worker = new Worker(new URL("./worker.js", import.meta.url), { type: "module" });
+    worker.onmessage = ({ data: { id, result, error, code, input } }) => {
+        const request = pending.get(id);
+        pending.delete(id);
+        if (error === undefined) {
+            request.resolve(result);
+        } else {
+            request.reject(Object.assign(new Error(error), { code, input }));
+        }
+    };
+    worker.onerror = (event) => {
+        for (const request of pending.values()) {
+            request.reject(new Error(event.message));
+        }
+        pending.clear();
+        stopCensorWorker();
+    };
+    return worker;
+}
+
+/**
+ * Censors text in a shared worker started on the first call
+ * @param {string} text
+ * @param {{ types?: number[], arg?: string }} [opts]
+ * @returns {Promise<object>} result of `censor_plain`
+ */
+export function censorInWorker(text, opts = {}) {
+    const id = nextId++;
+    return new Promise((resolve, reject) => {
+        pending.set(id, { resolve, reject });
+        (worker ?? start()).postMessage({
+            id,
+            text,
+            types: opts.types ?? [],
+            arg: opts.arg,
+        });
+    });
+}
+
+/** Terminates the worker, the next call to `censorInWorker` starts a new one */
+export function stopCensorWorker() {
+    worker?.terminate();
+    worker = undefined;
+}
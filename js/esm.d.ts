@@ -0,0 +1 @@
+export * from "../pkg/little_censor.js";
@@ -0,0 +1,6 @@
+// ES module which is ready to use once imported, without bundler specific glue
+import init from "../pkg/little_censor.js";
+
+await init();
+
+export * from "../pkg/little_censor.js";
@@ -0,0 +1,13 @@
+// Web Worker censoring messages sent by `censorInWorker`
+import init, { censor_plain } from "../pkg/little_censor.js";
+
+const ready = init();
+
+self.onmessage = async ({ data: { id, text, types, arg } }) => {
+    try {
+        await ready;
+        self.postMessage({ id, result: censor_plain(text, types, arg) });
+    } catch (error) {
+        self.postMessage({ id, error: error.message ?? String(error), code: error.code, input: error.input });
+    }
+};
@@ -0,0 +1,134 @@
+//! `#[derive(Censor)]` implementing `little_censor::fields::CensorFields`
+//!
+//! Use it through the `derive` feature of `little-censor`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, Ident, PathArguments, Token,
+    Type,
+};
+
+/// Implements `CensorFields` censoring every `String`, `Option<String>` and `Vec<String>` field
+///
+/// Fields are skipped with `#[censor(skip)]` and censored with additional types of
+/// censoring with `#[censor(types(Link, Email))]`.
+#[proc_macro_derive(Censor, attributes(censor))]
+pub fn derive_censor(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "Censor can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut censored = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let options = FieldOptions::parse(&field.attrs)?;
+        if !is_text(&field.ty) {
+            if !options.types.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "types can only be given for String, Option<String> and Vec<String> fields",
+                ));
+            }
+            continue;
+        }
+        if options.skip {
+            continue;
+        }
+        let member = match (&field.ident, fields) {
+            (Some(ident), _) => quote!(#ident),
+            (None, Fields::Unnamed(_)) => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+            (None, _) => continue,
+        };
+        let types = options.types;
+        censored.push(quote! {
+            changed |= ::little_censor::fields::CensorField::censor_field(
+                &mut self.#member,
+                censor,
+                &[#(::little_censor::censor::CensorTypes::#types),*],
+            )?;
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::little_censor::fields::CensorFields for #name #ty_generics #where_clause {
+            fn censor_fields(
+                &mut self,
+                censor: &::little_censor::censor::Censor,
+            ) -> ::core::result::Result<bool, ::little_censor::Error> {
+                let mut changed = false;
+                #(#censored)*
+                Ok(changed)
+            }
+        }
+    })
+}
+
+/// Options given with `#[censor(...)]`
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    types: Vec<Ident>,
+}
+
+impl FieldOptions {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("censor")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    options.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("types") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let types = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                    options.types.extend(types);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `skip` or `types(...)`"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Whether the type is `String` or `Option` or `Vec` of text
+fn is_text(ty: &Type) -> bool {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => segment,
+            None => return false,
+        },
+        _ => return false,
+    };
+    match (segment.ident.to_string().as_str(), &segment.arguments) {
+        ("String", PathArguments::None) => true,
+        ("Option" | "Vec", PathArguments::AngleBracketed(args)) => match args.args.first() {
+            Some(GenericArgument::Type(inner)) if args.args.len() == 1 => is_text(inner),
+            _ => false,
+        },
+        _ => false,
+    }
+}
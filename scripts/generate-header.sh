@@ -0,0 +1,6 @@
+#!/bin/sh
+# Regenerates the checked-in C header from src/ffi.rs, needs `cargo install cbindgen`.
+# Extra arguments are passed to cbindgen, e.g. `--verify` to only check the header.
+set -e
+cd "$(dirname "$0")/.."
+exec cbindgen --config cbindgen.toml --crate little-censor --output include/little_censor.h "$@"
@@ -0,0 +1,251 @@
+//! Censoring HTTP bodies in actix-web services, the counterpart of the `tower` layer
+//!
+//! [`CensoredText`] censors the text body of a single handler and [`CensorMiddleware`]
+//! censors JSON and `text/*` bodies of every request, and optionally response, of an app.
+
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use actix_web::body::{to_bytes_limited, BodyStream, BoxBody, MessageBody};
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+use actix_web::web::{Bytes, Data};
+use actix_web::{error, FromRequest, HttpMessage, HttpRequest};
+
+use crate::body::{censor_body, BodyError, Content};
+use crate::json::JsonOptions;
+
+type LocalFuture<T> = Pin<Box<dyn Future<Output = Result<T, actix_web::Error>>>>;
+
+/// Text body censored with `Data<JsonOptions>` of the app, or the default censor without it
+///
+/// The body is limited by `PayloadConfig` like the `String` extractor.
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::{web, App};
+/// use little_censor::actix::CensoredText;
+/// use little_censor::json::JsonOptions;
+///
+/// async fn send_message(message: CensoredText) -> String {
+///     message.text
+/// }
+///
+/// let app = App::new()
+///     .app_data(web::Data::new(JsonOptions::new()))
+///     .route("/messages", web::post().to(send_message));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CensoredText {
+    /// Censored body
+    pub text: String,
+    /// Whether anything was censored
+    pub modified: bool,
+}
+
+impl FromRequest for CensoredText {
+    type Error = actix_web::Error;
+    type Future = LocalFuture<Self>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let options = req
+            .app_data::<Data<JsonOptions>>()
+            .map(|options| options.clone().into_inner());
+        let text = String::from_request(req, payload);
+
+        Box::pin(async move {
+            let text = text.await?;
+            let censored = options
+                .unwrap_or_default()
+                .censor(text)
+                .map_err(error::ErrorInternalServerError)?;
+            Ok(Self {
+                modified: censored.changed(),
+                text: censored.censored,
+            })
+        })
+    }
+}
+
+/// Middleware censoring request bodies, and optionally response bodies, with a shared censor
+///
+/// Requests whose body can't be read or parsed are answered with `400 Bad Request`,
+/// bodies over the limit with `413 Payload Too Large` and failed censoring with
+/// `500 Internal Server Error`.
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::App;
+/// use little_censor::actix::CensorMiddleware;
+/// use little_censor::json::JsonOptions;
+///
+/// let app = App::new().wrap(CensorMiddleware::new(JsonOptions::new().with_keys(["text"])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CensorMiddleware {
+    options: Arc<JsonOptions>,
+    responses: bool,
+    limit: usize,
+}
+
+impl CensorMiddleware {
+    /// Censors request bodies up to 1 MiB, key patterns apply to JSON bodies only
+    pub fn new(options: JsonOptions) -> Self {
+        Self {
+            options: Arc::new(options),
+            responses: false,
+            limit: 1024 * 1024,
+        }
+    }
+
+    /// Whether response bodies are censored too
+    pub fn with_responses(mut self, responses: bool) -> Self {
+        self.responses = responses;
+        self
+    }
+
+    /// Sets maximum size of censored bodies in bytes
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    async fn censor<B: MessageBody>(
+        &self,
+        content: Content,
+        body: B,
+    ) -> Result<Bytes, actix_web::Error> {
+        let bytes = match to_bytes_limited(body, self.limit).await {
+            Ok(Ok(bytes)) => bytes,
+            Ok(Err(_)) => return Err(error::ErrorBadRequest("Body couldn't be read")),
+            Err(_) => return Err(error::ErrorPayloadTooLarge("Body is too large")),
+        };
+        match censor_body(content, &bytes, &self.options) {
+            Ok(Some(censored)) => Ok(Bytes::from(censored)),
+            Ok(None) => Ok(bytes),
+            Err(BodyError::Invalid) => Err(error::ErrorBadRequest("Body is invalid")),
+            Err(BodyError::Censor) => Err(error::ErrorInternalServerError("Censoring failed")),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CensorMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = CensorService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CensorService {
+            service: Rc::new(service),
+            middleware: self.clone(),
+        }))
+    }
+}
+
+/// Service created by [`CensorMiddleware`]
+pub struct CensorService<S> {
+    service: Rc<S>,
+    middleware: CensorMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for CensorService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Future = LocalFuture<Self::Response>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let middleware = self.middleware.clone();
+
+        Box::pin(async move {
+            if let Some(content) = content(req.headers()) {
+                let body = BodyStream::new(req.take_payload());
+                let bytes = middleware.censor(content, body).await?;
+                req.headers_mut()
+                    .insert(CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+                req.set_payload(Payload::from(bytes));
+            }
+
+            let response = service.call(req).await?.map_into_boxed_body();
+            let content = match content(response.headers()) {
+                Some(content) if middleware.responses => content,
+                _ => return Ok(response),
+            };
+            let (request, response) = response.into_parts();
+            let (mut response, body) = response.into_parts();
+            let bytes = middleware
+                .censor(content, body)
+                .await
+                .map_err(|_| error::ErrorInternalServerError("Censoring failed"))?;
+            // The length is set again from the new body
+            response.headers_mut().remove(CONTENT_LENGTH);
+            Ok(ServiceResponse::new(
+                request,
+                response.set_body(BoxBody::new(bytes)),
+            ))
+        })
+    }
+}
+
+fn content(headers: &HeaderMap) -> Option<Content> {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Content::of)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App};
+
+    use super::*;
+
+    #[test]
+    fn censors_bodies() {
+        actix_web::rt::System::new().block_on(async {
+            let app = test::init_service(
+                App::new()
+                    .wrap(CensorMiddleware::new(JsonOptions::new()).with_responses(true))
+                    .route("/echo", web::post().to(|body: Bytes| async move { body }))
+                    .route(
+                        "/text",
+                        web::post().to(|text: CensoredText| async move {
+                            format!("{} {}", text.modified, text.text)
+                        }),
+                    ),
+            )
+            .await;
+
+            let request = test::TestRequest::post()
+                .uri("/echo")
+                .insert_header((CONTENT_TYPE, "application/json"))
+                .set_payload(r#"["fuck"]"#)
+                .to_request();
+            let body = test::call_and_read_body(&app, request).await;
+            assert_eq!(body, r#"["f***"]"#);
+
+            let request = test::TestRequest::post()
+                .uri("/text")
+                .set_payload("fuck")
+                .to_request();
+            let body = test::call_and_read_body(&app, request).await;
+            assert_eq!(body, "true f***");
+        });
+    }
+}
@@ -0,0 +1,220 @@
+//! Manual moderator decisions
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Manual decision about a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// Message is delivered without censoring
+    Allow,
+    /// Message is blocked
+    Deny,
+}
+
+/// Decision made by a moderator about specific content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Override {
+    pub decision: Decision,
+    /// Moderator who made the decision
+    pub moderator: String,
+    pub note: Option<String>,
+    pub at: SystemTime,
+}
+
+/// Storage of overrides keyed by message content
+///
+/// Implement it to keep decisions in a database shared between instances.
+pub trait OverrideStore: Send + Sync {
+    /// Returns override stored for the content
+    fn load(&self, content: &str) -> Result<Option<Override>, Error>;
+
+    /// Stores override for the content, replacing the previous one
+    fn save(&self, content: &str, entry: Override) -> Result<(), Error>;
+
+    /// Removes override for the content
+    fn delete(&self, content: &str) -> Result<(), Error>;
+
+    /// Returns every stored override
+    fn entries(&self) -> Result<Vec<(String, Override)>, Error>;
+}
+
+/// Overrides kept in memory of the process
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, Override>>,
+}
+
+impl OverrideStore for MemoryStore {
+    fn load(&self, content: &str) -> Result<Option<Override>, Error> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get(content).cloned())
+    }
+
+    fn save(&self, content: &str, entry: Override) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(content.to_owned(), entry);
+        Ok(())
+    }
+
+    fn delete(&self, content: &str) -> Result<(), Error> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(content);
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(String, Override)>, Error> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .iter()
+            .map(|(content, entry)| (content.clone(), entry.clone()))
+            .collect())
+    }
+}
+
+/// Log of moderator overrides consulted for identical content
+///
+/// Attach it to a censor with [`crate::censor::Censor::with_audit_log`] to apply the
+/// decisions automatically, see [`crate::censor::Censored::overridden`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::audit::{AuditLog, Decision};
+///
+/// let log = AuditLog::new();
+/// log.record("my surname is Dick", Decision::Allow, "moderator", None).unwrap();
+///
+/// let entry = log.lookup("  my surname is Dick ").unwrap().unwrap();
+/// assert_eq!(entry.decision, Decision::Allow);
+/// ```
+pub struct AuditLog {
+    store: Box<dyn OverrideStore>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog").finish_non_exhaustive()
+    }
+}
+
+impl AuditLog {
+    /// Creates log kept in memory
+    pub fn new() -> Self {
+        Self::with_store(MemoryStore::default())
+    }
+
+    /// Creates log kept in given storage
+    pub fn with_store(store: impl OverrideStore + 'static) -> Self {
+        Self {
+            store: Box::new(store),
+        }
+    }
+
+    /// Records decision of a moderator about the content
+    ///
+    /// # Errors
+    ///
+    /// Returns errors of the underlying storage.
+    pub fn record(
+        &self,
+        content: &str,
+        decision: Decision,
+        moderator: &str,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        self.store.save(
+            key(content),
+            Override {
+                decision,
+                moderator: moderator.to_owned(),
+                note,
+                at: SystemTime::now(),
+            },
+        )
+    }
+
+    /// Returns decision recorded for the content
+    ///
+    /// # Errors
+    ///
+    /// Returns errors of the underlying storage.
+    pub fn lookup(&self, content: &str) -> Result<Option<Override>, Error> {
+        self.store.load(key(content))
+    }
+
+    /// Removes decision recorded for the content
+    ///
+    /// # Errors
+    ///
+    /// Returns errors of the underlying storage.
+    pub fn revoke(&self, content: &str) -> Result<(), Error> {
+        self.store.delete(key(content))
+    }
+
+    /// Returns every recorded decision
+    ///
+    /// # Errors
+    ///
+    /// Returns errors of the underlying storage.
+    pub fn entries(&self) -> Result<Vec<(String, Override)>, Error> {
+        self.store.entries()
+    }
+}
+
+/// Content is identical when it differs only by surrounding whitespace
+fn key(content: &str) -> &str {
+    content.trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::Censor;
+
+    #[test]
+    fn revoke_decision() {
+        let log = AuditLog::new();
+        log.record("text", Decision::Deny, "mod", Some(String::from("scam")))
+            .unwrap();
+        assert_eq!(log.entries().unwrap().len(), 1);
+
+        log.revoke("text").unwrap();
+        assert_eq!(log.lookup("text").unwrap(), None);
+    }
+
+    #[test]
+    fn censor_applies_overrides() {
+        let log = AuditLog::new();
+        log.record("fuck yeah", Decision::Allow, "mod", None)
+            .unwrap();
+        log.record("buy gold", Decision::Deny, "mod", None).unwrap();
+        let censor = Censor::new().with_audit_log(log);
+
+        let allowed = censor
+            .censor(String::from("fuck yeah"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(allowed.censored, "fuck yeah");
+        assert!(allowed.valid);
+        assert_eq!(allowed.overridden, Some(Decision::Allow));
+
+        let denied = censor
+            .censor(String::from("buy gold"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(denied.overridden, Some(Decision::Deny));
+        assert_eq!(denied.report().verdict, crate::report::Verdict::Block);
+    }
+}
@@ -0,0 +1,231 @@
+//! `diff` subcommand showing what censoring does to a file
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use little_censor::censor::Censored;
+use little_censor::error::Error;
+
+use crate::{Options, Scrubber};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// Prints original and censored lines of a file side by side, with a summary of
+/// triggered rules
+#[derive(Debug, Args)]
+pub(crate) struct Diff {
+    /// File to compare
+    file: PathBuf,
+    /// Prints unchanged lines too
+    #[arg(long)]
+    all: bool,
+    /// Width of each column, longer lines are wrapped
+    #[arg(long, default_value_t = 60)]
+    width: usize,
+    /// Colors changed regions
+    #[arg(long, value_enum, default_value_t)]
+    color: Color,
+    #[command(flatten)]
+    options: Options,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Color {
+    /// When stdout is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Lines and matches of a rule
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Triggered {
+    lines: usize,
+    matches: usize,
+}
+
+impl Diff {
+    pub(crate) fn run(self) -> Result<(), String> {
+        let scrubber = Scrubber::new(&self.options)?;
+        let text = fs::read_to_string(&self.file)
+            .map_err(|error| format!("{}: {error}", self.file.display()))?;
+        let color = match self.color {
+            Color::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            Color::Always => true,
+            Color::Never => false,
+        };
+        self.print(&scrubber, &text, color, &mut io::stdout().lock())
+            .map_err(|error| format!("{}: {error}", self.file.display()))
+    }
+
+    fn print(
+        &self,
+        scrubber: &Scrubber,
+        text: &str,
+        color: bool,
+        writer: &mut impl Write,
+    ) -> Result<(), Error> {
+        let width = self.width.max(1);
+        let mut rules: BTreeMap<&'static str, Triggered> = BTreeMap::new();
+        let mut changed = 0;
+        let mut total = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            total += 1;
+            let censored = scrubber.censor.censor(
+                line.to_owned(),
+                scrubber.types.clone(),
+                scrubber.arg.clone(),
+            )?;
+            if censored.censored() != line {
+                changed += 1;
+            } else if !self.all {
+                continue;
+            }
+
+            let mut kinds: Vec<&'static str> = Vec::new();
+            for span in censored.spans() {
+                let name = span.kind.name();
+                let triggered = rules.entry(name).or_default();
+                triggered.matches += 1;
+                if !kinds.contains(&name) {
+                    kinds.push(name);
+                    triggered.lines += 1;
+                }
+            }
+            for row in rows(line, &censored, index + 1, width, color) {
+                writeln!(writer, "{row}")?;
+            }
+        }
+
+        writeln!(writer)?;
+        writeln!(writer, "{:<12} {:>8} {:>8}", "rule", "lines", "matches")?;
+        for (name, triggered) in &rules {
+            writeln!(
+                writer,
+                "{name:<12} {:>8} {:>8}",
+                triggered.lines, triggered.matches
+            )?;
+        }
+        writeln!(writer, "{changed} of {total} lines changed")?;
+        Ok(writer.flush()?)
+    }
+}
+
+/// Rows of a line printed side by side, wrapped at `width` characters
+fn rows(line: &str, censored: &Censored, number: usize, width: usize, color: bool) -> Vec<String> {
+    // Spans are in bytes of the original line, the columns are compared in characters
+    let marked: Vec<bool> = line
+        .char_indices()
+        .map(|(index, _)| {
+            censored
+                .spans()
+                .iter()
+                .any(|span| (span.start..span.end).contains(&index))
+        })
+        .collect();
+    let original: Vec<char> = line.chars().collect();
+    let replaced: Vec<char> = censored.censored().chars().collect();
+    // Without spans the columns differ where the characters do
+    let replaced_marked: Vec<bool> = if replaced.len() == original.len() {
+        marked
+            .iter()
+            .zip(original.iter().zip(&replaced))
+            .map(|(&marked, (a, b))| marked || a != b)
+            .collect()
+    } else {
+        vec![censored.censored() != line; replaced.len()]
+    };
+
+    let count = original.len().max(replaced.len()).div_ceil(width).max(1);
+    (0..count)
+        .map(|row| {
+            let range = row * width..(row + 1) * width;
+            let prefix = match row {
+                0 => format!("{number:>5}"),
+                _ => String::from("     "),
+            };
+            format!(
+                "{prefix} │ {} │ {}",
+                column(&original, &marked, range.clone(), RED, color),
+                column(&replaced, &replaced_marked, range, GREEN, color)
+            )
+        })
+        .collect()
+}
+
+/// Characters of a column padded to its width, marked ones colored
+fn column(
+    chars: &[char],
+    marked: &[bool],
+    range: Range<usize>,
+    highlight: &str,
+    color: bool,
+) -> String {
+    let mut output = String::new();
+    let mut colored = false;
+    for index in range {
+        let c = chars.get(index).copied().unwrap_or(' ');
+        let mark = color && marked.get(index).copied().unwrap_or(false);
+        if mark != colored {
+            output.push_str(if mark { highlight } else { RESET });
+            colored = mark;
+        }
+        output.push(c);
+    }
+    if colored {
+        output.push_str(RESET);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_by_side_with_summary() {
+        let diff = Diff {
+            file: PathBuf::new(),
+            all: false,
+            width: 12,
+            color: Color::Always,
+            options: Options {
+                emails: true,
+                ..Options::default()
+            },
+        };
+        let scrubber = Scrubber::new(&diff.options).unwrap();
+        let mut output = Vec::new();
+
+        diff.print(
+            &scrubber,
+            "fine\nfuck, mail me@example.net\n",
+            true,
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!("    2 │ f{RED}uck{RESET}, mail {RED}m{RESET} │ f{GREEN}***{RESET}, mail {GREEN}*{RESET}")
+        );
+        assert_eq!(
+            lines[2],
+            format!("      │ {RED}t{RESET}            │ {GREEN}*{RESET}           ")
+        );
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[5], "email               1        1");
+        assert_eq!(lines[6], "profanity           1        1");
+        assert_eq!(lines[7], "1 of 2 lines changed");
+    }
+}
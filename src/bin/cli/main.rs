@@ -0,0 +1,340 @@
+//! Command line tool censoring text from arguments, stdin or files, e.g. in shell pipelines
+
+mod diff;
+mod scrub;
+mod scrub_file;
+mod tail;
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use little_censor::censor::{Censor, CensorTypes, Censored};
+use little_censor::config::Config;
+use little_censor::error::Error;
+use little_censor::span::SpanKind;
+use little_censor::{type_name, Vulgar};
+use serde_json::json;
+
+/// Censors text given as arguments, or every line of stdin
+#[derive(Debug, Parser)]
+#[command(
+    name = "little-censor",
+    version,
+    about,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    /// Text censored instead of stdin, multiple arguments are joined with spaces
+    text: Vec<String>,
+    #[command(flatten)]
+    options: Options,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Diff(diff::Diff),
+    Scrub(scrub::Scrub),
+    ScrubFile(scrub_file::ScrubFile),
+    Tail(tail::Tail),
+}
+
+/// Additional types of censoring and output format
+#[derive(Debug, Clone, Default, Args)]
+struct Options {
+    /// Configuration file, `little-censor.toml` of the working directory by default
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Censors links
+    #[arg(long)]
+    links: bool,
+    /// Censors email addresses
+    #[arg(long)]
+    emails: bool,
+    /// Censors IP addresses
+    #[arg(long)]
+    ips: bool,
+    /// Censors matches of the regular expression
+    #[arg(long, value_name = "RE")]
+    custom: Option<String>,
+    /// Adds a word to the word list, optionally with its type, e.g. `slur:SEVERE|OFFENSIVE`
+    #[arg(long = "word", value_name = "WORD[:TYPE]")]
+    words: Vec<Vulgar>,
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Censored text
+    #[default]
+    Text,
+    /// JSON object per line with spans, triggered rules and severity
+    Json,
+}
+
+/// Censor together with options given on the command line
+#[derive(Debug, Clone)]
+struct Scrubber {
+    censor: Censor,
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+    format: Format,
+}
+
+impl Scrubber {
+    /// Censor of the configuration file, with rules of the options added to configured ones
+    fn new(options: &Options) -> Result<Self, String> {
+        let config = Config::discover(options.config.as_deref()).map_err(|e| e.to_string())?;
+        let mut types = config.rules.clone();
+        for (enabled, rule) in [
+            (options.links, CensorTypes::Link),
+            (options.emails, CensorTypes::Email),
+            (options.ips, CensorTypes::IP),
+            (options.custom.is_some(), CensorTypes::Custom),
+        ] {
+            if enabled && !types.contains(&rule) {
+                types.push(rule);
+            }
+        }
+        let censor = config
+            .censor_with_words(options.words.clone())
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            censor,
+            types: types.into_boxed_slice(),
+            arg: options.custom.clone().or(config.custom),
+            format: options.format,
+        })
+    }
+
+    /// Censors the `number`th line of a file, returns output and number of redactions
+    ///
+    /// Line ending of the text output is kept as it is.
+    fn line(
+        &self,
+        line: &str,
+        number: usize,
+        file: Option<&Path>,
+    ) -> Result<(String, usize), Error> {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let censored =
+            self.censor
+                .censor(content.to_owned(), self.types.clone(), self.arg.clone())?;
+        let output = match self.format {
+            Format::Text => format!("{}{}", censored.censored(), &line[content.len()..]),
+            Format::Json => format!("{}\n", record(&censored, number, file)),
+        };
+        Ok((output, censored.spans().len()))
+    }
+
+    /// Censors every line of the reader into the writer, returns number of redactions
+    fn copy(
+        &self,
+        mut reader: impl BufRead,
+        mut writer: impl Write,
+        file: Option<&Path>,
+    ) -> Result<usize, Error> {
+        let mut line = String::new();
+        let mut redactions = 0;
+        let mut number = 0;
+        loop {
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .map_err(|error| io_error(error, file))?;
+            if read == 0 {
+                writer.flush().map_err(|error| io_error(error, file))?;
+                return Ok(redactions);
+            }
+            number += 1;
+            let (censored, count) = self.line(&line, number, file)?;
+            redactions += count;
+            writer
+                .write_all(censored.as_bytes())
+                .map_err(|error| io_error(error, file))?;
+        }
+    }
+}
+
+/// I/O error naming the file it happened on, if it isn't a standard stream
+fn io_error(error: io::Error, file: Option<&Path>) -> Error {
+    match file {
+        Some(file) => Error::Io(io::Error::new(
+            error.kind(),
+            format!("{}: {error}", file.display()),
+        )),
+        None => Error::Io(error),
+    }
+}
+
+/// Structured result of censoring a line
+fn record(censored: &Censored, number: usize, file: Option<&Path>) -> serde_json::Value {
+    let spans: Vec<_> = censored
+        .spans()
+        .iter()
+        .map(|span| json!({ "start": span.start, "end": span.end, "kind": span.kind.name() }))
+        .collect();
+    let rules: Vec<_> = censored
+        .rules()
+        .iter()
+        .map(|&rule| SpanKind::Rule(rule).name())
+        .collect();
+    let mut record = json!({
+        "line": number,
+        "censored": censored.censored(),
+        "valid": censored.valid(),
+        "pii_redacted": censored.pii_redacted(),
+        "profanity_censored": censored.profanity_censored(),
+        "severity": type_name::format(censored.typ()),
+        "verdict": format!("{:?}", censored.verdict()).to_lowercase(),
+        "rules": rules,
+        "spans": spans,
+    });
+    if let Some(file) = file {
+        record["file"] = json!(file.display().to_string());
+    }
+    record
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Some(Command::Diff(diff)) => diff.run(),
+        Some(Command::Scrub(scrub)) => scrub.run(),
+        Some(Command::ScrubFile(scrub_file)) => scrub_file.run(),
+        Some(Command::Tail(tail)) => tail.run(),
+        None => Scrubber::new(&cli.options).and_then(|scrubber| {
+            let result = if cli.text.is_empty() {
+                scrubber
+                    .copy(io::stdin().lock(), io::stdout().lock(), None)
+                    .map(|_| ())
+            } else {
+                scrubber
+                    .line(&cli.text.join(" "), 1, None)
+                    .map(|(output, _)| println!("{}", output.trim_end_matches('\n')))
+            };
+            result.map_err(|error| error.to_string())
+        }),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("little-censor: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_line_endings() {
+        let options = Options {
+            emails: true,
+            ..Options::default()
+        };
+        let mut censored = Vec::new();
+
+        let redactions = Scrubber::new(&options)
+            .unwrap()
+            .copy(&b"fuck\r\nme@example.net"[..], &mut censored, None)
+            .unwrap();
+
+        assert_eq!(redactions, 2);
+        assert_eq!(censored, b"f***\r\n**************");
+    }
+
+    #[test]
+    fn io_errors_name_the_file() {
+        struct Failing;
+
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "bad sector"))
+            }
+        }
+
+        let error = Scrubber::new(&Options::default())
+            .unwrap()
+            .copy(
+                io::BufReader::new(Failing),
+                io::sink(),
+                Some(Path::new("logs/app.log")),
+            )
+            .unwrap_err();
+
+        match error {
+            Error::Io(error) => {
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+                assert_eq!(error.to_string(), "logs/app.log: bad sector");
+            }
+            error => panic!("unexpected error {error}"),
+        }
+    }
+
+    #[test]
+    fn json_records() {
+        let options = Options {
+            emails: true,
+            format: Format::Json,
+            ..Options::default()
+        };
+        let mut output = Vec::new();
+
+        Scrubber::new(&options)
+            .unwrap()
+            .copy(
+                &b"fine\nme@example.net\n"[..],
+                &mut output,
+                Some(Path::new("app.log")),
+            )
+            .unwrap();
+
+        let records: Vec<serde_json::Value> = output
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["line"], 2);
+        assert_eq!(records[1]["file"], "app.log");
+        assert_eq!(records[1]["rules"], json!(["email"]));
+        assert_eq!(records[1]["spans"][0]["kind"], "email");
+    }
+
+    #[test]
+    fn words_added_to_packs() {
+        let dir = std::env::temp_dir().join(format!("little-censor-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pack = dir.join("words.lcwp");
+        std::fs::write(
+            &pack,
+            little_censor::pack::write_pack(&[Vulgar::new(String::from("clipacked"), None)]),
+        )
+        .unwrap();
+        let config = dir.join("little-censor.toml");
+        std::fs::write(&config, format!("packs = [{:?}]\n", pack)).unwrap();
+        let options = Options {
+            config: Some(config),
+            words: vec![Vulgar::new(String::from("cliadded"), None)],
+            ..Options::default()
+        };
+        let mut censored = Vec::new();
+
+        Scrubber::new(&options)
+            .unwrap()
+            .copy(&b"clipacked cliadded"[..], &mut censored, None)
+            .unwrap();
+
+        assert_eq!(censored, b"c******** c*******");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! `scrub` subcommand censoring files matching glob patterns
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use clap::Args;
+use little_censor::error::Error;
+
+use crate::{io_error, Format, Options, Scrubber};
+
+/// Censors files matching glob patterns, prints a summary of redactions per file
+#[derive(Debug, Args)]
+pub(crate) struct Scrub {
+    /// Glob patterns of files, e.g. 'logs/**/*.log'
+    #[arg(required = true)]
+    patterns: Vec<String>,
+    /// Writes censored files back instead of printing them to stdout
+    #[arg(long)]
+    in_place: bool,
+    /// Keeps originals of edited files with an added `.bak` extension
+    #[arg(long, requires = "in_place")]
+    backup: bool,
+    /// Number of files censored at once, number of CPUs by default
+    #[arg(long, short)]
+    jobs: Option<usize>,
+    #[command(flatten)]
+    options: Options,
+}
+
+/// Censored file, output is empty when it was edited in place
+struct Scrubbed {
+    redactions: usize,
+    output: Vec<u8>,
+}
+
+impl Scrub {
+    pub(crate) fn run(self) -> Result<(), String> {
+        if self.in_place && self.options.format == Format::Json {
+            return Err(String::from("JSON output can't be written in place"));
+        }
+        let files = self.files()?;
+        let scrubber = Scrubber::new(&self.options)?;
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1)
+            .clamp(1, files.len().max(1));
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(files.len()));
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let path = match files.get(index) {
+                        Some(path) => path,
+                        None => return,
+                    };
+                    let result = self.scrub(&scrubber, path);
+                    results
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push((index, result));
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+        results.sort_by_key(|(index, _)| *index);
+
+        let mut stdout = io::stdout().lock();
+        let mut total = 0;
+        let mut failed = 0;
+        for (index, result) in results {
+            let path = files[index].display();
+            match result {
+                Ok(scrubbed) => {
+                    stdout
+                        .write_all(&scrubbed.output)
+                        .map_err(|error| error.to_string())?;
+                    eprintln!("{path}: {} redactions", scrubbed.redactions);
+                    total += scrubbed.redactions;
+                }
+                Err(error) => {
+                    eprintln!("{path}: {error}");
+                    failed += 1;
+                }
+            }
+        }
+        eprintln!("{total} redactions in {} files", files.len() - failed);
+        match failed {
+            0 => Ok(()),
+            _ => Err(format!("{failed} files couldn't be censored")),
+        }
+    }
+
+    /// Files matching any of the patterns, without duplicates
+    fn files(&self) -> Result<Vec<PathBuf>, String> {
+        let mut files = Vec::new();
+        for pattern in &self.patterns {
+            let paths = glob::glob(pattern).map_err(|error| format!("{pattern}: {error}"))?;
+            for path in paths.flatten() {
+                if path.is_file() && !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn scrub(&self, scrubber: &Scrubber, path: &Path) -> Result<Scrubbed, Error> {
+        let reader = BufReader::new(File::open(path).map_err(|error| io_error(error, Some(path)))?);
+        if !self.in_place {
+            let mut output = Vec::new();
+            let redactions = scrubber.copy(reader, &mut output, Some(path))?;
+            return Ok(Scrubbed { redactions, output });
+        }
+
+        // Written next to the file, so it's replaced by renaming
+        let temporary = with_suffix(path, ".little-censor");
+        let result = File::create(&temporary)
+            .map_err(|error| io_error(error, Some(&temporary)))
+            .and_then(|file| scrubber.copy(reader, BufWriter::new(file), Some(path)));
+        let redactions = match result {
+            Ok(0) | Err(_) => {
+                let _ = fs::remove_file(&temporary);
+                return result.map(|redactions| Scrubbed {
+                    redactions,
+                    output: Vec::new(),
+                });
+            }
+            Ok(redactions) => redactions,
+        };
+
+        let permissions = fs::metadata(path)
+            .map_err(|error| io_error(error, Some(path)))?
+            .permissions();
+        fs::set_permissions(&temporary, permissions)
+            .map_err(|error| io_error(error, Some(&temporary)))?;
+        if self.backup {
+            fs::rename(path, with_suffix(path, ".bak"))
+                .map_err(|error| io_error(error, Some(path)))?;
+        }
+        fs::rename(&temporary, path).map_err(|error| io_error(error, Some(path)))?;
+        Ok(Scrubbed {
+            redactions,
+            output: Vec::new(),
+        })
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_place_with_backup() {
+        let dir = std::env::temp_dir().join(format!("little-censor-scrub-{}", std::process::id()));
+        fs::create_dir_all(dir.join("logs")).unwrap();
+        fs::write(dir.join("logs/app.log"), "fuck\nfine\n").unwrap();
+        fs::write(dir.join("logs/clean.log"), "fine\n").unwrap();
+
+        let scrub = Scrub {
+            patterns: vec![format!("{}/**/*.log", dir.display())],
+            in_place: true,
+            backup: true,
+            jobs: Some(2),
+            options: Options::default(),
+        };
+        scrub.run().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("logs/app.log")).unwrap(),
+            "f***\nfine\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("logs/app.log.bak")).unwrap(),
+            "fuck\nfine\n"
+        );
+        assert!(!dir.join("logs/clean.log.bak").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+//! `scrub-file` subcommand censoring a large file into a new one
+
+use std::path::PathBuf;
+
+use clap::Args;
+use little_censor::json::JsonOptions;
+use little_censor::mmap::{FileScrubber, DEFAULT_CHUNK_SIZE};
+
+use crate::{Format, Options, Scrubber};
+
+/// Censors a large plain text file into a new one using every CPU, prints number of
+/// redactions
+#[derive(Debug, Args)]
+pub(crate) struct ScrubFile {
+    /// File censored, it must not change while it's read
+    input: PathBuf,
+    /// New file with censored lines
+    output: PathBuf,
+    /// Number of chunks censored at once, number of CPUs by default
+    #[arg(long, short)]
+    jobs: Option<usize>,
+    /// Size of chunks in bytes, extended to the end of their last line
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_CHUNK_SIZE)]
+    chunk_size: usize,
+    #[command(flatten)]
+    options: Options,
+}
+
+impl ScrubFile {
+    pub(crate) fn run(self) -> Result<(), String> {
+        if self.options.format == Format::Json {
+            return Err(String::from("JSON output isn't supported by scrub-file"));
+        }
+        let scrubber = Scrubber::new(&self.options)?;
+        let mut options = JsonOptions::new()
+            .with_censor(scrubber.censor)
+            .with_types(scrubber.types.iter().copied());
+        if let Some(arg) = scrubber.arg {
+            options = options.with_arg(arg);
+        }
+        let mut file_scrubber = FileScrubber::new(options).with_chunk_size(self.chunk_size);
+        if let Some(jobs) = self.jobs {
+            file_scrubber = file_scrubber.with_jobs(jobs);
+        }
+
+        let redactions = file_scrubber
+            .scrub(&self.input, &self.output)
+            .map_err(|error| format!("{}: {error}", self.input.display()))?;
+        eprintln!("{}: {redactions} redactions", self.output.display());
+        Ok(())
+    }
+}
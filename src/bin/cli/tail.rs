@@ -0,0 +1,169 @@
+//! `tail` subcommand printing censored lines of a growing file
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use clap::Args;
+use little_censor::error::Error;
+
+use crate::{io_error, Options, Scrubber};
+
+/// How often a followed file is checked for new lines
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Prints censored last lines of a file, and lines appended to it with `-f`
+#[derive(Debug, Args)]
+pub(crate) struct Tail {
+    /// File to print
+    file: PathBuf,
+    /// Keeps printing lines appended to the file, also after it was truncated or rotated
+    #[arg(short, long)]
+    follow: bool,
+    /// Number of lines printed from the end of the file
+    #[arg(short = 'n', long, default_value_t = 10)]
+    lines: usize,
+    #[command(flatten)]
+    options: Options,
+}
+
+impl Tail {
+    pub(crate) fn run(self) -> Result<(), String> {
+        let scrubber = Scrubber::new(&self.options)?;
+        let mut follower = Follower::new(&self.file);
+        let mut stdout = io::stdout().lock();
+
+        // Unfinished last line is printed only when it can't be continued
+        follower
+            .start(&scrubber, &mut stdout, self.lines, !self.follow)
+            .map_err(|error| format!("{}: {error}", self.file.display()))?;
+        if !self.follow {
+            return Ok(());
+        }
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            follower
+                .poll(&scrubber, &mut stdout)
+                .map_err(|error| format!("{}: {error}", self.file.display()))?;
+        }
+    }
+}
+
+/// Position in a followed file
+struct Follower<'a> {
+    path: &'a Path,
+    position: u64,
+    /// Start of a line which wasn't finished yet
+    pending: Vec<u8>,
+    /// Number of the last printed line
+    number: usize,
+}
+
+impl<'a> Follower<'a> {
+    fn new(path: &'a Path) -> Self {
+        Self {
+            path,
+            position: 0,
+            pending: Vec::new(),
+            number: 0,
+        }
+    }
+
+    /// Prints the last `lines` lines of the file
+    fn start(
+        &mut self,
+        scrubber: &Scrubber,
+        writer: &mut impl Write,
+        lines: usize,
+        finish: bool,
+    ) -> Result<(), Error> {
+        let data = self.read()?;
+        let total = data.split_inclusive(|&byte| byte == b'\n').count();
+        let skipped = total.saturating_sub(lines);
+        let start = data
+            .split_inclusive(|&byte| byte == b'\n')
+            .take(skipped)
+            .map(<[u8]>::len)
+            .sum();
+        self.number = skipped;
+        self.print(scrubber, writer, &data[start..], finish)
+    }
+
+    /// Prints lines appended since the last call
+    fn poll(&mut self, scrubber: &Scrubber, writer: &mut impl Write) -> Result<(), Error> {
+        let data = self.read()?;
+        self.print(scrubber, writer, &data, false)
+    }
+
+    /// Reads the file from the last position, from its start if it got shorter
+    fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let path = Some(self.path);
+        let mut file = File::open(self.path).map_err(|error| io_error(error, path))?;
+        let len = file
+            .metadata()
+            .map_err(|error| io_error(error, path))?
+            .len();
+        if len < self.position {
+            self.position = 0;
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(self.position))
+            .map_err(|error| io_error(error, path))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|error| io_error(error, path))?;
+        self.position += data.len() as u64;
+        Ok(data)
+    }
+
+    /// Prints finished lines, the last one only when `finish` is set
+    fn print(
+        &mut self,
+        scrubber: &Scrubber,
+        writer: &mut impl Write,
+        data: &[u8],
+        finish: bool,
+    ) -> Result<(), Error> {
+        self.pending.extend_from_slice(data);
+        let end = match self.pending.iter().rposition(|&byte| byte == b'\n') {
+            Some(end) => end + 1,
+            None if finish => self.pending.len(),
+            None => return Ok(()),
+        };
+        let lines: Vec<u8> = self.pending.drain(..end).collect();
+        for line in String::from_utf8_lossy(&lines).split_inclusive('\n') {
+            self.number += 1;
+            let (censored, _) = scrubber.line(line, self.number, Some(self.path))?;
+            writer.write_all(censored.as_bytes())?;
+        }
+        Ok(writer.flush()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+
+    use super::*;
+
+    #[test]
+    fn follows_appended_lines() {
+        let path =
+            std::env::temp_dir().join(format!("little-censor-tail-{}.log", std::process::id()));
+        fs::write(&path, "first\nfuck\n").unwrap();
+        let scrubber = Scrubber::new(&Options::default()).unwrap();
+        let mut follower = Follower::new(&path);
+        let mut output = Vec::new();
+
+        follower.start(&scrubber, &mut output, 1, false).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"fuck again\nunfini").unwrap();
+        follower.poll(&scrubber, &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "f***\nf*** again\n");
+        assert_eq!(follower.pending, b"unfini");
+        fs::remove_file(path).unwrap();
+    }
+}
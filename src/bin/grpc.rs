@@ -0,0 +1,26 @@
+//! gRPC moderation server, listens on the address given as the first argument
+//! or `127.0.0.1:50051` by default
+//!
+//! `AddWords` calls are accepted with the token of the `LITTLE_CENSOR_TOKEN` variable.
+
+use little_censor::grpc::proto::moderation_server::ModerationServer;
+use little_censor::grpc::ModerationService;
+use tonic::transport::Server;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("127.0.0.1:50051"))
+        .parse()?;
+
+    let mut service = ModerationService::new();
+    if let Ok(token) = std::env::var("LITTLE_CENSOR_TOKEN") {
+        service = service.with_token(token);
+    }
+    Server::builder()
+        .add_service(ModerationServer::new(service))
+        .serve(address)
+        .await?;
+    Ok(())
+}
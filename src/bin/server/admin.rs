@@ -0,0 +1,157 @@
+//! Endpoints for operators, authenticated with `server.admin_token` of the configuration
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use little_censor::snapshot::ConfigSnapshot;
+use little_censor::type_name;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::ApiError;
+use crate::AppState;
+
+pub(crate) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/reload", post(reload))
+        .route("/words", get(words))
+        .route("/snapshot", get(snapshot))
+}
+
+/// Rejects requests without the configured bearer token, all of them when there's none
+pub(crate) fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let settings = state.settings();
+    let token = match &settings.admin_token {
+        Some(token) => token,
+        None => {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "Admin endpoints are disabled without server.admin_token",
+                "ADMIN_DISABLED",
+            ))
+        }
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match given {
+        Some(given) if same(given.as_bytes(), token.as_bytes()) => Ok(()),
+        _ => Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "Invalid admin token",
+            "UNAUTHORIZED",
+        )),
+    }
+}
+
+/// Compares in time independent of where the tokens differ
+fn same(given: &[u8], token: &[u8]) -> bool {
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Reads the configuration and word packs again, requests in flight keep the old settings
+pub(crate) async fn reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize(&state, &headers)?;
+    state.reload().await?;
+    Ok(Json(json!({ "reloaded": true })))
+}
+
+/// Words added with `PUT /words`, with their types
+pub(crate) async fn words(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    authorize(&state, &headers)?;
+    let words: Vec<_> = state
+        .words
+        .load()
+        .await?
+        .into_iter()
+        .map(|vulgar| json!({ "word": vulgar.word, "type": type_name::format(vulgar.word_type) }))
+        .collect();
+    Ok(Json(json!(words)))
+}
+
+/// Query of `GET /admin/snapshot`
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SnapshotQuery {
+    /// Replaces words by their fingerprints
+    #[serde(default)]
+    hashed: bool,
+}
+
+/// Active configuration and word list, for audits of which policy processed messages
+pub(crate) async fn snapshot(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Json<ConfigSnapshot>, ApiError> {
+    authorize(&state, &headers)?;
+    let settings = state.settings();
+    let mut snapshot = settings
+        .censor
+        .snapshot()
+        .with_rules(settings.rules.iter().copied());
+    if query.hashed {
+        snapshot = snapshot.with_hashed_words();
+    }
+    Ok(Json(snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::http::HeaderValue;
+    use little_censor::config::Config;
+    use little_censor::store::MemoryStore;
+
+    use super::*;
+    use crate::Settings;
+
+    #[tokio::test]
+    async fn reload_requires_token() {
+        let path = std::env::temp_dir().join(format!("little-censor-{}.toml", std::process::id()));
+        std::fs::write(&path, "[server]\nadmin_token = \"secret\"\n").unwrap();
+        let config = Config::load(&path).unwrap();
+        let state = AppState::new(
+            Settings::new(&config).unwrap(),
+            Arc::new(MemoryStore::default()),
+        )
+        .with_config(&path);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer wrong"),
+        );
+        let error = reload(State(state.clone()), headers.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+
+        std::fs::write(
+            &path,
+            "replacement = \"#\"\n[server]\nadmin_token = \"secret\"\n",
+        )
+        .unwrap();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let Json(response) = reload(State(state.clone()), headers).await.unwrap();
+        assert_eq!(response["reloaded"], true);
+        assert_eq!(state.settings().censor.replacement(), '#');
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+//! Routes of the moderation API
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{post, put};
+use axum::{Json, Router};
+use little_censor::censor::{CensorTypes, Censored};
+use little_censor::config::parse_rule;
+use little_censor::error::Error;
+use little_censor::{type_name, Type, Vulgar};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{admin, health, AppState, ReloadError};
+
+pub(crate) fn router(state: AppState) -> Router {
+    let router = Router::new()
+        .route("/censor", post(censor))
+        .route("/analyze", post(analyze))
+        .route("/words", put(add_words))
+        .nest("/admin", admin::routes())
+        .merge(health::routes());
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", axum::routing::get(metrics));
+    router.with_state(state)
+}
+
+/// Parses names of additional types of censoring, e.g. `"link"`
+fn rules(names: &[String]) -> Result<Box<[CensorTypes]>, String> {
+    names
+        .iter()
+        .map(|name| parse_rule(name).ok_or_else(|| format!("Censor type {name} doesn't exist")))
+        .collect()
+}
+
+/// Error answered with its message and machine readable code
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    pub(crate) status: StatusCode,
+    message: String,
+    code: &'static str,
+}
+
+impl ApiError {
+    pub(crate) fn new(status: StatusCode, message: impl Into<String>, code: &'static str) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            code,
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        let status = match error {
+            Error::BudgetExceeded => StatusCode::TOO_MANY_REQUESTS,
+            Error::InputTooLong { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::Storage | Error::Backend(_) | Error::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        Self {
+            status,
+            message: error.to_string(),
+            code: error.code(),
+        }
+    }
+}
+
+impl From<ReloadError> for ApiError {
+    fn from(error: ReloadError) -> Self {
+        match error {
+            ReloadError::Config(error) => Self::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                error.to_string(),
+                "INVALID_CONFIG",
+            ),
+            ReloadError::Store(error) => error.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({ "error": self.message, "code": self.code });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CensorRequest {
+    text: String,
+    /// Rules of the configuration are used without them
+    rules: Option<Vec<String>>,
+    /// Pattern of the `custom` rule, the configured one is used without it
+    arg: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Span {
+    start: usize,
+    end: usize,
+    kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct CensorResponse {
+    censored: String,
+    valid: bool,
+    truncated: bool,
+    pii_redacted: bool,
+    profanity_censored: bool,
+    #[serde(rename = "type")]
+    typ: String,
+    spans: Vec<Span>,
+    verdict: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct AnalyzeResponse {
+    valid: bool,
+    pii_redacted: bool,
+    profanity_censored: bool,
+    #[serde(rename = "type")]
+    typ: String,
+    spans: Vec<Span>,
+    verdict: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Word {
+    word: String,
+    /// Type written like `"SEXUAL|SEVERE"`
+    #[serde(rename = "type")]
+    typ: Option<String>,
+}
+
+fn censor_request(state: &AppState, request: CensorRequest) -> Result<Censored, ApiError> {
+    let settings = state.settings();
+    let types = match &request.rules {
+        Some(names) => rules(names)
+            .map_err(|message| ApiError::new(StatusCode::BAD_REQUEST, message, "UNKNOWN_RULE"))?,
+        None => settings.rules.clone(),
+    };
+    let arg = request.arg.or_else(|| settings.custom.clone());
+    Ok(settings.censor.censor(request.text, types, arg)?)
+}
+
+fn spans(censored: &Censored) -> Vec<Span> {
+    censored
+        .spans()
+        .iter()
+        .map(|span| Span {
+            start: span.start,
+            end: span.end,
+            kind: span.kind.name(),
+        })
+        .collect()
+}
+
+fn verdict(censored: &Censored) -> String {
+    format!("{:?}", censored.verdict()).to_lowercase()
+}
+
+pub(crate) async fn censor(
+    State(state): State<AppState>,
+    Json(request): Json<CensorRequest>,
+) -> Result<Json<CensorResponse>, ApiError> {
+    let censored = censor_request(&state, request)?;
+    Ok(Json(CensorResponse {
+        censored: censored.censored().to_owned(),
+        valid: censored.valid(),
+        truncated: censored.truncated(),
+        pii_redacted: censored.pii_redacted(),
+        profanity_censored: censored.profanity_censored(),
+        typ: type_name::format(censored.typ()),
+        spans: spans(&censored),
+        verdict: verdict(&censored),
+    }))
+}
+
+pub(crate) async fn analyze(
+    State(state): State<AppState>,
+    Json(request): Json<CensorRequest>,
+) -> Result<Json<AnalyzeResponse>, ApiError> {
+    let censored = censor_request(&state, request)?;
+    Ok(Json(AnalyzeResponse {
+        valid: censored.valid(),
+        pii_redacted: censored.pii_redacted(),
+        profanity_censored: censored.profanity_censored(),
+        typ: type_name::format(censored.typ()),
+        spans: spans(&censored),
+        verdict: verdict(&censored),
+    }))
+}
+
+/// Adds words to the store and swaps in a word list with them, authenticated like the
+/// admin endpoints
+pub(crate) async fn add_words(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(words): Json<Vec<Word>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    admin::authorize(&state, &headers)?;
+    let mut vulgars = Vec::with_capacity(words.len());
+    for (index, word) in words.into_iter().enumerate() {
+        if word.word.is_empty() {
+            return Err(Error::EmptyWord { index }.into());
+        }
+        let word_type = match &word.typ {
+            Some(name) => type_name::parse(name)?,
+            None => Type::default(),
+        };
+        vulgars.push(Vulgar::new(word.word, Some(word_type)));
+    }
+    let added = vulgars.len();
+    state.words.save(vulgars).await?;
+    state.reload_words().await?;
+    Ok(Json(json!({ "added": added })))
+}
+
+/// Prometheus counters of the censor
+#[cfg(feature = "metrics")]
+pub(crate) async fn metrics() -> impl IntoResponse {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4",
+        )],
+        little_censor::metrics(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::http::{header, HeaderValue};
+    use little_censor::config::ServerConfig;
+    use little_censor::store::MemoryStore;
+
+    use crate::{Config, Settings};
+
+    fn request(text: &str, rules: Option<Vec<String>>) -> Json<CensorRequest> {
+        Json(CensorRequest {
+            text: text.to_owned(),
+            rules,
+            arg: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn censor_with_configured_rules() {
+        let config = Config {
+            rules: vec![CensorTypes::Email],
+            replacement: Some('#'),
+            ..Config::default()
+        };
+        let state = AppState::new(
+            Settings::new(&config).unwrap(),
+            Arc::new(MemoryStore::default()),
+        );
+
+        let Json(response) = censor(State(state.clone()), request("fuck me@example.net", None))
+            .await
+            .unwrap();
+        assert_eq!(response.censored, "f### ##############");
+
+        let error = analyze(
+            State(state),
+            request("text", Some(vec![String::from("phone")])),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn added_words_require_token() {
+        let config = Config {
+            server: ServerConfig {
+                admin_token: Some(String::from("secret")),
+                ..ServerConfig::default()
+            },
+            ..Config::default()
+        };
+        let state = AppState::new(
+            Settings::new(&config).unwrap(),
+            Arc::new(MemoryStore::default()),
+        );
+        let words = || {
+            Json(vec![Word {
+                word: String::from("sidecarword"),
+                typ: None,
+            }])
+        };
+
+        let error = add_words(State(state.clone()), HeaderMap::new(), words())
+            .await
+            .unwrap_err();
+        assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let Json(response) = add_words(State(state.clone()), headers, words())
+            .await
+            .unwrap();
+        assert_eq!(response["added"], 1);
+        let Json(response) = censor(State(state), request("a sidecarword", None))
+            .await
+            .unwrap();
+        assert_eq!(response.censored, "a s**********");
+    }
+}
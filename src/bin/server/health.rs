@@ -0,0 +1,134 @@
+//! Probes of orchestrators, readiness censors sample messages with the current word lists
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use little_censor::censor::CensorTypes;
+use little_censor::Type;
+use serde_json::{json, Map, Value};
+
+use crate::{AppState, Settings};
+
+pub(crate) fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+}
+
+/// Answers as long as the server runs, restarting wouldn't help with a broken word list
+pub(crate) async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Fails while the self-check does, so no traffic is routed to this instance
+pub(crate) async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let checks = self_check(&state.settings());
+    let ready = checks.iter().all(|(_, result)| result.is_ok());
+    let checks: Map<String, Value> = checks
+        .into_iter()
+        .map(|(name, result)| {
+            let value = match result {
+                Ok(()) => json!("ok"),
+                Err(error) => json!(error),
+            };
+            (name.to_owned(), value)
+        })
+        .collect();
+    if ready {
+        (
+            StatusCode::OK,
+            Json(json!({ "status": "ready", "checks": checks })),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "unavailable", "checks": checks })),
+        )
+    }
+}
+
+/// Censors sample messages, each check fails with the reason
+///
+/// The probe of the censor is used, so strict mode, the budget or allowed domains of the
+/// configuration don't fail the samples, and they don't reach hooks, statistics or metrics.
+fn self_check(settings: &Settings) -> Vec<(&'static str, Result<(), String>)> {
+    let probe = settings.censor.probe();
+    let censor = |text: &str, types: Box<[CensorTypes]>, arg: Option<String>| {
+        probe
+            .censor(text.to_owned(), types, arg)
+            .map_err(|error| error.to_string())
+    };
+
+    let words = censor("fuck", Box::new([]), None).and_then(|censored| {
+        if censored.typ().is(Type::PROFANE) {
+            Ok(())
+        } else {
+            Err(String::from("Sample profanity wasn't recognized"))
+        }
+    });
+    let rules = censor(
+        "https://example.net me@example.net 127.0.0.1",
+        Box::new([CensorTypes::Link, CensorTypes::Email, CensorTypes::IP]),
+        None,
+    )
+    .and_then(|censored| match censored.rules().len() {
+        3 => Ok(()),
+        _ => Err(String::from("Sample link, email or IP wasn't masked")),
+    });
+    let mut checks = vec![("words", words), ("rules", rules)];
+    if let Some(pattern) = &settings.custom {
+        let custom = censor("", Box::new([CensorTypes::Custom]), Some(pattern.clone()));
+        checks.push(("custom", custom.map(drop)));
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use little_censor::budget::Budget;
+    use little_censor::config::Config;
+    use little_censor::store::MemoryStore;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn ready_with_default_settings() {
+        let state = AppState::new(
+            Settings::new(&Config::default()).unwrap(),
+            Arc::new(MemoryStore::default()),
+        );
+
+        let (status, Json(body)) = readyz(State(state)).await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["checks"]["words"], "ok");
+        assert_eq!(body["checks"]["rules"], "ok");
+    }
+
+    #[tokio::test]
+    async fn ready_with_strict_settings() {
+        let settings = Settings::new(&Config::default()).unwrap();
+        let censor = settings
+            .censor
+            .clone()
+            .with_strict(true)
+            .with_stats(true)
+            .with_budget(Budget::new().with_max_length(4))
+            .with_allowed_domains(["example.net"]);
+        let state = AppState::new(
+            Settings {
+                censor: censor.clone(),
+                ..settings
+            },
+            Arc::new(MemoryStore::default()),
+        );
+
+        let (status, Json(body)) = readyz(State(state)).await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(censor.stats().unwrap().messages, 0);
+    }
+}
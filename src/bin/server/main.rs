@@ -0,0 +1,247 @@
+//! HTTP moderation server, a sidecar for services which aren't written in Rust
+//!
+//! Every request is censored with one shared censor, whose configuration is reloaded
+//! when its file changes.
+
+mod admin;
+mod api;
+mod health;
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use clap::Parser;
+use little_censor::censor::{Censor, CensorTypes};
+use little_censor::config::{Config, ConfigError, FILE_NAME};
+use little_censor::error::Error;
+use little_censor::store::{FileStore, MemoryStore, WordStore};
+use little_censor::Vulgar;
+
+/// How often the configuration file is checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Serves `POST /censor`, `POST /analyze`, `PUT /words` and `/admin` endpoints
+#[derive(Debug, Parser)]
+#[command(name = "little-censor-server", version, about)]
+struct Args {
+    /// Address the server listens on, `server.address` of the configuration or 127.0.0.1:8080
+    #[arg(long)]
+    address: Option<SocketAddr>,
+    /// Configuration file reloaded when it changes, `little-censor.toml` of the working
+    /// directory by default
+    #[arg(long)]
+    config: Option<PathBuf>,
+}
+
+/// Censor shared by every request
+#[derive(Debug, Clone)]
+struct Settings {
+    censor: Censor,
+    rules: Box<[CensorTypes]>,
+    custom: Option<String>,
+    admin_token: Option<String>,
+    /// Configuration the settings were built from, for rebuilding them with new words
+    config: Config,
+}
+
+impl Settings {
+    /// Settings without stored words
+    #[cfg(test)]
+    fn new(config: &Config) -> Result<Self, ConfigError> {
+        Self::with_words(config, Vec::new())
+    }
+
+    /// Settings whose word list has the stored words in addition to the packs
+    fn with_words(config: &Config, words: Vec<Vulgar>) -> Result<Self, ConfigError> {
+        Ok(Self {
+            censor: config.censor_with_words(words)?,
+            rules: config.rules.clone().into_boxed_slice(),
+            custom: config.custom.clone(),
+            admin_token: config.server.admin_token.clone(),
+            config: config.clone(),
+        })
+    }
+}
+
+/// Why settings couldn't be rebuilt, the current ones are kept
+#[derive(Debug)]
+enum ReloadError {
+    Config(ConfigError),
+    Store(Error),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(error) => error.fmt(f),
+            Self::Store(error) => write!(f, "server.store: {error}"),
+        }
+    }
+}
+
+impl From<ConfigError> for ReloadError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+impl From<Error> for ReloadError {
+    fn from(error: Error) -> Self {
+        Self::Store(error)
+    }
+}
+
+/// State of the server, settings are swapped as a whole so requests in flight keep theirs
+#[derive(Clone)]
+struct AppState {
+    settings: Arc<RwLock<Arc<Settings>>>,
+    /// Store of words added with `PUT /words`
+    words: Arc<dyn WordStore>,
+    /// Held while settings are rebuilt, so the last rebuild sees every stored word
+    rebuilding: Arc<tokio::sync::Mutex<()>>,
+    /// Configuration file settings are reloaded from
+    config: Option<Arc<Path>>,
+}
+
+impl AppState {
+    fn new(settings: Settings, words: Arc<dyn WordStore>) -> Self {
+        Self {
+            settings: Arc::new(RwLock::new(Arc::new(settings))),
+            words,
+            rebuilding: Arc::default(),
+            config: None,
+        }
+    }
+
+    fn with_config(mut self, path: &Path) -> Self {
+        self.config = Some(Arc::from(path));
+        self
+    }
+
+    fn settings(&self) -> Arc<Settings> {
+        Arc::clone(&self.settings.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn replace(&self, settings: Settings) {
+        *self.settings.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(settings);
+    }
+
+    /// Reads the configuration, its word packs and the stored words again, keeps current
+    /// settings if anything is invalid
+    async fn reload(&self) -> Result<(), ReloadError> {
+        let _rebuilding = self.rebuilding.lock().await;
+        let config = Config::discover(self.config.as_deref())?;
+        let words = self.words.load().await?;
+        self.replace(Settings::with_words(&config, words)?);
+        Ok(())
+    }
+
+    /// Builds a new word list with the stored words and swaps it in, e.g. after words
+    /// were added
+    async fn reload_words(&self) -> Result<(), ReloadError> {
+        let _rebuilding = self.rebuilding.lock().await;
+        let words = self.words.load().await?;
+        self.replace(Settings::with_words(&self.settings().config, words)?);
+        Ok(())
+    }
+}
+
+/// Store of words at the location of `server.store`, chosen by its form
+fn open_store(location: Option<&str>) -> Result<Arc<dyn WordStore>, String> {
+    match location {
+        None => Ok(Arc::new(MemoryStore::default())),
+        #[cfg(feature = "redis")]
+        Some(url) if url.starts_with("redis://") => {
+            let words = little_censor::redis_store::RedisWords::new(url)
+                .map_err(|error| format!("server.store: {url}: {error}"))?;
+            Ok(Arc::new(words))
+        }
+        #[cfg(feature = "sqlite")]
+        Some(path) if path.ends_with(".db") => {
+            let store = little_censor::sqlite::SqliteStore::open(path)
+                .map_err(|error| format!("server.store: {path}: {error}"))?;
+            Ok(Arc::new(store))
+        }
+        Some(location) if location.starts_with("redis://") || location.ends_with(".db") => {
+            Err(format!(
+                "server.store: {location} needs the server built with the redis or sqlite feature"
+            ))
+        }
+        Some(dir) => Ok(Arc::new(FileStore::new(dir))),
+    }
+}
+
+/// Rebuilds the word list whenever another instance changes the shared one
+#[cfg(feature = "redis")]
+fn subscribe(state: &AppState, url: &str) -> Result<(), Error> {
+    let runtime = tokio::runtime::Handle::current();
+    let state = state.clone();
+    little_censor::redis_store::RedisWords::new(url)?.subscribe_with(move || {
+        let state = state.clone();
+        runtime.spawn(async move {
+            if let Err(error) = state.reload_words().await {
+                eprintln!("Keeping previous word list, {error}");
+            }
+        });
+    });
+    Ok(())
+}
+
+/// Reloads settings whenever the file is modified, keeps the old ones if it's invalid
+async fn watch(state: AppState, path: PathBuf) {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    let mut last: Option<SystemTime> = modified(&path);
+    loop {
+        tokio::time::sleep(RELOAD_INTERVAL).await;
+        let current = modified(&path);
+        if current == last {
+            continue;
+        }
+        last = current;
+        match state.reload().await {
+            Ok(()) => eprintln!("Reloaded {}", path.display()),
+            Err(error) => eprintln!("Keeping previous configuration, {error}"),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let path = args
+        .config
+        .or_else(|| Some(PathBuf::from(FILE_NAME)).filter(|path| path.is_file()));
+    let config = Config::discover(path.as_deref())?;
+    let address = args
+        .address
+        .or(config.server.address)
+        .unwrap_or(SocketAddr::from(([127, 0, 0, 1], 8080)));
+
+    // The store is opened once, reloading the configuration doesn't change it
+    let words = open_store(config.server.store.as_deref())?;
+    let stored = words.load().await?;
+    let mut state = AppState::new(Settings::with_words(&config, stored)?, words);
+    #[cfg(feature = "redis")]
+    if let Some(url) = config.server.store.as_deref() {
+        if url.starts_with("redis://") {
+            subscribe(&state, url)?;
+        }
+    }
+    if let Some(path) = path {
+        state = state.with_config(&path);
+        tokio::spawn(watch(state.clone(), path));
+    }
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    eprintln!("Listening on {address}");
+    axum::serve(listener, api::router(state)).await?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Censoring HTTP bodies, shared by the `tower` and `actix` middleware
+
+use crate::json::{censor_json, JsonOptions};
+
+/// Format of a censored body
+pub(crate) enum Content {
+    Json,
+    Text,
+}
+
+impl Content {
+    /// Format of bodies with this content type, `None` if they aren't censored
+    pub(crate) fn of(content_type: &str) -> Option<Self> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        if mime == "application/json" || mime.ends_with("+json") {
+            Some(Self::Json)
+        } else if mime.starts_with("text/") {
+            Some(Self::Text)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a body couldn't be censored
+pub(crate) enum BodyError {
+    /// The body isn't UTF-8 or valid JSON
+    Invalid,
+    /// Censoring failed
+    Censor,
+}
+
+/// Censored body, `None` if nothing was censored
+pub(crate) fn censor_body(
+    content: Content,
+    bytes: &[u8],
+    options: &JsonOptions,
+) -> Result<Option<String>, BodyError> {
+    let text = std::str::from_utf8(bytes).map_err(|_| BodyError::Invalid)?;
+    match content {
+        Content::Json => {
+            let mut value: serde_json::Value =
+                serde_json::from_str(text).map_err(|_| BodyError::Invalid)?;
+            let report = censor_json(&mut value, options).map_err(|_| BodyError::Censor)?;
+            Ok((!report.paths.is_empty()).then(|| value.to_string()))
+        }
+        Content::Text => {
+            let censored = options
+                .censor(text.to_owned())
+                .map_err(|_| BodyError::Censor)?;
+            Ok(censored.changed().then_some(censored.censored))
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Limits on the work done for a single sentence
+
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+/// What to do when any of the budget limits is reached
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnExceed {
+    /// Fail with `Error::BudgetExceeded`, or `Error::InputTooLong` above the maximum length
+    #[default]
+    Error,
+    /// Stop the analysis and return what was censored so far
+    Truncate,
+}
+
+/// Upper bounds for processing a single sentence
+///
+/// Every limit is optional, default budget is unlimited.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use little_censor::budget::{Budget, OnExceed};
+///
+/// let budget = Budget::new()
+///     .with_max_length(64 * 1024)
+///     .with_max_matches(256)
+///     .with_max_time(Duration::from_millis(5))
+///     .with_on_exceed(OnExceed::Truncate);
+///
+/// assert_eq!(budget.max_length, Some(64 * 1024));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Budget {
+    /// Maximum length of the sentence in bytes
+    pub max_length: Option<usize>,
+    /// Maximum number of regex matches masked in a sentence
+    pub max_matches: Option<usize>,
+    /// Maximum wall time spent on a sentence
+    ///
+    /// Not supported on `wasm32-unknown-unknown` which has no clock.
+    pub max_time: Option<Duration>,
+    /// Behaviour after reaching any of the limits
+    pub on_exceed: OnExceed,
+}
+
+impl Budget {
+    /// Creates unlimited budget
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets maximum length of the sentence in bytes
+    pub fn with_max_length(mut self, bytes: usize) -> Self {
+        self.max_length = Some(bytes);
+        self
+    }
+
+    /// Sets maximum number of masked regex matches
+    pub fn with_max_matches(mut self, matches: usize) -> Self {
+        self.max_matches = Some(matches);
+        self
+    }
+
+    /// Sets maximum time spent on a sentence
+    pub fn with_max_time(mut self, time: Duration) -> Self {
+        self.max_time = Some(time);
+        self
+    }
+
+    /// Sets behaviour after reaching any of the limits
+    pub fn with_on_exceed(mut self, on_exceed: OnExceed) -> Self {
+        self.on_exceed = on_exceed;
+        self
+    }
+}
+
+/// Budget spent while censoring a single sentence
+pub(crate) struct Tracker<'a> {
+    budget: &'a Budget,
+    started: Option<Instant>,
+    matches: usize,
+    out_of_time: bool,
+    pub(crate) truncated: bool,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) fn new(budget: &'a Budget) -> Self {
+        Self {
+            budget,
+            // Reading the clock panics on targets without one, so only do it when needed
+            started: budget.max_time.map(|_| Instant::now()),
+            matches: 0,
+            out_of_time: false,
+            truncated: false,
+        }
+    }
+
+    /// Returns part of the sentence that fits into the length limit
+    pub(crate) fn limit_length<'s>(&mut self, sentence: &'s str) -> Result<&'s str, Error> {
+        match self.budget.max_length {
+            Some(max) if sentence.len() > max => {
+                if self.budget.on_exceed == OnExceed::Error {
+                    return Err(Error::InputTooLong {
+                        limit: max,
+                        actual: sentence.len(),
+                    });
+                }
+                self.exceed()?;
+                let mut end = max;
+                while !sentence.is_char_boundary(end) {
+                    end -= 1;
+                }
+                Ok(&sentence[..end])
+            }
+            _ => Ok(sentence),
+        }
+    }
+
+    /// Accounts for a single match, returns `false` when it shouldn't be masked anymore
+    pub(crate) fn take_match(&mut self) -> Result<bool, Error> {
+        if matches!(self.budget.max_matches, Some(max) if self.matches >= max) {
+            self.exceed()?;
+            return Ok(false);
+        }
+        self.matches += 1;
+        self.check_time()
+    }
+
+    /// Returns `false` when time budget was used up
+    pub(crate) fn check_time(&mut self) -> Result<bool, Error> {
+        if self.out_of_time {
+            return Ok(false);
+        }
+        match (self.started, self.budget.max_time) {
+            (Some(started), Some(max)) if started.elapsed() > max => {
+                self.exceed()?;
+                self.out_of_time = true;
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn exceed(&mut self) -> Result<(), Error> {
+        match self.budget.on_exceed {
+            OnExceed::Error => Err(Error::BudgetExceeded),
+            OnExceed::Truncate => {
+                self.truncated = true;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_budget() {
+        let budget = Budget::new();
+        let mut tracker = Tracker::new(&budget);
+
+        assert_eq!(tracker.limit_length("ąćę").unwrap(), "ąćę");
+        assert!(tracker.take_match().unwrap());
+        assert!(!tracker.truncated);
+    }
+
+    #[test]
+    fn length_on_char_boundary() {
+        let budget = Budget::new()
+            .with_max_length(3)
+            .with_on_exceed(OnExceed::Truncate);
+        let mut tracker = Tracker::new(&budget);
+
+        assert_eq!(tracker.limit_length("ąćę").unwrap(), "ą");
+        assert!(tracker.truncated);
+    }
+
+    #[test]
+    fn length_exceeded() {
+        let budget = Budget::new().with_max_length(4);
+        let mut tracker = Tracker::new(&budget);
+
+        assert!(matches!(
+            tracker.limit_length("ąćę"),
+            Err(Error::InputTooLong {
+                limit: 4,
+                actual: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn matches_exceeded() {
+        let budget = Budget::new().with_max_matches(1);
+        let mut tracker = Tracker::new(&budget);
+
+        assert!(tracker.take_match().unwrap());
+        assert!(matches!(tracker.take_match(), Err(Error::BudgetExceeded)));
+    }
+
+    #[test]
+    fn time_exceeded() {
+        let budget = Budget::new()
+            .with_max_time(Duration::ZERO)
+            .with_on_exceed(OnExceed::Truncate);
+        let mut tracker = Tracker::new(&budget);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert!(!tracker.check_time().unwrap());
+        assert!(tracker.truncated);
+    }
+}
@@ -0,0 +1,118 @@
+//! Word packs compiled into the program by build scripts, so embedded and wasm builds ship
+//! their word list inside the binary without parsing it at startup
+//!
+//! The build script of the crate depending on little-censor with the `build-helper`
+//! feature under `[build-dependencies]` generates the words:
+//!
+//! ```no_run
+//! // build.rs
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("words.rs");
+//! little_censor::build_helper::embed_pack("words/gaming.lcwp", out).unwrap();
+//! ```
+//!
+//! and the crate includes them as a static slice:
+//!
+//! ```ignore
+//! static WORDS: &[StaticVulgar] = include!(concat!(env!("OUT_DIR"), "/words.rs"));
+//!
+//! little_censor::add_static_words(WORDS)?;
+//! ```
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::pack::read_pack;
+
+/// Rust expression of type `&[StaticVulgar]` with the words of a pack in either format
+///
+/// # Errors
+///
+/// Returns `Error::Parse` if the pack is malformed or `Error::EmptyWord` with its
+/// index if any of the words is empty.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::build_helper::generate;
+/// use little_censor::pack::write_pack;
+/// use little_censor::{Type, Vulgar};
+///
+/// let pack = write_pack(&[Vulgar::new(String::from("noob"), Some(Type::MEAN & Type::MILD))]);
+/// let code = generate(&pack).unwrap();
+///
+/// assert!(code.contains("little_censor::StaticVulgar::new(\"noob\", "));
+/// ```
+pub fn generate(pack: &[u8]) -> Result<String, Error> {
+    let vulgars = read_pack(pack)?;
+    let mut code = String::from("// Generated by little_censor::build_helper, don't edit\n&[\n");
+    for (index, vulgar) in vulgars.iter().enumerate() {
+        if vulgar.word.is_empty() {
+            return Err(Error::EmptyWord { index });
+        }
+        // Debug formatting of a string is a valid Rust string literal
+        writeln!(
+            code,
+            "    little_censor::StaticVulgar::new({:?}, {}, {:?}),",
+            vulgar.word,
+            vulgar.word_type.bits(),
+            vulgar.weight
+        )
+        .expect("Writing to a String doesn't fail");
+    }
+    code.push_str("]\n");
+    Ok(code)
+}
+
+/// Writes Rust of the pack at `pack` into `out`, see [`generate`]
+///
+/// Meant to be called from build scripts, it tells Cargo to run the script again when
+/// the pack changes. `out` is only written when its content changes, so dependents
+/// aren't rebuilt needlessly.
+///
+/// # Errors
+///
+/// Returns `Error::Io` if the pack can't be read or the output written, otherwise the
+/// same errors as [`generate`].
+pub fn embed_pack(pack: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<(), Error> {
+    let pack = pack.as_ref();
+    println!("cargo:rerun-if-changed={}", pack.display());
+    let code = generate(&std::fs::read(pack)?)?;
+    if std::fs::read_to_string(out.as_ref()).ok().as_deref() != Some(code.as_str()) {
+        std::fs::write(out, code)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::write_pack;
+    use crate::{StaticVulgar, Type, Vulgar};
+
+    #[test]
+    fn generated_words_match_pack() {
+        let embedded = [
+            StaticVulgar::new("quote\"d", Type::SEVERE.bits(), None),
+            StaticVulgar::new("kill ~2 yourself", Type::INAPPROPRIATE.bits(), Some(3)),
+        ];
+        let vulgars: Vec<Vulgar> = embedded.iter().map(Vulgar::from).collect();
+
+        assert_eq!(
+            generate(&write_pack(&vulgars)).unwrap(),
+            format!(
+                "// Generated by little_censor::build_helper, don't edit\n&[\n    \
+                 little_censor::StaticVulgar::new(\"quote\\\"d\", {}, None),\n    \
+                 little_censor::StaticVulgar::new(\"kill ~2 yourself\", {}, Some(3)),\n]\n",
+                Type::SEVERE.bits(),
+                Type::INAPPROPRIATE.bits()
+            )
+        );
+        assert!(matches!(
+            generate(&write_pack(&[Vulgar::from(&StaticVulgar::new(
+                "", 0, None
+            ))])),
+            Err(Error::EmptyWord { index: 0 })
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+//! Categories of censored content defined by the application, independent of rustrict's `Type`
+
+use regex::{Regex, RegexBuilder};
+
+use crate::censor::CensorTypes;
+use crate::span::{Span, SpanKind};
+
+/// Named category of words and regex rules, e.g. `"drugs"` or `"phishing"`
+///
+/// A message belongs to the category when one of its words or rules was censored in it,
+/// the words have to be in the word list to be censored at all.
+#[derive(Debug, Clone)]
+pub struct CustomCategory {
+    name: String,
+    words: Vec<Regex>,
+    rules: Vec<CensorTypes>,
+}
+
+impl CustomCategory {
+    /// Creates category without any words or rules
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            words: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// Assigns words to the category, they are matched case insensitively
+    pub fn with_words<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.words.extend(words.into_iter().map(|word| {
+            RegexBuilder::new(&regex::escape(word.as_ref()))
+                .case_insensitive(true)
+                .build()
+                .expect("Escaped word is a valid pattern")
+        }));
+        self
+    }
+
+    /// Assigns regex rules to the category
+    pub fn with_rules(mut self, rules: impl IntoIterator<Item = CensorTypes>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Name of the category
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether any of the censored spans belongs to the category
+    fn matches(&self, original: &str, spans: &[Span]) -> bool {
+        spans.iter().any(|span| match span.kind {
+            SpanKind::Rule(rule) => self.rules.contains(&rule),
+            SpanKind::Profanity => self.words.iter().any(|word| {
+                word.find_iter(original)
+                    .any(|found| found.start() < span.end && span.start < found.end())
+            }),
+            SpanKind::Mention => false,
+        })
+    }
+}
+
+/// Custom categories given to messages, see [`crate::censor::Censor::with_categories`]
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::category::{CategoryRegistry, CustomCategory};
+/// use little_censor::censor::{Censor, CensorTypes};
+///
+/// let censor = Censor::new().with_categories(
+///     CategoryRegistry::new()
+///         .with(CustomCategory::new("phishing").with_rules([CensorTypes::Link]))
+///         .with(CustomCategory::new("profanity").with_words(["fuck"])),
+/// );
+/// let censored = censor
+///     .censor(String::from("Fuck, see https://example.net"), Box::new([CensorTypes::Link]), None)
+///     .unwrap();
+///
+/// assert_eq!(censored.categories(), ["phishing", "profanity"]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CategoryRegistry {
+    categories: Vec<CustomCategory>,
+}
+
+impl CategoryRegistry {
+    /// Creates registry without any categories
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the category
+    pub fn with(mut self, category: CustomCategory) -> Self {
+        self.categories.push(category);
+        self
+    }
+
+    /// Registered categories
+    pub fn categories(&self) -> &[CustomCategory] {
+        &self.categories
+    }
+
+    /// Sorted names of the categories the censored spans belong to
+    pub(crate) fn classify(&self, original: &str, spans: &[Span]) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .categories
+            .iter()
+            .filter(|category| category.matches(original, spans))
+            .map(|category| category.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_censored_spans() {
+        let registry = CategoryRegistry::new()
+            .with(CustomCategory::new("drugs").with_words(["Weed"]))
+            .with(CustomCategory::new("phishing").with_rules([CensorTypes::Link]))
+            .with(CustomCategory::new("pii").with_rules([CensorTypes::Email]));
+        let spans = [
+            Span::new(5..8, SpanKind::Profanity),
+            Span::new(13..27, SpanKind::Rule(CensorTypes::Link)),
+        ];
+
+        assert_eq!(
+            registry.classify("some weed, see https://a.com", &spans),
+            ["drugs", "phishing"]
+        );
+        assert!(registry.classify("weed", &[]).is_empty());
+    }
+}
@@ -1,29 +1,78 @@
 //! Main Censorship module
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use once_cell::sync::Lazy;
 use regex::Regex;
-use rustrict::CensorStr;
+use rustrict::{BlockReason, Trie, Type};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
 
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 use wasm_bindgen::prelude::wasm_bindgen;
-#[cfg(feature = "wasm")]
-use wasm_bindgen::JsError;
 
+#[cfg(feature = "wasm-min")]
+use crate::error::WasmError;
+#[cfg(feature = "wasm-min")]
+use crate::JsType;
+
+use crate::audit::{AuditLog, Decision};
+use crate::budget::{Budget, Tracker};
+use crate::category::CategoryRegistry;
+use crate::duplicate::DuplicateGuard;
 use crate::error::Error;
+use crate::expiring::ExpiringWords;
+use crate::flood::FloodGuard;
+use crate::hook::{Alert, Hook};
+use crate::link::LinkGuard;
+use crate::metrics::{StageTimings, Stopwatch};
+use crate::phrase::{censor_phrases, Phrase, PHRASES};
+use crate::profile::{Profile, WordList};
+use crate::report::{ModerationReport, Report, Thresholds, Verdict};
+use crate::snapshot::{
+    self, ConfigSnapshot, PackInfo, SnapshotProfile, SnapshotThreshold, SnapshotWord,
+};
+use crate::span::{hunks, masked_spans, Hunk, Span, SpanKind};
+use crate::stats::{Stats, StatsCollector};
+use crate::{pack, type_name, words, AddSummary, Vulgar};
+
+/// Number of compiled custom patterns kept by a single censor
+const REGEX_CACHE_SIZE: usize = 32;
 
 static LINK_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"https?:\/\/(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()!@:%_\+.~#?&\/\/=]*)"#).expect("Failed to create regex")
+    builtin_regex(
+        r#"https?:\/\/(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()!@:%_\+.~#?&\/\/=]*)"#,
+    )
 });
 static IP_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"(\b25[0-5]|\b2[0-4][0-9]|\b[01]?[0-9][0-9]?)(\.(25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)){3}"#).expect("Failed to create regex")
-});
-static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"[^@ \t\r\n]+@[^@ \t\r\n]+\.[^@ \t\r\n]+"#).expect("Failed to create regex")
+    builtin_regex(
+        r#"(\b25[0-5]|\b2[0-4][0-9]|\b[01]?[0-9][0-9]?)(\.(25[0-5]|2[0-4][0-9]|[01]?[0-9][0-9]?)){3}"#,
+    )
 });
+static MENTION_REGEX: Lazy<Regex> = Lazy::new(|| builtin_regex(r"<@[!&]?\d+>|\B@\w+"));
+static EMAIL_REGEX: Lazy<Regex> =
+    Lazy::new(|| builtin_regex(r#"[^@ \t\r\n]+@[^@ \t\r\n]+\.[^@ \t\r\n]+"#));
+
+/// Compiles built-in pattern, using ASCII classes when Unicode tables are disabled
+fn builtin_regex(pattern: &str) -> Regex {
+    let pattern = if cfg!(feature = "unicode") {
+        pattern.to_owned()
+    } else {
+        pattern
+            .replace(r"\b", r"(?-u:\b)")
+            .replace(r"\B", r"(?-u:\B)")
+            .replace(r"\w", r"(?-u:\w)")
+            .replace(r"\d", "[0-9]")
+    };
+    Regex::new(&pattern).expect("Failed to create regex")
+}
 
 /// Types to add additional Censor Methods
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen)]
 pub enum CensorTypes {
     /// E.g. <https://example.net>
     Link,
@@ -35,31 +84,245 @@ pub enum CensorTypes {
     Custom,
 }
 
+/// What makes a censored sentence invalid, see [`Censored::valid`]
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{Censor, CensorTypes, Validity};
+/// use little_censor::span::SpanKind;
+///
+/// let censor = Censor::new().with_validity(Validity::Kinds(vec![SpanKind::Profanity]));
+/// let censored = censor
+///     .censor(String::from("see https://example.net"), Box::new([CensorTypes::Link]), None)
+///     .unwrap();
+///
+/// assert_eq!(censored.censored(), "see *******************");
+/// assert!(censored.valid());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Validity {
+    /// Any change of the sentence
+    #[default]
+    Changed,
+    /// Censored regions of these kinds only, e.g. profanity but not masked links
+    Kinds(Vec<SpanKind>),
+}
+
 /// Response struct containing info about censor
-#[derive(Debug, PartialEq, Eq)]
-#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen)]
 pub struct Censored {
-    original: String,
-    censored: String,
-    valid: bool,
+    pub(crate) original: String,
+    pub(crate) censored: String,
+    pub(crate) valid: bool,
+    pub(crate) truncated: bool,
+    pub(crate) timings: Option<StageTimings>,
+    pub(crate) typ: Type,
+    pub(crate) blocked: Option<BlockReason>,
+    pub(crate) rules: Vec<CensorTypes>,
+    pub(crate) overridden: Option<Decision>,
+    pub(crate) verdict: Verdict,
+    pub(crate) shadow: Option<String>,
+    pub(crate) spans: Vec<Span>,
+    pub(crate) categories: Vec<String>,
+    pub(crate) score: u32,
+}
+
+impl Censored {
+    /// Creates machine readable report about this message
+    pub fn report(&self) -> ModerationReport {
+        ModerationReport::new(self)
+    }
+
+    /// Changed regions with their original and replacement text, sorted by their start
+    ///
+    /// Overlapping and adjacent spans form a single hunk. Nothing changed in shadow mode,
+    /// so there are no hunks then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::censor::{censor, CensorTypes};
+    ///
+    /// let censored = censor(
+    ///     String::from("fuck, mail me@example.net"),
+    ///     Box::new([CensorTypes::Email]),
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// let diff = censored.diff();
+    ///
+    /// assert_eq!(diff[0].range, 1..4);
+    /// assert_eq!(diff[1].original, "me@example.net");
+    /// assert_eq!(diff[1].replacement, "**************");
+    /// ```
+    pub fn diff(&self) -> Vec<Hunk> {
+        hunks(&self.original, &self.censored, &self.spans)
+    }
+
+    /// Whether a regex rule censored part of the sentence, e.g. a link or an email address
+    ///
+    /// Mentions above the limit aren't counted.
+    pub fn pii_redacted(&self) -> bool {
+        self.spans
+            .iter()
+            .any(|span| matches!(span.kind, SpanKind::Rule(_)))
+    }
+
+    /// Whether a vulgar word or phrase was censored
+    pub fn profanity_censored(&self) -> bool {
+        self.spans
+            .iter()
+            .any(|span| span.kind == SpanKind::Profanity)
+    }
+
+    /// Whether the censored sentence differs from the original one
+    ///
+    /// Unlike [`Censored::valid`] it doesn't depend on [`Censor::with_validity`].
+    pub(crate) fn changed(&self) -> bool {
+        self.censored != self.original
+    }
+
+    /// Updates the verdict after the analysis changed
+    pub(crate) fn judge(&mut self, thresholds: &Thresholds) {
+        self.verdict = thresholds.judge(self);
+    }
+
+    /// Joins result of censoring text that directly follows this one
+    pub(crate) fn append(&mut self, next: Censored) {
+        let offset = self.original.len();
+        let unsafe_typ = (self.typ | next.typ) & !Type::SAFE;
+        self.typ = if unsafe_typ == Type::NONE {
+            self.typ | next.typ
+        } else {
+            unsafe_typ
+        };
+
+        self.original.push_str(&next.original);
+        self.censored.push_str(&next.censored);
+        self.valid &= next.valid;
+        self.truncated |= next.truncated;
+        self.score = self.score.saturating_add(next.score);
+        self.blocked = self.blocked.take().or(next.blocked);
+        self.verdict = self.verdict.max(next.verdict);
+        for rule in next.rules {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+            }
+        }
+        self.rules.sort();
+        for category in next.categories {
+            if !self.categories.contains(&category) {
+                self.categories.push(category);
+            }
+        }
+        self.categories.sort();
+        self.spans.extend(
+            next.spans
+                .into_iter()
+                .map(|span| Span::new(span.start + offset..span.end + offset, span.kind)),
+        );
+    }
+}
+
+#[cfg(not(feature = "wasm-min"))]
+impl Censored {
+    /// Sentence before censoring
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+
+    /// Sentence after censoring
+    pub fn censored(&self) -> &str {
+        &self.censored
+    }
+
+    /// Whether nothing had to be censored, see [`Censor::with_validity`]
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Whether analysis stopped early because of the budget
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Time spent in every stage, present only when metrics are enabled
+    pub fn timings(&self) -> Option<&StageTimings> {
+        self.timings.as_ref()
+    }
+
+    /// Type of vulgar words found in the sentence
+    pub fn typ(&self) -> Type {
+        self.typ
+    }
+
+    /// Reason why the message was blocked by [`crate::context::UserContext`]
+    pub fn blocked(&self) -> Option<&BlockReason> {
+        self.blocked.as_ref()
+    }
+
+    /// Regex rules which matched the sentence
+    pub fn rules(&self) -> &[CensorTypes] {
+        &self.rules
+    }
+
+    /// Moderator decision found in the [`AuditLog`] for this sentence
+    ///
+    /// Allowed sentences aren't censored at all.
+    pub fn overridden(&self) -> Option<Decision> {
+        self.overridden
+    }
+
+    /// What should be done with the message according to the censor's [`Thresholds`]
+    pub fn verdict(&self) -> Verdict {
+        self.verdict
+    }
+
+    /// Censored regions of the original sentence, sorted by their start
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Sorted names of custom categories of the censored regions, see
+    /// [`Censor::with_categories`]
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// Sum of weights of censored vulgar words, see [`Vulgar::with_weight`]
+    ///
+    /// Words and phrases without a weight count as 1.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Sentence which would be returned outside of shadow mode
+    ///
+    /// Present only when [`Censor::with_shadow`] is enabled, [`Censored::censored`] is
+    /// then the original sentence while the analysis and verdict stay the same.
+    pub fn shadow(&self) -> Option<&str> {
+        self.shadow.as_deref()
+    }
 }
 
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 pub trait Printable {
     fn debug_str(&self) -> String;
 }
 
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 impl Printable for Censored {
     fn debug_str(&self) -> String {
         format!(
-            "Censored {{ original: {}, censored: {}, valid: {} }}",
-            self.original, self.censored, self.valid
+            "Censored {{ original: {}, censored: {}, valid: {}, truncated: {} }}",
+            self.original, self.censored, self.valid, self.truncated
         )
     }
 }
 
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen]
 impl Censored {
     #[wasm_bindgen(js_name = debug)]
@@ -68,7 +331,7 @@ impl Censored {
     }
 }
 
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen]
 impl Censored {
     #[wasm_bindgen(getter)]
@@ -85,6 +348,88 @@ impl Censored {
     pub fn valid(&self) -> bool {
         self.valid
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// See [`Censored::pii_redacted`]
+    #[wasm_bindgen(getter = piiRedacted)]
+    pub fn wasm_pii_redacted(&self) -> bool {
+        self.pii_redacted()
+    }
+
+    /// See [`Censored::profanity_censored`]
+    #[wasm_bindgen(getter = profanityCensored)]
+    pub fn wasm_profanity_censored(&self) -> bool {
+        self.profanity_censored()
+    }
+
+    /// Censored regions as `{start, end, kind}` objects with UTF-16 offsets
+    #[wasm_bindgen(getter)]
+    pub fn spans(&self) -> js_sys::Array {
+        self.spans
+            .iter()
+            .map(|span| {
+                let object = js_sys::Object::new();
+                for (key, value) in [
+                    ("start", utf16_offset(&self.original, span.start).into()),
+                    ("end", utf16_offset(&self.original, span.end).into()),
+                    ("kind", span.kind.name().into()),
+                ] {
+                    let _ = js_sys::Reflect::set(&object, &key.into(), &value);
+                }
+                wasm_bindgen::JsValue::from(object)
+            })
+            .collect()
+    }
+
+    /// Changed regions as `{start, end, original, replacement}` objects with UTF-16
+    /// offsets, see [`Censored::diff`]
+    #[wasm_bindgen(js_name = diff)]
+    pub fn wasm_diff(&self) -> js_sys::Array {
+        self.diff()
+            .into_iter()
+            .map(|hunk| {
+                let object = js_sys::Object::new();
+                for (key, value) in [
+                    (
+                        "start",
+                        utf16_offset(&self.original, hunk.range.start).into(),
+                    ),
+                    ("end", utf16_offset(&self.original, hunk.range.end).into()),
+                    ("original", hunk.original.into()),
+                    ("replacement", hunk.replacement.into()),
+                ] {
+                    let _ = js_sys::Reflect::set(&object, &key.into(), &value);
+                }
+                wasm_bindgen::JsValue::from(object)
+            })
+            .collect()
+    }
+
+    /// Sum of weights of censored vulgar words, words without a weight count as 1
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Flags of the type of vulgar words found, values of `Type`
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> js_sys::Array {
+        JsType::flags(self.typ)
+            .into_iter()
+            .map(|flag| wasm_bindgen::JsValue::from(flag as u32))
+            .collect()
+    }
+}
+
+/// Number of UTF-16 code units before the byte offset
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+pub(crate) fn utf16_offset(text: &str, offset: usize) -> u32 {
+    text.get(..offset)
+        .map_or(0, |prefix| prefix.encode_utf16().count() as u32)
 }
 
 /// Censors given string
@@ -97,24 +442,23 @@ impl Censored {
 ///
 /// # Errors
 ///
-/// Returns an Error variant if any of the following conditions are met:
+/// Throws an Error with `code` set to:
 ///
-/// * If argument was't provided when it was needed `Error::NoArgs`.
-/// * When invalid regex was passed `Error::Regex`.
+/// * `NO_ARGS` if argument was't provided when it was needed.
+/// * `INVALID_REGEX` with the pattern as `input` when invalid regex was passed.
 ///
 /// # Examples
 ///
 /// let sentence = "Some sentence"
 /// let censored = censor(sentence, [CensorTypes.Custom], "(\\w+)");
-#[cfg(feature = "wasm")]
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen(js_name = "censor")]
 pub fn censor_w(
     sentence: String,
     types: Box<[CensorTypes]>,
     arg: Option<String>,
-) -> Result<Censored, JsError> {
-    let res = censor(sentence, types, arg)?;
-    Ok(res)
+) -> Result<Censored, WasmError> {
+    censor(sentence, types, arg).map_err(WasmError::from)
 }
 
 /// Censors given string
@@ -130,7 +474,7 @@ pub fn censor_w(
 /// Returns an `Err` variant if any of the following conditions are met:
 ///
 /// * If argument was't provided when it was needed `Error::NoArgs`.
-/// * When invalid regex was passed `Error::Regex`.
+/// * When invalid regex was passed `Error::InvalidRegex` with the pattern and its error.
 ///
 /// # Examples
 ///
@@ -145,61 +489,1157 @@ pub fn censor(
     types: Box<[CensorTypes]>,
     arg: Option<String>,
 ) -> Result<Censored, Error> {
-    let mut types = types.into_vec();
-    types.sort();
-    types.dedup();
-    let mut custom = sentence.clone();
-
-    for typ in types {
-        match typ {
-            CensorTypes::Link => regex_censor(&mut custom, LINK_REGEX.clone()),
-            CensorTypes::IP => regex_censor(&mut custom, IP_REGEX.clone()),
-            CensorTypes::Email => regex_censor(&mut custom, EMAIL_REGEX.clone()),
-            CensorTypes::Custom => {
-                let regex = Regex::new(arg.as_ref().ok_or(Error::NoArgs)?)?;
-                regex_censor(&mut custom, regex);
+    Censor::default().censor(sentence, types, arg)
+}
+
+/// Reusable censor configuration
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::budget::{Budget, OnExceed};
+/// use little_censor::censor::{Censor, CensorTypes};
+///
+/// let censor = Censor::new().with_budget(
+///     Budget::new()
+///         .with_max_length(8)
+///         .with_on_exceed(OnExceed::Truncate),
+/// );
+/// let censored = censor
+///     .censor(String::from("127.0.0.1 127.0.0.1"), Box::new([CensorTypes::IP]), None)
+///     .unwrap();
+///
+/// assert!(censored.truncated());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Censor {
+    budget: Budget,
+    threshold: Option<Type>,
+    metrics: bool,
+    stats: Option<Arc<StatsCollector>>,
+    flood_guard: Option<Arc<FloodGuard>>,
+    duplicate_guard: Option<Arc<DuplicateGuard>>,
+    link_guard: Option<Arc<LinkGuard>>,
+    thresholds: Thresholds,
+    validity: Validity,
+    categories: CategoryRegistry,
+    alert: Option<Arc<Alert>>,
+    shadow: bool,
+    strict: bool,
+    mention_limit: Option<(usize, bool)>,
+    replacement: Option<char>,
+    allowed_domains: Vec<String>,
+    word_list: Option<WordList>,
+    weights: Arc<HashMap<String, u32>>,
+    custom_words: Arc<Vec<Vulgar>>,
+    expiring: Arc<ExpiringWords>,
+    packs: Vec<PackInfo>,
+    profiles: HashMap<String, Profile>,
+    audit_log: Option<Arc<AuditLog>>,
+    regex_cache: Arc<RegexCache>,
+    /// Whether this is a censor of [`Censor::probe`], which isn't counted in metrics
+    #[cfg(feature = "metrics")]
+    probe: bool,
+}
+
+impl Censor {
+    /// Creates censor with default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits work done for a single sentence
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Censors only vulgar words at or above this threshold, `Type::INAPPROPRIATE` by default
+    pub fn with_threshold(mut self, threshold: Type) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Threshold for censoring vulgar words, `None` means the default one
+    pub fn threshold(&self) -> Option<Type> {
+        self.threshold
+    }
+
+    /// Sets verdicts for messages of given types, see [`Censored::verdict`]
+    pub fn with_thresholds(mut self, thresholds: Thresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Verdicts for messages of given types
+    pub fn thresholds(&self) -> &Thresholds {
+        &self.thresholds
+    }
+
+    /// Sets which censored regions make sentences invalid, any change by default
+    ///
+    /// Sentences stay censored either way, only [`Censored::valid`] and the verdict
+    /// depend on it.
+    pub fn with_validity(mut self, validity: Validity) -> Self {
+        self.validity = validity;
+        self
+    }
+
+    /// Gives censored regions of messages custom categories, see [`Censored::categories`]
+    ///
+    /// Verdicts of the categories are set with [`Thresholds::with_category`].
+    pub fn with_categories(mut self, categories: CategoryRegistry) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Sum of weights of words covered by profanity spans, temporary words take
+    /// precedence over the ones given to [`Censor::with_words`] and those over the
+    /// global ones
+    fn score(&self, original: &str, spans: &[Span]) -> u32 {
+        spans
+            .iter()
+            .filter(|span| span.kind == SpanKind::Profanity)
+            .map(|span| {
+                // Spans leave out the first characters of words, so the whole words are looked up
+                let start = original[..span.start]
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| c.is_whitespace())
+                    .map_or(0, |(index, c)| index + c.len_utf8());
+                let end = original[span.end..]
+                    .find(char::is_whitespace)
+                    .map_or(original.len(), |index| span.end + index);
+                let word = original[start..end].trim_matches(|c: char| !c.is_alphanumeric());
+                self.expiring
+                    .weight(word)
+                    .or_else(|| self.weights.get(&words::weight_key(word)).copied())
+                    .or_else(|| words::weight(word))
+                    .unwrap_or(1)
+            })
+            .fold(0, u32::saturating_add)
+    }
+
+    /// Applies validity, categories and score to the censored sentence and updates its verdict
+    pub(crate) fn judge(&self, censored: &mut Censored) {
+        censored.categories = self
+            .categories
+            .classify(&censored.original, &censored.spans);
+        censored.score = self.score(&censored.original, &censored.spans);
+        if let Validity::Kinds(kinds) = &self.validity {
+            censored.valid |= !censored.spans.iter().any(|span| kinds.contains(&span.kind));
+        }
+        censored.judge(&self.thresholds);
+    }
+
+    /// Notifies the hook about messages of type at or above the threshold
+    ///
+    /// Messages censored with [`Censor::censor_user`] are reported with their author.
+    pub fn with_hook(mut self, threshold: Type, hook: impl Hook + 'static) -> Self {
+        self.alert = Some(Arc::new(Alert {
+            threshold,
+            hook: Box::new(hook),
+        }));
+        self
+    }
+
+    /// Types sentences with more than `max` mentions as `Type::SPAM`
+    ///
+    /// Both `@name` and `<@id>` mentions are counted, with `mask` the ones above
+    /// the limit are masked.
+    pub fn with_mention_limit(mut self, max: usize, mask: bool) -> Self {
+        self.mention_limit = Some((max, mask));
+        self
+    }
+
+    /// Keeps links to these domains and their subdomains when censoring `CensorTypes::Link`
+    pub fn with_allowed_domains<S: AsRef<str>>(
+        mut self,
+        domains: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_domains = domains
+            .into_iter()
+            .map(|domain| domain.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Masks censored characters with `replacement` instead of `*`
+    pub fn with_replacement(mut self, replacement: char) -> Self {
+        self.replacement = Some(replacement);
+        self
+    }
+
+    /// Character masking censored text
+    pub fn replacement(&self) -> char {
+        self.replacement.unwrap_or('*')
+    }
+
+    /// Analyzes sentences without modifying them, see [`Censored::shadow`]
+    ///
+    /// Useful for trying a configuration out before enforcing it.
+    pub fn with_shadow(mut self, enabled: bool) -> Self {
+        self.shadow = enabled;
+        self
+    }
+
+    /// Rejects sentences which would be censored with `Error::ContentRejected` instead
+    ///
+    /// Whether a sentence would be censored depends on the threshold and
+    /// [`Censor::with_validity`]. Nothing is rejected in shadow mode.
+    pub fn with_strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// Records time spent in every stage into [`Censored::timings`]
+    ///
+    /// Requires a clock, so it can't be enabled on `wasm32-unknown-unknown`.
+    pub fn with_metrics(mut self, enabled: bool) -> Self {
+        self.metrics = enabled;
+        self
+    }
+
+    /// Collects throughput statistics of this censor and its clones, see [`Censor::stats`]
+    ///
+    /// Requires a clock, so it can't be enabled on `wasm32-unknown-unknown`.
+    pub fn with_stats(mut self, enabled: bool) -> Self {
+        self.stats = enabled.then(Default::default);
+        self
+    }
+
+    /// Returns statistics collected so far, `None` if they are disabled
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+
+    /// Clears collected statistics
+    pub fn reset_stats(&self) {
+        if let Some(stats) = &self.stats {
+            stats.reset();
+        }
+    }
+
+    /// Censor with the same word lists, threshold and replacement but nothing else, for
+    /// checking the word lists without side effects, e.g. by readiness probes
+    ///
+    /// Hooks, the audit log, guards, statistics and metrics aren't involved, and strict
+    /// and shadow modes, the budget, allowed domains and profiles are left out, so sample
+    /// messages aren't rejected or kept by the configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::censor::Censor;
+    ///
+    /// let censor = Censor::new().with_strict(true).with_stats(true);
+    /// let probe = censor.probe();
+    ///
+    /// assert!(probe.censor(String::from("fuck"), Box::new([]), None).is_ok());
+    /// assert_eq!(censor.stats().unwrap().messages, 0);
+    /// ```
+    pub fn probe(&self) -> Self {
+        Self {
+            threshold: self.threshold,
+            replacement: self.replacement,
+            word_list: self.word_list.clone(),
+            weights: Arc::clone(&self.weights),
+            custom_words: Arc::clone(&self.custom_words),
+            expiring: Arc::clone(&self.expiring),
+            packs: self.packs.clone(),
+            regex_cache: Arc::clone(&self.regex_cache),
+            #[cfg(feature = "metrics")]
+            probe: true,
+            ..Self::default()
+        }
+    }
+
+    /// Flags bursts of messages sent by a single user, see [`Censor::censor_user`]
+    pub fn with_flood_guard(mut self, guard: FloodGuard) -> Self {
+        self.flood_guard = Some(Arc::new(guard));
+        self
+    }
+
+    /// Uses default word list extended with given words instead of the global one
+    ///
+    /// Like word lists of profiles, building it copies the default one, so censors
+    /// should be created at startup rather than per message. Clones of the censor share
+    /// the word list, it is freed with the last of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` if any of the words is empty.
+    pub fn with_words(mut self, vulgars: Vec<Vulgar>) -> Result<Self, Error> {
+        let weights = vulgars
+            .iter()
+            .filter_map(|vulgar| Some((words::weight_key(&vulgar.word), vulgar.weight?)))
+            .collect();
+        self.custom_words = Arc::new(vulgars.clone());
+        let words = WordList::new(vulgars)?;
+        self.weights = Arc::new(weights);
+        Ok(self.with_word_list(words))
+    }
+
+    /// Adds words to this censor and its clones for the given time, e.g. a leaked
+    /// codename during an embargo
+    ///
+    /// Temporary words are matched as whole words, like phrases, in addition to the word
+    /// list, which isn't changed. Words of the list they shadow are weighted like the
+    /// temporary ones and get their own weight back when the time runs out. Adding a
+    /// temporary word again replaces its time, durations too long for the clock never
+    /// expire. Expired words are cleaned up when the next sentence is censored, see
+    /// [`Censor::expiring_words`] for the ones still pending.
+    ///
+    /// Requires a clock, so it isn't available in the browser.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` with the index of the word if any of the words is empty,
+    /// none of them are added then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use little_censor::censor::Censor;
+    /// use little_censor::Vulgar;
+    ///
+    /// let censor = Censor::new();
+    /// let vulgars = vec![Vulgar::new(String::from("nightjar"), None)];
+    /// censor.add_words_for(vulgars, Duration::from_secs(24 * 60 * 60)).unwrap();
+    ///
+    /// let censored = censor.censor(String::from("project nightjar"), Box::new([]), None).unwrap();
+    /// assert_eq!(censored.censored(), "project n*******");
+    /// assert_eq!(censor.expiring_words()[0].0, "nightjar");
+    /// ```
+    pub fn add_words_for(
+        &self,
+        vulgars: Vec<Vulgar>,
+        duration: Duration,
+    ) -> Result<AddSummary, Error> {
+        self.expiring.add(vulgars, duration)
+    }
+
+    /// Words added with [`Censor::add_words_for`] which haven't expired yet, with the time
+    /// they expire at, the earliest first
+    pub fn expiring_words(&self) -> Vec<(String, Instant)> {
+        self.expiring.pending()
+    }
+
+    /// Remembers packs the censor's own word list was built from, for its snapshots
+    #[cfg(feature = "config")]
+    pub(crate) fn with_packs(mut self, packs: Vec<PackInfo>) -> Self {
+        self.packs = packs;
+        self
+    }
+
+    /// Serializable copy of the configuration and of the word list, to prove which
+    /// policy was active when a message was processed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::censor::{Censor, CensorTypes};
+    ///
+    /// let snapshot = Censor::new()
+    ///     .with_strict(true)
+    ///     .snapshot()
+    ///     .with_rules([CensorTypes::Link])
+    ///     .with_hashed_words();
+    ///
+    /// assert!(snapshot.strict);
+    /// assert!(serde_json::to_string(&snapshot).is_ok());
+    /// ```
+    pub fn snapshot(&self) -> ConfigSnapshot {
+        let snapshot_word = |vulgar: &Vulgar| SnapshotWord {
+            word: vulgar.word.clone(),
+            word_type: type_name::format(vulgar.word_type),
+            weight: vulgar.weight,
+        };
+        let mut censor_words: Vec<SnapshotWord> =
+            self.custom_words.iter().map(snapshot_word).collect();
+        censor_words.sort_by(|a, b| a.word.cmp(&b.word));
+        let thresholds = self
+            .thresholds
+            .rules
+            .iter()
+            .map(|&(typ, verdict)| SnapshotThreshold {
+                on: type_name::format(typ),
+                category: false,
+                verdict,
+            })
+            .chain(
+                self.thresholds
+                    .categories
+                    .iter()
+                    .map(|(name, verdict)| SnapshotThreshold {
+                        on: name.clone(),
+                        category: true,
+                        verdict: *verdict,
+                    }),
+            )
+            .collect();
+        let mut profiles: Vec<SnapshotProfile> = self
+            .profiles
+            .iter()
+            .map(|(name, profile)| SnapshotProfile {
+                name: name.clone(),
+                rules: profile.types.clone(),
+                pattern: profile.arg.clone(),
+                threshold: profile.threshold.map(type_name::format),
+                own_words: profile.words.is_some(),
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ConfigSnapshot {
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            hashed: false,
+            words: snapshot::global_words(),
+            censor_words,
+            packs: pack::LOADED
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .chain(&self.packs)
+                .cloned()
+                .collect(),
+            rules: Vec::new(),
+            threshold: type_name::format(self.threshold.unwrap_or(Type::INAPPROPRIATE)),
+            thresholds,
+            categories: self
+                .categories
+                .categories()
+                .iter()
+                .map(|category| category.name().to_owned())
+                .collect(),
+            profiles,
+            allowed_domains: self.allowed_domains.clone(),
+            replacement: self.replacement(),
+            max_length: self.budget.max_length,
+            shadow: self.shadow,
+            strict: self.strict,
+        }
+    }
+
+    pub(crate) fn with_word_list(mut self, words: WordList) -> Self {
+        self.word_list = Some(words);
+        self
+    }
+
+    /// Own word list of the censor, built from the default one if it doesn't have one
+    #[cfg(feature = "wasm")]
+    pub(crate) fn word_list_mut(&mut self) -> &mut WordList {
+        self.word_list
+            .get_or_insert_with(|| WordList::new([]).expect("Default word list has no empty words"))
+    }
+
+    /// Word list given to [`Censor::run`] when no profile overrides it
+    fn words(&self) -> Option<&WordList> {
+        self.word_list.as_ref()
+    }
+
+    /// Registers named profile, see [`Censor::censor_with_profile`]
+    pub fn with_profile(mut self, name: impl Into<String>, profile: Profile) -> Self {
+        self.profiles.insert(name.into(), profile);
+        self
+    }
+
+    /// Applies moderator decisions recorded for identical sentences
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = Some(Arc::new(log));
+        self
+    }
+
+    /// Flags repeated messages of a single user, see [`Censor::censor_user`]
+    pub fn with_duplicate_guard(mut self, guard: DuplicateGuard) -> Self {
+        self.duplicate_guard = Some(Arc::new(guard));
+        self
+    }
+
+    /// Limits links posted by a single user, see [`Censor::censor_user`]
+    pub fn with_link_guard(mut self, guard: LinkGuard) -> Self {
+        self.link_guard = Some(Arc::new(guard));
+        self
+    }
+
+    /// Censors given string
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` variant if any of the following conditions are met:
+    ///
+    /// * If argument was't provided when it was needed `Error::NoArgs`.
+    /// * When invalid regex was passed `Error::InvalidRegex` with the pattern and its error.
+    /// * When budget was exceeded and it is configured to fail `Error::BudgetExceeded`,
+    ///   or `Error::InputTooLong` when the sentence is above the maximum length.
+    /// * When the audit log storage failed `Error::Storage`.
+    /// * When the censor is strict and the sentence isn't valid `Error::ContentRejected`
+    ///   with its report.
+    pub fn censor(
+        &self,
+        sentence: String,
+        types: Box<[CensorTypes]>,
+        arg: Option<String>,
+    ) -> Result<Censored, Error> {
+        let censored = self.run(
+            sentence,
+            &types,
+            arg.as_deref(),
+            self.threshold,
+            self.words(),
+        )?;
+        self.finish(censored, None)
+    }
+
+    /// Analyzes given string like [`Censor::censor`] but returns only the [`Report`]
+    ///
+    /// Useful when messages are accepted or rejected as a whole, the censored sentence
+    /// isn't kept. Hooks are notified like when censoring.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    pub fn inspect(
+        &self,
+        sentence: &str,
+        types: &[CensorTypes],
+        arg: Option<&str>,
+    ) -> Result<Report, Error> {
+        let censored = self.run(
+            sentence.to_owned(),
+            types,
+            arg,
+            self.threshold,
+            self.words(),
+        )?;
+        self.notify(&censored, None);
+        Ok(Report::from(&censored))
+    }
+
+    /// Censors given string with rules, threshold and word list of a registered profile
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::UnknownProfile` if no profile was registered under this name,
+    /// otherwise the same errors as [`Censor::censor`].
+    pub fn censor_with_profile(&self, profile: &str, sentence: String) -> Result<Censored, Error> {
+        let profile = self.profiles.get(profile).ok_or(Error::UnknownProfile)?;
+        let censored = self.run(
+            sentence,
+            &profile.types,
+            profile.arg.as_deref(),
+            profile.threshold.or(self.threshold),
+            profile.words.as_ref().or(self.words()),
+        )?;
+        self.finish(censored, None)
+    }
+
+    /// Censors given string with the threshold like [`Censor::censor`], without notifying
+    /// the hook or applying shadow and strict modes, see [`Censor::finish`]
+    ///
+    /// For callers adjusting the result before it's finished.
+    pub(crate) fn analyze(
+        &self,
+        sentence: String,
+        types: &[CensorTypes],
+        arg: Option<&str>,
+        threshold: Type,
+    ) -> Result<Censored, Error> {
+        self.run(sentence, types, arg, Some(threshold), self.words())
+    }
+
+    /// Notifies the hook and applies shadow or strict mode
+    pub(crate) fn finish(
+        &self,
+        mut censored: Censored,
+        user: Option<&str>,
+    ) -> Result<Censored, Error> {
+        self.notify(&censored, user);
+        if self.shadow {
+            let original = censored.original.clone();
+            censored.shadow = Some(std::mem::replace(&mut censored.censored, original));
+        } else if self.strict && !censored.valid {
+            return Err(Error::ContentRejected(Report::from(&censored)));
+        }
+        Ok(censored)
+    }
+
+    /// Reports the message to the hook if it's severe enough
+    fn notify(&self, censored: &Censored, user: Option<&str>) {
+        if let Some(alert) = &self.alert {
+            if !censored.typ.is(Type::SAFE) && censored.typ.is(alert.threshold) {
+                let report = censored.report();
+                match user {
+                    Some(user) => alert.hook.notify(&report.with_user(user)),
+                    None => alert.hook.notify(&report),
+                }
+            }
+        }
+    }
+
+    fn run(
+        &self,
+        sentence: String,
+        types: &[CensorTypes],
+        arg: Option<&str>,
+        threshold: Option<Type>,
+        words: Option<&WordList>,
+    ) -> Result<Censored, Error> {
+        let started = (self.stats.is_some() || cfg!(feature = "metrics")).then(Instant::now);
+        let (custom, cache_hit) = match arg {
+            Some(pattern) if types.contains(&CensorTypes::Custom) => {
+                let (regex, hit) = self.regex_cache.get(pattern)?;
+                (Some(regex), Some(hit))
+            }
+            _ => (None, None),
+        };
+
+        let mut rules = Rules::new(types, custom, threshold);
+        rules.words = words.cloned();
+        rules.temporary = self.expiring.active();
+        rules.allowed_domains.clone_from(&self.allowed_domains);
+        rules.replacement = self.replacement();
+        let mut tracker = Tracker::new(&self.budget);
+        let mut timings = self.metrics.then(StageTimings::default);
+        let pass = censor_text(&sentence, &rules, &mut tracker, timings.as_mut())?;
+
+        let latency = started.map(|started| started.elapsed());
+        if let (Some(stats), Some(latency)) = (&self.stats, latency) {
+            stats.record(&pass, cache_hit, latency);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(latency) = latency.filter(|_| !self.probe) {
+            crate::metrics::observe(&pass, cache_hit, latency);
+        }
+
+        let mut censored = Censored {
+            valid: sentence == pass.censored,
+            original: sentence,
+            censored: pass.censored,
+            truncated: tracker.truncated,
+            timings,
+            typ: pass.typ,
+            blocked: None,
+            rules: pass
+                .rule_matches
+                .iter()
+                .filter(|(_, matches)| *matches > 0)
+                .map(|&(typ, _)| typ)
+                .collect(),
+            overridden: None,
+            verdict: Verdict::Pass,
+            shadow: None,
+            spans: pass.spans,
+            categories: Vec::new(),
+            score: 0,
+        };
+        if let Some((max, mask)) = self.mention_limit {
+            if limit_mentions(&mut censored, max, mask, rules.replacement) {
+                censored.typ = (censored.typ & !Type::SAFE) | (Type::SPAM & Type::MODERATE);
             }
         }
+
+        if let Some(log) = &self.audit_log {
+            if let Some(entry) = log.lookup(&censored.original)? {
+                if entry.decision == Decision::Allow {
+                    censored.censored = censored.original.clone();
+                    censored.valid = true;
+                    censored.spans.clear();
+                }
+                censored.overridden = Some(entry.decision);
+            }
+        }
+        self.judge(&mut censored);
+
+        Ok(censored)
     }
 
-    let censored = custom.censor();
-    let censored = fix_sentence(custom, censored);
+    /// Censors message sent by given user
+    ///
+    /// Works like [`Censor::censor`], but messages sent during a flood detected by
+    /// the [`FloodGuard`] or repeated according to the [`DuplicateGuard`] are
+    /// additionally typed as `Type::SPAM`. Links above the limit of the [`LinkGuard`]
+    /// are masked and make the message spam as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    pub fn censor_user(
+        &self,
+        user: &str,
+        sentence: String,
+        types: Box<[CensorTypes]>,
+        arg: Option<String>,
+    ) -> Result<Censored, Error> {
+        let flood = self
+            .flood_guard
+            .as_ref()
+            .is_some_and(|guard| guard.hit(user));
+        let duplicate = self
+            .duplicate_guard
+            .as_ref()
+            .is_some_and(|guard| guard.hit(user, &sentence));
 
-    Ok(Censored {
-        original: sentence.clone(),
-        censored: censored.clone(),
-        valid: sentence == censored,
+        let mut censored = self.run(
+            sentence,
+            &types,
+            arg.as_deref(),
+            self.threshold,
+            self.words(),
+        )?;
+        let links = match &self.link_guard {
+            Some(guard) => limit_links(&mut censored, guard, user, self.replacement()),
+            None => false,
+        };
+        if flood || duplicate || links {
+            censored.typ = (censored.typ & !Type::SAFE) | (Type::SPAM & Type::MODERATE);
+            self.judge(&mut censored);
+        }
+        self.finish(censored, Some(user))
+    }
+}
+
+/// Finds mentions above the limit and optionally masks them, returns whether there were any
+fn limit_mentions(censored: &mut Censored, max: usize, mask: bool, replacement: char) -> bool {
+    let mentions: Vec<_> = MENTION_REGEX
+        .find_iter(&censored.censored)
+        .map(|mention| mention.range())
+        .skip(max)
+        .collect();
+    if mentions.is_empty() {
+        return false;
+    }
+
+    if mask {
+        let spans = original_ranges(censored, &mentions);
+        for mention in mentions.iter().rev() {
+            let masked = mask_str(&censored.censored[mention.clone()], replacement);
+            censored.censored.replace_range(mention.clone(), &masked);
+        }
+        censored.valid = false;
+        add_spans(censored, spans, SpanKind::Mention);
+    }
+    true
+}
+
+/// Replaces every character of `text` with `replacement` like regex rules do, so
+/// characters of the censored sentence stay at the positions of the original ones
+fn mask_str(text: &str, replacement: char) -> String {
+    text.chars().map(|_| replacement).collect()
+}
+
+/// Ranges of the original sentence with the characters at `ranges` of the censored one
+///
+/// Masks may be longer or shorter than the characters they replace, but censoring keeps
+/// the number of characters, rule matches included.
+fn original_ranges(censored: &Censored, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+    let offset = |chars: usize| {
+        censored
+            .original
+            .char_indices()
+            .nth(chars)
+            .map_or(censored.original.len(), |(offset, _)| offset)
+    };
+    ranges
+        .iter()
+        .map(|range| {
+            let start = censored.censored[..range.start].chars().count();
+            let end = start + censored.censored[range.clone()].chars().count();
+            offset(start)..offset(end)
+        })
+        .collect()
+}
+
+fn add_spans(censored: &mut Censored, ranges: Vec<Range<usize>>, kind: SpanKind) {
+    censored
+        .spans
+        .extend(ranges.into_iter().map(|range| Span::new(range, kind)));
+    censored.spans.sort_by_key(|span| span.start);
+}
+
+/// Masks links above the limit of the guard, returns whether any were masked
+fn limit_links(censored: &mut Censored, guard: &LinkGuard, user: &str, replacement: char) -> bool {
+    let links: Vec<_> = LINK_REGEX
+        .find_iter(&censored.censored)
+        .map(|link| link.range())
+        .collect();
+    if links.is_empty() {
+        return false;
+    }
+
+    let allowed = guard.allow(user, links.len());
+    if allowed == links.len() {
+        return false;
+    }
+    let spans = original_ranges(censored, &links[allowed..]);
+    for link in links[allowed..].iter().rev() {
+        let masked = mask_str(&censored.censored[link.clone()], replacement);
+        censored.censored.replace_range(link.clone(), &masked);
+    }
+    add_spans(censored, spans, SpanKind::Rule(CensorTypes::Link));
+    if !censored.rules.contains(&CensorTypes::Link) {
+        censored.rules.push(CensorTypes::Link);
+        censored.rules.sort();
+    }
+    censored.valid = false;
+    true
+}
+
+/// Compiled custom patterns shared between clones of a censor
+#[derive(Debug, Default)]
+struct RegexCache {
+    patterns: Mutex<HashMap<String, Regex>>,
+}
+
+impl RegexCache {
+    /// Returns compiled pattern and whether it was already cached
+    fn get(&self, pattern: &str) -> Result<(Regex, bool), Error> {
+        let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(regex) = patterns.get(pattern) {
+            return Ok((regex.clone(), true));
+        }
+
+        let regex = Regex::new(pattern).map_err(|error| Error::invalid_regex(pattern, error))?;
+        if patterns.len() >= REGEX_CACHE_SIZE {
+            patterns.clear();
+        }
+        patterns.insert(pattern.to_owned(), regex.clone());
+        Ok((regex, false))
+    }
+}
+
+/// Rules applied to a single sentence
+pub(crate) struct Rules {
+    /// Sorted regex rules without duplicates
+    pub(crate) types: Vec<CensorTypes>,
+    /// Compiled argument of `CensorTypes::Custom`
+    pub(crate) custom: Option<Regex>,
+    /// Threshold for censoring vulgar words, `None` means rustrict default
+    pub(crate) threshold: Option<Type>,
+    /// Word list replacing the default one, its phrases are matched in addition to
+    /// the ones added with [`crate::add_words`]
+    pub(crate) words: Option<WordList>,
+    /// Words of [`Censor::add_words_for`] which haven't expired yet
+    pub(crate) temporary: Vec<Phrase>,
+    /// Lowercase domains whose links aren't masked by `CensorTypes::Link`
+    pub(crate) allowed_domains: Vec<String>,
+    /// Character masking censored text
+    pub(crate) replacement: char,
+}
+
+impl Rules {
+    pub(crate) fn new(
+        types: &[CensorTypes],
+        custom: Option<Regex>,
+        threshold: Option<Type>,
+    ) -> Self {
+        let mut types = types.to_vec();
+        types.sort();
+        types.dedup();
+
+        Self {
+            types,
+            custom,
+            threshold,
+            words: None,
+            temporary: Vec::new(),
+            allowed_domains: Vec::new(),
+            replacement: '*',
+        }
+    }
+}
+
+/// Outcome of running every censoring stage over a sentence
+pub(crate) struct Pass {
+    pub(crate) censored: String,
+    /// Type of vulgar words found, `Type::NONE` when profanity stage didn't run
+    pub(crate) typ: Type,
+    /// Number of masked matches of every regex rule
+    pub(crate) rule_matches: Vec<(CensorTypes, usize)>,
+    /// Number of vulgar words found
+    pub(crate) profanity_matches: usize,
+    /// Masked regions sorted by their start
+    pub(crate) spans: Vec<Span>,
+}
+
+/// Compiles pattern for `CensorTypes::Custom` when it is requested
+pub(crate) fn compile_custom(
+    types: &[CensorTypes],
+    arg: Option<&str>,
+) -> Result<Option<Regex>, Error> {
+    match arg {
+        Some(pattern) if types.contains(&CensorTypes::Custom) => Regex::new(pattern)
+            .map(Some)
+            .map_err(|error| Error::invalid_regex(pattern, error)),
+        _ => Ok(None),
+    }
+}
+
+/// Runs every censoring stage over `sentence`
+pub(crate) fn censor_text(
+    sentence: &str,
+    rules: &Rules,
+    tracker: &mut Tracker,
+    timings: Option<&mut StageTimings>,
+) -> Result<Pass, Error> {
+    let mut stopwatch = Stopwatch::new(timings.is_some());
+    let analyzed = tracker.limit_length(sentence)?;
+    let mut masked = analyzed.to_owned();
+    let mut rule_matches = Vec::with_capacity(rules.types.len());
+    let mut spans = Vec::new();
+
+    for &typ in &rules.types {
+        if !tracker.check_time()? {
+            break;
+        }
+        let regex = match typ {
+            CensorTypes::Link => &*LINK_REGEX,
+            CensorTypes::IP => &*IP_REGEX,
+            CensorTypes::Email => &*EMAIL_REGEX,
+            CensorTypes::Custom => rules.custom.as_ref().ok_or(Error::NoArgs)?,
+        };
+        let allowed =
+            |link: &str| typ == CensorTypes::Link && is_allowed_link(link, &rules.allowed_domains);
+        let matches = regex_censor(&mut masked, regex, tracker, allowed)?;
+        rule_matches.push((typ, matches.len()));
+        spans.extend(
+            matches
+                .into_iter()
+                .map(|range| Span::new(range, SpanKind::Rule(typ))),
+        );
+    }
+
+    let regex_time = stopwatch.lap();
+
+    let mut profanity_matches = 0;
+    let mut typ = Type::NONE;
+    let vulgar = if tracker.check_time()? {
+        let _trie = rules.words.is_none().then(words::read_trie);
+        let mut censor = rustrict::Censor::from_str(&masked);
+        if let Some(threshold) = rules.threshold {
+            censor.with_censor_threshold(threshold);
+        }
+        if let Some(words) = &rules.words {
+            // SAFETY: rustrict only accepts static tries, `censor` is dropped at the end of
+            // this block while `rules` keeps the trie alive
+            let trie: &'static Trie = unsafe { &*Arc::as_ptr(&words.trie) };
+            censor.with_trie(trie);
+        }
+        censor.with_censor_replacement(rules.replacement);
+        let (vulgar, analysis) = censor.censor_and_analyze();
+        profanity_matches = censor.total_matches();
+        typ = analysis;
+        Some(vulgar)
+    } else {
+        None
+    };
+    let profanity_time = stopwatch.lap();
+
+    let mut censored = match vulgar {
+        Some(vulgar) => {
+            let mut censored = fix_sentence(masked.clone(), vulgar, rules.replacement);
+            let threshold = rules.threshold.unwrap_or(Type::INAPPROPRIATE);
+            let global = PHRASES.read().unwrap_or_else(|e| e.into_inner());
+            let (phrase_typ, phrase_matches) = censor_phrases(
+                &mut censored,
+                global
+                    .iter()
+                    .chain(rules.words.iter().flat_map(|words| words.phrases.iter()))
+                    .chain(&rules.temporary),
+                threshold,
+                rules.replacement,
+            );
+            if phrase_typ != Type::NONE {
+                typ = (typ & !Type::SAFE) | phrase_typ;
+            }
+            profanity_matches += phrase_matches;
+            let profanity =
+                masked_spans(&masked, &censored, SpanKind::Profanity, rules.replacement);
+            let censored =
+                replace_rule_masks(analyzed, &masked, censored, &spans, rules.replacement);
+            spans.extend(profanity);
+            censored
+        }
+        None => replace_rule_masks(analyzed, &masked, masked.clone(), &spans, rules.replacement),
+    };
+    censored.push_str(&sentence[analyzed.len()..]);
+    spans.sort_by_key(|span| span.start);
+
+    if let Some(timings) = timings {
+        *timings = StageTimings {
+            regex: regex_time,
+            profanity: profanity_time,
+            rebuild: stopwatch.lap(),
+        };
+    }
+
+    Ok(Pass {
+        censored,
+        typ,
+        rule_matches,
+        profanity_matches,
+        spans,
     })
 }
 
-/// Censor by given regex pattern
-fn regex_censor(sentence: &mut String, regex: Regex) {
+/// Censor by given regex pattern, returns ranges of masked matches
+fn regex_censor(
+    sentence: &mut String,
+    regex: &Regex,
+    tracker: &mut Tracker,
+    allowed: impl Fn(&str) -> bool,
+) -> Result<Vec<Range<usize>>, Error> {
     let binding = sentence.clone();
-    let matches: Vec<&str> = regex.find_iter(&binding).map(|v| v.as_str()).collect();
+    let matches: Vec<_> = regex
+        .find_iter(&binding)
+        .filter(|value| !allowed(value.as_str()))
+        .collect();
 
-    // Replace links with coresponding number of stars
+    // Replace matches with a star per byte, identical text elsewhere is kept
+    let mut masked = Vec::with_capacity(matches.len());
     for value in matches {
-        *sentence = sentence.replace(value, &"*".repeat(value.len()));
+        if !tracker.take_match()? {
+            break;
+        }
+        sentence.replace_range(value.range(), &"*".repeat(value.len()));
+        masked.push(value.range());
     }
+
+    Ok(masked)
 }
 
-fn fix_sentence(original: String, censored: String) -> String {
-    censored
-        .chars()
-        .zip(original.chars())
-        .map(|(censor_char, original_char)| {
-            if censor_char != '*' {
-                original_char
+/// Whether host of the link is one of the domains or their subdomain
+fn is_allowed_link(link: &str, domains: &[String]) -> bool {
+    if domains.is_empty() {
+        return false;
+    }
+    let host = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let host = host
+        .split(['/', ':', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    domains.iter().any(|domain| {
+        host == *domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+/// Regex rules mask matches byte by byte with `*` to keep offsets of later
+/// matches, so the replacement is applied once every stage is done
+///
+/// Every character of the rule spans is masked again, so no later stage can weaken
+/// masks of links, addresses or custom patterns. The spans get one replacement per
+/// character of `original`, like the rest of the sentence, instead of one per byte.
+fn replace_rule_masks(
+    original: &str,
+    masked: &str,
+    censored: String,
+    spans: &[Span],
+    replacement: char,
+) -> String {
+    if spans.is_empty() {
+        return censored;
+    }
+    masked
+        .char_indices()
+        .zip(censored.chars())
+        .filter_map(|((offset, _), c)| {
+            if spans.iter().any(|span| span.range().contains(&offset)) {
+                original.is_char_boundary(offset).then_some(replacement)
             } else {
-                censor_char
+                Some(c)
             }
         })
         .collect()
 }
 
+/// Takes masks of the profanity pass over the original, a grapheme with any masked
+/// character is masked as a whole
+///
+/// Otherwise masking part of an emoji ZWJ sequence or a letter with combining marks
+/// leaves garbled characters, e.g. an accent on a mask. The result always has the
+/// characters of the original, even if censoring added or removed some.
+fn fix_sentence(original: String, censored: String, replacement: char) -> String {
+    let ranges = masked_ranges(&original, &censored, replacement);
+    let mut fixed = String::with_capacity(original.len());
+    for (offset, grapheme) in original.grapheme_indices(true) {
+        let end = offset + grapheme.len();
+        if ranges
+            .iter()
+            .any(|range| range.start < end && offset < range.end)
+        {
+            fixed.extend(grapheme.chars().map(|_| replacement));
+        } else {
+            fixed.push_str(grapheme);
+        }
+    }
+    fixed
+}
+
+/// Byte ranges of `original` masked in `censored`
+///
+/// Both texts are walked together. Where a character differs without being masked,
+/// censoring changed the length, so the walk skips the removed or added character
+/// when the next one lines up again, instead of shifting every later mask.
+fn masked_ranges(original: &str, censored: &str, replacement: char) -> Vec<Range<usize>> {
+    let original: Vec<(usize, char)> = original.char_indices().collect();
+    let censored: Vec<char> = censored.chars().collect();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while let (Some(&(offset, o)), Some(&c)) = (original.get(i), censored.get(j)) {
+        if c == o {
+            i += 1;
+            j += 1;
+        } else if c == replacement {
+            let end = offset + o.len_utf8();
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end = end,
+                _ => ranges.push(offset..end),
+            }
+            i += 1;
+            j += 1;
+        } else if original.get(i + 1).is_some_and(|&(_, next)| next == c) {
+            // Character removed by censoring
+            i += 1;
+        } else if censored.get(j + 1) == Some(&o) {
+            // Character added by censoring
+            j += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    ranges
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
+    use crate::category::CustomCategory;
+
+    /// Fields of [`super::Censored`] compared by the tests written before it had any others
+    #[derive(Debug)]
+    struct Censored {
+        original: String,
+        censored: String,
+        valid: bool,
+    }
+
+    impl PartialEq<Censored> for super::Censored {
+        fn eq(&self, other: &Censored) -> bool {
+            self.original == other.original
+                && self.censored == other.censored
+                && self.valid == other.valid
+        }
+    }
 
     #[test]
     fn censor_word() {
@@ -215,6 +1655,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn masks_whole_graphemes() {
+        assert_eq!(
+            fix_sentence(
+                String::from("cafe\u{301} ok"),
+                String::from("caf*\u{301} ok"),
+                '*'
+            ),
+            "caf** ok"
+        );
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(
+            fix_sentence(
+                format!("{family}!"),
+                String::from("*\u{200D}\u{1F469}\u{200D}\u{1F467}!"),
+                '*'
+            ),
+            "*****!"
+        );
+    }
+
+    #[test]
+    fn aligns_changed_length() {
+        let fix = |original: &str, censored: &str| {
+            fix_sentence(original.to_owned(), censored.to_owned(), '*')
+        };
+
+        assert_eq!(fix("x\u{200B}fuck ok", "xf*** ok"), "x\u{200B}f*** ok");
+        assert_eq!(fix("fuck ok fuck", "f***! ok f***"), "f*** ok f***");
+        assert_eq!(fix("fuck there", "f***"), "f*** there");
+    }
+
     #[test]
     fn utf8_chars() {
         let sentence = String::from("fuck ąćęłńśóźżäöüß fuck");
@@ -243,6 +1715,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn link_and_profanity_in_one_word() {
+        for replacement in ['*', '#'] {
+            let censored = Censor::new()
+                .with_replacement(replacement)
+                .censor(
+                    String::from("fuckhttps://example.net fuck"),
+                    Box::new([CensorTypes::Link]),
+                    None,
+                )
+                .unwrap();
+            let mask = |count| replacement.to_string().repeat(count);
+
+            assert_eq!(censored.censored, format!("f{} f{}", mask(22), mask(3)));
+            assert!(censored
+                .spans
+                .iter()
+                .any(|span| span.range() == (4..23)
+                    && span.kind == SpanKind::Rule(CensorTypes::Link)));
+        }
+    }
+
+    #[test]
+    fn rule_masks_survive_later_stages() {
+        let spans = [Span::new(2..5, SpanKind::Rule(CensorTypes::IP))];
+
+        assert_eq!(
+            replace_rule_masks("a 1.2", "a ***", String::from("a *1*"), &spans, '*'),
+            "a ***"
+        );
+    }
+
+    #[test]
+    fn masks_only_matches() {
+        let censored = censor(
+            String::from("secret secrets"),
+            Box::new([CensorTypes::Custom]),
+            Some(String::from(r"\bsecret\b")),
+        )
+        .unwrap();
+
+        assert_eq!(censored.censored, "****** secrets");
+    }
+
+    #[test]
+    fn allowed_domains() {
+        let censor = Censor::new().with_allowed_domains(["Example.net"]);
+
+        let censored = censor
+            .censor(
+                String::from("https://docs.example.net/a https://example.net.evil.com"),
+                Box::new([CensorTypes::Link]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            censored.censored,
+            "https://docs.example.net/a ****************************"
+        );
+    }
+
+    #[test]
+    fn replacement_char() {
+        let censor = Censor::new().with_replacement('█');
+
+        let censored = censor
+            .censor(
+                String::from("fuck 127.0.0.1 *"),
+                Box::new([CensorTypes::IP]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "f███ █████████ *");
+        assert_eq!(censored.spans.len(), 2);
+    }
+
+    #[test]
+    fn censor_own_words() {
+        let censor = Censor::new()
+            .with_words(vec![Vulgar::new(String::from("heck"), None)])
+            .unwrap();
+
+        let own = censor.censor(String::from("heck"), Box::new([]), None);
+        let global = Censor::new().censor(String::from("heck"), Box::new([]), None);
+
+        assert_eq!(own.unwrap().censored, "h***");
+        assert_eq!(global.unwrap().censored, "heck");
+    }
+
     #[test]
     fn ip_regex_censor() {
         let sentence = String::from("ip leak 127.0.0.1");
@@ -270,4 +1833,382 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn budget_truncates_long_sentence() {
+        let censor = Censor::new().with_budget(
+            Budget::new()
+                .with_max_length(10)
+                .with_on_exceed(crate::budget::OnExceed::Truncate),
+        );
+        let censored = censor
+            .censor(String::from("fuck fuck fuck"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "f*** f*** fuck");
+        assert!(!censored.valid);
+        assert!(censored.truncated);
+        assert_eq!(censored.timings, None);
+    }
+
+    #[test]
+    fn budget_exceeded() {
+        let censor = Censor::new().with_budget(Budget::new().with_max_matches(1));
+        let censored = censor.censor(
+            String::from("127.0.0.1 and 10.0.0.1"),
+            Box::new([CensorTypes::IP]),
+            None,
+        );
+        assert!(matches!(censored, Err(Error::BudgetExceeded)));
+    }
+
+    #[test]
+    fn stage_timings() {
+        let sentence = String::from("fuck world");
+        let censored =
+            Censor::new()
+                .with_metrics(true)
+                .censor(sentence.clone(), Box::new([]), None);
+        assert!(censored.unwrap().timings.is_some());
+
+        let censored = Censor::new().censor(sentence, Box::new([]), None);
+        assert_eq!(censored.unwrap().timings, None);
+    }
+
+    #[test]
+    fn throughput_stats() {
+        let censor = Censor::new().with_stats(true);
+        let types = [CensorTypes::IP, CensorTypes::Custom];
+        let arg = Some(String::from("secret"));
+
+        for _ in 0..2 {
+            censor
+                .censor(
+                    String::from("fuck 127.0.0.1 secret"),
+                    Box::new(types),
+                    arg.clone(),
+                )
+                .unwrap();
+        }
+
+        let stats = censor.stats().unwrap();
+        assert_eq!(stats.messages, 2);
+        assert_eq!(stats.matches.get(&CensorTypes::IP), Some(&2));
+        assert_eq!(stats.matches.get(&CensorTypes::Custom), Some(&2));
+        assert_eq!(stats.profanity_matches, 2);
+        assert_eq!((stats.cache_hits, stats.cache_misses), (1, 1));
+        assert!(stats.p99_latency.is_some());
+
+        assert!(Censor::new().stats().is_none());
+    }
+
+    #[test]
+    fn flood_is_spam() {
+        let censor = Censor::new().with_flood_guard(FloodGuard::new(1, Duration::from_secs(60)));
+
+        let first = censor
+            .censor_user("user", String::from("hello"), Box::new([]), None)
+            .unwrap();
+        let second = censor
+            .censor_user("user", String::from("hello"), Box::new([]), None)
+            .unwrap();
+
+        assert!(!first.typ.is(Type::SPAM));
+        assert!(second.typ.is(Type::SPAM));
+    }
+
+    #[test]
+    fn duplicate_is_spam() {
+        let censor = Censor::new()
+            .with_duplicate_guard(DuplicateGuard::new(Duration::from_secs(60)).with_max_repeats(1));
+
+        let first = censor
+            .censor_user("user", String::from("free nitro"), Box::new([]), None)
+            .unwrap();
+        let second = censor
+            .censor_user("user", String::from("FREE NITRO"), Box::new([]), None)
+            .unwrap();
+
+        assert!(!first.typ.is(Type::SPAM));
+        assert!(second.typ.is(Type::SPAM));
+    }
+
+    #[test]
+    fn censored_spans() {
+        let censored = censor(
+            String::from("fuck 127.0.0.1"),
+            Box::new([CensorTypes::IP]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            censored.spans,
+            vec![
+                Span::new(1..4, SpanKind::Profanity),
+                Span::new(5..14, SpanKind::Rule(CensorTypes::IP)),
+            ]
+        );
+    }
+
+    #[test]
+    fn pii_and_profanity_flags() {
+        let pii = censor(
+            String::from("mail me@example.net"),
+            Box::new([CensorTypes::Email]),
+            None,
+        )
+        .unwrap();
+        let profanity = censor(String::from("fuck"), Box::new([]), None).unwrap();
+
+        assert!(pii.pii_redacted());
+        assert!(!pii.profanity_censored());
+        assert!(!profanity.pii_redacted());
+        assert!(profanity.profanity_censored());
+    }
+
+    #[test]
+    fn inspect_matches_censor() {
+        let censor = Censor::new().with_validity(Validity::Kinds(vec![SpanKind::Profanity]));
+        let censored = censor
+            .censor(
+                String::from("fuck me@example.net"),
+                Box::new([CensorTypes::Email]),
+                None,
+            )
+            .unwrap();
+
+        let report = censor
+            .inspect("fuck me@example.net", &[CensorTypes::Email], None)
+            .unwrap();
+
+        assert_eq!(report, Report::from(&censored));
+        assert_eq!(report.rules, vec![CensorTypes::Email]);
+        assert_eq!(report.spans, censored.spans);
+    }
+
+    #[test]
+    fn strict_rejects_invalid() {
+        let censor = Censor::new()
+            .with_strict(true)
+            .with_validity(Validity::Kinds(vec![SpanKind::Profanity]));
+
+        let link = censor
+            .censor(
+                String::from("see https://a.com"),
+                Box::new([CensorTypes::Link]),
+                None,
+            )
+            .unwrap();
+        let error = censor
+            .censor(String::from("fuck"), Box::new([]), None)
+            .unwrap_err();
+        let shadowed =
+            censor
+                .clone()
+                .with_shadow(true)
+                .censor(String::from("fuck"), Box::new([]), None);
+
+        assert_eq!(link.censored, "see *************");
+        match error {
+            Error::ContentRejected(report) => {
+                assert_eq!(report.verdict, Verdict::Censor);
+                assert_eq!(report.spans, vec![Span::new(1..4, SpanKind::Profanity)]);
+            }
+            error => panic!("unexpected error {error}"),
+        }
+        assert!(shadowed.is_ok());
+    }
+
+    #[test]
+    fn category_verdict() {
+        let censor = Censor::new()
+            .with_categories(
+                CategoryRegistry::new()
+                    .with(CustomCategory::new("phishing").with_rules([CensorTypes::Link])),
+            )
+            .with_thresholds(Thresholds::new().with_category("phishing", Verdict::Block));
+
+        let link = censor
+            .censor(
+                String::from("see https://a.com"),
+                Box::new([CensorTypes::Link]),
+                None,
+            )
+            .unwrap();
+        let email = censor
+            .censor(
+                String::from("mail me@example.net"),
+                Box::new([CensorTypes::Email]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(link.categories, ["phishing"]);
+        assert_eq!(link.verdict, Verdict::Block);
+        assert!(email.categories.is_empty());
+        assert_eq!(email.verdict, Verdict::Censor);
+    }
+
+    #[test]
+    fn shadow_mode() {
+        let censor = Censor::new().with_shadow(true);
+
+        let censored = censor
+            .censor(String::from("fuck"), Box::new([]), None)
+            .unwrap();
+
+        assert_eq!(censored.censored, "fuck");
+        assert_eq!(censored.shadow.as_deref(), Some("f***"));
+        assert!(!censored.valid);
+        assert_eq!(censored.verdict, Verdict::Censor);
+        assert_eq!(censored.report().censored, "f***");
+    }
+
+    #[test]
+    fn mention_spam() {
+        let censor = Censor::new().with_mention_limit(2, true);
+
+        let censored = censor
+            .censor(
+                String::from("@a <@123> @c me@example.net"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+        let allowed = censor
+            .censor(String::from("@a @b"), Box::new([]), None)
+            .unwrap();
+
+        assert_eq!(censored.censored, "@a <@123> ** me@example.net");
+        assert!(censored.typ.is(Type::SPAM));
+        assert!(!allowed.typ.is(Type::SPAM));
+    }
+
+    #[test]
+    fn diff_after_multibyte_matches() {
+        let censored = censor(
+            String::from("żółw@x.pl fuck"),
+            Box::new([CensorTypes::Email]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(censored.censored, "********* f***");
+        assert_eq!(
+            censored.diff(),
+            [
+                Hunk {
+                    range: 0..12,
+                    original: String::from("żółw@x.pl"),
+                    replacement: String::from("*********"),
+                },
+                Hunk {
+                    range: 14..17,
+                    original: String::from("uck"),
+                    replacement: String::from("***"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mention_spans_with_wide_replacement() {
+        let censor = Censor::new()
+            .with_replacement('█')
+            .with_mention_limit(0, true)
+            .with_link_guard(LinkGuard::new(0, Duration::from_secs(60)));
+
+        let censored = censor
+            .censor_user(
+                "wide",
+                String::from("fuck @bob https://a.com"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "f███ ████ █████████████");
+        let spans: Vec<_> = censored
+            .spans
+            .iter()
+            .filter(|span| span.kind != SpanKind::Profanity)
+            .map(|span| (span.range(), &censored.original[span.range()]))
+            .collect();
+        assert_eq!(spans, [(5..9, "@bob"), (10..23, "https://a.com")]);
+    }
+
+    #[test]
+    fn mention_spans_after_multibyte_matches() {
+        let censor = Censor::new().with_mention_limit(0, true);
+
+        let censored = censor
+            .censor_user(
+                "multibyte",
+                String::from("żółw@x.pl @bob"),
+                Box::new([CensorTypes::Email]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "********* ****");
+        let spans: Vec<_> = censored
+            .spans
+            .iter()
+            .map(|span| (span.range(), &censored.original[span.range()]))
+            .collect();
+        assert_eq!(spans, [(0..12, "żółw@x.pl"), (13..17, "@bob")]);
+    }
+
+    #[test]
+    fn links_above_limit_are_masked() {
+        let censor = Censor::new().with_link_guard(LinkGuard::new(1, Duration::from_secs(60)));
+
+        let first = censor
+            .censor_user(
+                "user",
+                String::from("see https://a.com"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+        let second = censor
+            .censor_user(
+                "user",
+                String::from("and https://b.com"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(first.censored, "see https://a.com");
+        assert_eq!(second.censored, "and *************");
+        assert_eq!(second.rules, vec![CensorTypes::Link]);
+        assert!(second.typ.is(Type::SPAM));
+    }
+
+    #[test]
+    fn validity_kinds() {
+        let censor = Censor::new().with_validity(Validity::Kinds(vec![SpanKind::Profanity]));
+
+        let link = censor
+            .censor(
+                String::from("see https://a.com"),
+                Box::new([CensorTypes::Link]),
+                None,
+            )
+            .unwrap();
+        let profanity = censor
+            .censor(
+                String::from("fuck https://a.com"),
+                Box::new([CensorTypes::Link]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(link.censored, "see *************");
+        assert!(link.valid);
+        assert_eq!(link.verdict, Verdict::Pass);
+        assert!(!profanity.valid);
+        assert_eq!(profanity.verdict, Verdict::Censor);
+    }
 }
@@ -0,0 +1,312 @@
+//! `little-censor.toml` configuration shared by the command line tool and the server
+//!
+//! ```toml
+//! rules = ["link", "email"]
+//! custom = 'token=\w+'
+//! threshold = "MODERATE_OR_HIGHER"
+//! replacement = "#"
+//! max_length = 65536
+//! packs = ["words/gaming.lcwp"]
+//!
+//! [thresholds]
+//! review = "SEXUAL"
+//! block = "SEVERE"
+//!
+//! [server]
+//! address = "0.0.0.0:8080"
+//! store = "moderation.db"
+//! admin_token = "change me"
+//! ```
+//!
+//! Every key is optional, relative paths of packs are resolved against the directory of
+//! the configuration file.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::budget::Budget;
+use crate::censor::{Censor, CensorTypes};
+use crate::error::Error;
+use crate::pack::{self, read_pack};
+use crate::report::{Thresholds, Verdict};
+use crate::{type_name, Type, Vulgar};
+
+/// Name of the configuration file looked up in the working directory
+pub const FILE_NAME: &str = "little-censor.toml";
+
+/// Contents of `little-censor.toml`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Additional types of censoring, e.g. `["link", "email"]`
+    #[serde(deserialize_with = "rules")]
+    pub rules: Vec<CensorTypes>,
+    /// Pattern of the `custom` rule
+    #[serde(deserialize_with = "pattern")]
+    pub custom: Option<String>,
+    /// Censors only vulgar words at or above this type
+    #[serde(deserialize_with = "optional_type")]
+    pub threshold: Option<Type>,
+    /// Verdicts for messages of given types
+    pub thresholds: VerdictTypes,
+    /// Character masking censored text
+    pub replacement: Option<char>,
+    /// Maximum length of censored text in bytes, longer text fails with
+    /// `Error::InputTooLong`
+    pub max_length: Option<usize>,
+    /// Word packs added to the word list, see [`crate::pack`]
+    pub packs: Vec<PathBuf>,
+    /// Settings of the `little-censor-server` binary
+    pub server: ServerConfig,
+}
+
+/// Types of messages given each verdict, see [`Thresholds`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerdictTypes {
+    #[serde(deserialize_with = "optional_type")]
+    pub censor: Option<Type>,
+    #[serde(deserialize_with = "optional_type")]
+    pub review: Option<Type>,
+    #[serde(deserialize_with = "optional_type")]
+    pub block: Option<Type>,
+}
+
+/// Settings of the `little-censor-server` binary
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address the server listens on
+    pub address: Option<SocketAddr>,
+    /// Where words added with `PUT /words` are kept, a directory, a `.db` SQLite file
+    /// or a `redis://` URL, in memory by default
+    pub store: Option<String>,
+    /// Bearer token of `/admin` endpoints, which are disabled without it
+    pub admin_token: Option<String>,
+}
+
+/// Invalid configuration, the message points at the offending key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.trim_end())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Parses the configuration, relative paths of packs are kept as they are
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the line and key if the document is malformed, has unknown
+    /// keys or invalid values.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        toml_edit::de::from_str(text).map_err(|error| ConfigError(error.to_string()))
+    }
+
+    /// Reads and parses the configuration file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or [`Config::parse`] fails.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| ConfigError(format!("{}: {error}", path.display())))?;
+        let mut config = Self::parse(&text)
+            .map_err(|error| ConfigError(format!("{}: {error}", path.display())))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for pack in &mut config.packs {
+            *pack = dir.join(&*pack);
+        }
+        Ok(config)
+    }
+
+    /// Loads the file given, or `little-censor.toml` of the working directory if it exists
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if [`Config::load`] fails.
+    pub fn discover(path: Option<&Path>) -> Result<Self, ConfigError> {
+        match path {
+            Some(path) => Self::load(path),
+            None if Path::new(FILE_NAME).is_file() => Self::load(Path::new(FILE_NAME)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Builds censor with the configured threshold, verdicts, replacement, maximum length
+    /// and packs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the pack which can't be read or is malformed.
+    pub fn censor(&self) -> Result<Censor, ConfigError> {
+        self.censor_with_words(Vec::new())
+    }
+
+    /// Builds censor like [`Config::censor`], whose word list has the given words in
+    /// addition to the ones of the packs, e.g. words added while the program runs
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the pack which can't be read or is malformed, or the
+    /// index of an empty word.
+    pub fn censor_with_words(&self, words: Vec<Vulgar>) -> Result<Censor, ConfigError> {
+        let mut censor = Censor::new().with_thresholds(self.thresholds.thresholds());
+        if let Some(threshold) = self.threshold {
+            censor = censor.with_threshold(threshold);
+        }
+        if let Some(replacement) = self.replacement {
+            censor = censor.with_replacement(replacement);
+        }
+        if let Some(max_length) = self.max_length {
+            censor = censor.with_budget(Budget::new().with_max_length(max_length));
+        }
+        if self.packs.is_empty() && words.is_empty() {
+            return Ok(censor);
+        }
+
+        let mut vulgars = Vec::new();
+        let mut packs = Vec::new();
+        for path in &self.packs {
+            let error = |error: &dyn fmt::Display| {
+                ConfigError(format!("packs: {}: {error}", path.display()))
+            };
+            let bytes = std::fs::read(path).map_err(|e| error(&e))?;
+            let words = read_pack(&bytes).map_err(|e| error(&e))?;
+            packs.push(pack::info(&bytes, words.len()));
+            vulgars.extend(words);
+        }
+        let pack_words = vulgars.len();
+        vulgars.extend(words);
+        censor
+            .with_words(vulgars)
+            .map(|censor| censor.with_packs(packs))
+            .map_err(|error| match error {
+                Error::EmptyWord { index } if index >= pack_words => {
+                    let error = Error::EmptyWord {
+                        index: index - pack_words,
+                    };
+                    ConfigError(format!("words: {error}"))
+                }
+                error => ConfigError(format!("packs: {error}")),
+            })
+    }
+}
+
+impl VerdictTypes {
+    /// Thresholds with a rule for every configured verdict
+    pub fn thresholds(&self) -> Thresholds {
+        [
+            (self.censor, Verdict::Censor),
+            (self.review, Verdict::Review),
+            (self.block, Verdict::Block),
+        ]
+        .into_iter()
+        .fold(Thresholds::new(), |thresholds, (typ, verdict)| match typ {
+            Some(typ) => thresholds.with(typ, verdict),
+            None => thresholds,
+        })
+    }
+}
+
+/// Parses lowercase name of an additional type of censoring, e.g. `"link"`
+pub fn parse_rule(name: &str) -> Option<CensorTypes> {
+    match name {
+        "link" => Some(CensorTypes::Link),
+        "ip" => Some(CensorTypes::IP),
+        "email" => Some(CensorTypes::Email),
+        "custom" => Some(CensorTypes::Custom),
+        _ => None,
+    }
+}
+
+fn rules<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<CensorTypes>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| {
+            parse_rule(name).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "unknown rule `{name}`, expected one of `link`, `ip`, `email`, `custom`"
+                ))
+            })
+        })
+        .collect()
+}
+
+fn pattern<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    match regex::Regex::new(&pattern) {
+        Ok(_) => Ok(Some(pattern)),
+        Err(error) => Err(serde::de::Error::custom(error)),
+    }
+}
+
+fn optional_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Type>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    match type_name::parse(&name) {
+        Ok(typ) => Ok(Some(typ)),
+        Err(_) => Err(serde::de::Error::custom(format!(
+            "unknown type `{name}`, expected a type like \"SEXUAL|SEVERE\""
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn parses_every_key() {
+        let config = Config::parse(
+            r##"
+rules = ["link", "custom"]
+custom = '#\d+'
+threshold = "SEVERE"
+replacement = "#"
+max_length = 8
+
+[thresholds]
+block = "SEXUAL"
+
+[server]
+address = "0.0.0.0:8080"
+"##,
+        )
+        .unwrap();
+        let censor = config.censor().unwrap();
+
+        assert_eq!(config.rules, [CensorTypes::Link, CensorTypes::Custom]);
+        assert_eq!(config.server.address, Some(([0, 0, 0, 0], 8080).into()));
+        assert_eq!(censor.replacement(), '#');
+        assert_eq!(censor.threshold(), Some(Type::SEVERE));
+        assert_eq!(censor.thresholds().verdict(Type::SEXUAL), Verdict::Block);
+        assert!(matches!(
+            censor.censor(String::from("too long text"), Box::new([]), None),
+            Err(Error::InputTooLong {
+                limit: 8,
+                actual: 13
+            })
+        ));
+    }
+
+    #[test]
+    fn errors_point_at_key() {
+        let error = Config::parse("rules = [\"link\"]\n\n[thresholds]\nblock = \"LOUD\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 4"), "{error}");
+        assert!(error.contains("unknown type `LOUD`"), "{error}");
+
+        let error = Config::parse("replacment = \"#\"").unwrap_err().to_string();
+        assert!(error.contains("replacment"), "{error}");
+    }
+}
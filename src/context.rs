@@ -0,0 +1,793 @@
+//! Per-user conversational context
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+
+use rustrict::{Context, Type};
+use serde::{Deserialize, Serialize};
+
+use crate::censor::{Censor, CensorTypes, Censored};
+use crate::error::Error;
+use crate::phrase::tokenize;
+use crate::report::Severity;
+use crate::words;
+
+/// Number of recent messages remembered by default
+const DEFAULT_WINDOW: usize = 10;
+/// Number of inappropriate messages in the window after which stricter threshold is used
+const DEFAULT_STRICT_AFTER: usize = 3;
+/// Number of consecutive messages joined when looking for words split between them
+const DEFAULT_SPLIT_WINDOW: usize = 3;
+
+/// How much the account is trusted, decides how strictly its messages are censored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Trust {
+    /// Recently created account, censored at `Type::MILD_OR_HIGHER`
+    New,
+    /// Regular account, censored with the censor's threshold
+    #[default]
+    Regular,
+    /// Verified account, censored only at `Type::SEVERE`
+    Verified,
+    /// Moderator, censored only at `Type::SEVERE`
+    Moderator,
+}
+
+impl Trust {
+    /// Threshold used for accounts of this trust, `None` keeps the censor's threshold
+    pub fn threshold(self) -> Option<Type> {
+        match self {
+            Self::New => Some(Type::MILD_OR_HIGHER),
+            Self::Regular => None,
+            Self::Verified | Self::Moderator => Some(Type::SEVERE),
+        }
+    }
+}
+
+/// Weight of the newest message in the toxicity index of a channel by default
+const DEFAULT_SMOOTHING: f64 = 0.05;
+
+/// Rolling toxicity index of every channel
+///
+/// The index is a moving average of severities of messages, from 0 for channels
+/// without any vulgar words to 1 when every message is severe or blocked.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::censor;
+/// use little_censor::context::ChannelHealth;
+///
+/// let mut health = ChannelHealth::new();
+/// for message in ["hi", "fuck", "hello"] {
+///     let censored = censor(String::from(message), Box::new([]), None).unwrap();
+///     health.record("general", &censored);
+/// }
+/// let censored = censor(String::from("hi"), Box::new([]), None).unwrap();
+/// health.record("news", &censored);
+///
+/// assert_eq!(health.worst(1)[0].0, "general");
+/// assert_eq!(health.toxicity("news"), 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelHealth {
+    smoothing: f64,
+    channels: HashMap<String, f64>,
+}
+
+impl Default for ChannelHealth {
+    fn default() -> Self {
+        Self {
+            smoothing: DEFAULT_SMOOTHING,
+            channels: HashMap::new(),
+        }
+    }
+}
+
+impl ChannelHealth {
+    /// Creates index without any messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets weight of the newest message between 0 and 1, higher reacts faster
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Accounts for a message sent to the channel, returns its new toxicity
+    pub fn record(&mut self, channel: &str, censored: &Censored) -> f64 {
+        let score = if censored.blocked.is_some() {
+            1.0
+        } else {
+            Severity::from(censored.typ) as u8 as f64 / Severity::Severe as u8 as f64
+        };
+
+        let toxicity = self.channels.entry(channel.to_owned()).or_default();
+        *toxicity += (score - *toxicity) * self.smoothing;
+        *toxicity
+    }
+
+    /// Toxicity index of the channel, 0 for unknown channels
+    pub fn toxicity(&self, channel: &str) -> f64 {
+        self.channels.get(channel).copied().unwrap_or_default()
+    }
+
+    /// Up to `count` channels with the highest toxicity, the worst first
+    pub fn worst(&self, count: usize) -> Vec<(String, f64)> {
+        let mut channels: Vec<_> = self
+            .channels
+            .iter()
+            .map(|(channel, &toxicity)| (channel.clone(), toxicity))
+            .collect();
+        channels.sort_by(|a, b| b.1.total_cmp(&a.1));
+        channels.truncate(count);
+        channels
+    }
+
+    /// Forgets the channel
+    pub fn forget(&mut self, channel: &str) {
+        self.channels.remove(channel);
+    }
+}
+
+/// Direction in which severity of user's recent messages changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Trend {
+    /// Messages are getting less severe
+    Improving,
+    /// Severity doesn't change much, e.g. after a one-off slip
+    Steady,
+    /// Messages are getting more severe
+    Worsening,
+}
+
+/// Change of severity per message, as a fraction of a level, below which the trend is steady
+const STEADY_SLOPE: f64 = 0.1;
+
+/// Moderation state of a single user accumulated across their messages
+///
+/// Every message goes through rustrict's [`Context`], which blocks spam, repetition and
+/// messages of muted users, and then through the given [`Censor`]. Users who sent several
+/// inappropriate messages recently are censored with a stricter threshold, otherwise the
+/// threshold depends on the [`Trust`] of the account.
+///
+/// Recent messages are also joined together, so a word split between them (`"fu"` followed
+/// by `"ck"`) marks the last message as `Type::EVASIVE`.
+///
+/// First messages of a user can be put on probation with [`UserContext::with_probation`],
+/// they are censored with a stricter threshold and links in them are always masked.
+///
+/// Words on the user's own allowlist, e.g. their surname, are never censored in their
+/// messages, the censor itself stays unchanged for everyone else.
+///
+/// The whole state can be saved and restored with serde, see [`crate::state::save_state`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::Censor;
+/// use little_censor::context::UserContext;
+///
+/// let censor = Censor::new();
+/// let mut context = UserContext::new().with_strict_after(1);
+///
+/// let censored = context
+///     .censor(&censor, String::from("hello"), Box::new([]), None)
+///     .unwrap();
+///
+/// assert_eq!(censored.censored(), "hello");
+/// assert!(!context.is_strict());
+/// ```
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserContext {
+    context: Context,
+    recent: VecDeque<Type>,
+    window: usize,
+    strict_after: usize,
+    strict_threshold: Type,
+    fragments: VecDeque<String>,
+    split_window: usize,
+    trust: Trust,
+    allowed: HashSet<String>,
+    probation: usize,
+    probation_threshold: Type,
+    messages: usize,
+}
+
+impl Default for UserContext {
+    fn default() -> Self {
+        Self {
+            context: Context::new(),
+            recent: VecDeque::new(),
+            window: DEFAULT_WINDOW,
+            strict_after: DEFAULT_STRICT_AFTER,
+            strict_threshold: Type::MILD_OR_HIGHER,
+            fragments: VecDeque::new(),
+            split_window: DEFAULT_SPLIT_WINDOW,
+            trust: Trust::default(),
+            allowed: HashSet::new(),
+            probation: 0,
+            probation_threshold: Type::MILD_OR_HIGHER,
+            messages: 0,
+        }
+    }
+}
+
+impl UserContext {
+    /// Creates context of a user without any history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets number of recent messages taken into account
+    pub fn with_window(mut self, messages: usize) -> Self {
+        self.window = messages;
+        self.recent.truncate(messages);
+        self
+    }
+
+    /// Sets number of recent inappropriate messages after which user is treated strictly
+    pub fn with_strict_after(mut self, offences: usize) -> Self {
+        self.strict_after = offences;
+        self
+    }
+
+    /// Sets threshold used for repeat offenders, `Type::MILD_OR_HIGHER` by default
+    pub fn with_strict_threshold(mut self, threshold: Type) -> Self {
+        self.strict_threshold = threshold;
+        self
+    }
+
+    /// Sets number of consecutive messages joined when looking for split words, 0 disables it
+    pub fn with_split_window(mut self, messages: usize) -> Self {
+        self.split_window = messages;
+        self.fragments.clear();
+        self
+    }
+
+    /// Sets trust of the account
+    pub fn with_trust(mut self, trust: Trust) -> Self {
+        self.trust = trust;
+        self
+    }
+
+    /// Changes trust of the account, e.g. after it was verified
+    pub fn set_trust(&mut self, trust: Trust) {
+        self.trust = trust;
+    }
+
+    /// Trust of the account
+    pub fn trust(&self) -> Trust {
+        self.trust
+    }
+
+    /// Puts first `messages` of the user on probation, 0 disables it
+    pub fn with_probation(mut self, messages: usize) -> Self {
+        self.probation = messages;
+        self
+    }
+
+    /// Sets threshold used during probation, `Type::MILD_OR_HIGHER` by default
+    pub fn with_probation_threshold(mut self, threshold: Type) -> Self {
+        self.probation_threshold = threshold;
+        self
+    }
+
+    /// Whether the next message of the user is on probation
+    pub fn on_probation(&self) -> bool {
+        self.messages < self.probation
+    }
+
+    /// Sets words which are never censored in messages of this user
+    pub fn with_allowed_words<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.allowed = words
+            .into_iter()
+            .map(|word| word.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Adds word to the allowlist of this user
+    pub fn allow(&mut self, word: &str) {
+        self.allowed.insert(word.to_lowercase());
+    }
+
+    /// Removes word from the allowlist of this user
+    pub fn disallow(&mut self, word: &str) {
+        self.allowed.remove(&word.to_lowercase());
+    }
+
+    /// Threshold used for the next message of this user
+    pub fn threshold(&self, censor: &Censor) -> Type {
+        if self.is_strict() {
+            self.strict_threshold
+        } else if self.on_probation() {
+            self.probation_threshold
+        } else {
+            self.trust
+                .threshold()
+                .or_else(|| censor.threshold())
+                .unwrap_or(Type::INAPPROPRIATE)
+        }
+    }
+
+    /// Number of inappropriate messages in the window
+    pub fn offences(&self) -> usize {
+        self.recent
+            .iter()
+            .filter(|typ| typ.is(Type::INAPPROPRIATE))
+            .count()
+    }
+
+    /// Whether the user is currently treated as a repeat offender
+    pub fn is_strict(&self) -> bool {
+        self.strict_after > 0 && self.offences() >= self.strict_after
+    }
+
+    /// Types of the most recent messages, oldest first
+    pub fn recent(&self) -> impl Iterator<Item = Type> + '_ {
+        self.recent.iter().copied()
+    }
+
+    /// Direction of severity of the messages in the window
+    ///
+    /// Fits a line through severity levels of the messages, so a single slip
+    /// weighs much less than a sustained change.
+    pub fn trend(&self) -> Trend {
+        let slope = self.severity_slope();
+        if slope > STEADY_SLOPE {
+            Trend::Worsening
+        } else if slope < -STEADY_SLOPE {
+            Trend::Improving
+        } else {
+            Trend::Steady
+        }
+    }
+
+    /// Least squares slope of severity levels of the messages in the window
+    fn severity_slope(&self) -> f64 {
+        let n = self.recent.len() as f64;
+        if self.recent.len() < 2 {
+            return 0.0;
+        }
+
+        let levels = self
+            .recent
+            .iter()
+            .map(|&typ| Severity::from(typ) as u8 as f64);
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = levels.clone().sum::<f64>() / n;
+        let (covariance, variance) = levels.enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x as f64 - mean_x;
+            (cov + dx * (y - mean_y), var + dx * dx)
+        });
+        covariance / variance
+    }
+
+    /// Underlying rustrict context, e.g. to mute the user
+    pub fn rustrict_context(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    /// Censors message of this user and remembers its analysis
+    ///
+    /// If rustrict's context decides to block the message it's still censored, the
+    /// reason is available in [`Censored::blocked`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    pub fn censor(
+        &mut self,
+        censor: &Censor,
+        sentence: String,
+        types: Box<[CensorTypes]>,
+        arg: Option<String>,
+    ) -> Result<Censored, Error> {
+        let allowed = self.allowed_words(&sentence);
+        let analyzed = blank(&sentence, &allowed);
+        let blocked = {
+            let _trie = words::read_trie();
+            self.context.process(analyzed.clone()).err()
+        };
+        let threshold = self.threshold(censor);
+        let fragment = analyzed.trim().to_owned();
+        let types = if self.on_probation() && !types.contains(&CensorTypes::Link) {
+            types.iter().copied().chain([CensorTypes::Link]).collect()
+        } else {
+            types
+        };
+
+        // Finished only after the adjustments below, so hooks and strict and shadow modes
+        // see restored words and split words
+        let mut censored = censor.analyze(analyzed, &types, arg.as_deref(), threshold)?;
+        censored.blocked = blocked;
+        if !allowed.is_empty() {
+            censored.spans.retain(|span| {
+                !allowed
+                    .iter()
+                    .any(|word| word.start < span.end && span.start < word.end)
+            });
+            censored.censored = restore(&censored.censored, &sentence, &allowed);
+            censored.shadow = censored
+                .shadow
+                .map(|shadow| restore(&shadow, &sentence, &allowed));
+            censored.valid = censored.censored == sentence;
+            censored.original = sentence;
+        }
+
+        if self.is_split(&fragment, censored.typ, threshold) {
+            censored.typ = (censored.typ & !Type::SAFE) | (Type::EVASIVE & Type::MODERATE);
+            // Nothing is masked, but the message completes a word which would be
+            censored.valid = false;
+        }
+        censor.judge(&mut censored);
+
+        self.messages = self.messages.saturating_add(1);
+        self.remember(censored.typ);
+        self.remember_fragment(fragment, censored.typ, threshold);
+        censor.finish(censored, None)
+    }
+
+    /// Byte ranges of allowed words in the sentence
+    fn allowed_words(&self, sentence: &str) -> Vec<Range<usize>> {
+        tokenize(sentence)
+            .into_iter()
+            .filter(|(word, _)| self.allowed.contains(word))
+            .map(|(_, range)| range)
+            .collect()
+    }
+
+    /// Whether the message completes a word started in previous messages
+    fn is_split(&self, fragment: &str, typ: Type, threshold: Type) -> bool {
+        if self.fragments.is_empty() || typ.is(threshold) {
+            return false;
+        }
+
+        // Try every suffix of the window, so only consecutive messages are joined
+        (0..self.fragments.len()).any(|start| {
+            let joined: String = self
+                .fragments
+                .iter()
+                .skip(start)
+                .map(String::as_str)
+                .chain([fragment])
+                .collect();
+            let _trie = words::read_trie();
+            rustrict::Censor::from_str(&joined).analyze().is(threshold)
+        })
+    }
+
+    fn remember_fragment(&mut self, fragment: String, typ: Type, threshold: Type) {
+        // Inappropriate messages are reported on their own, joining them would flag what follows
+        if self.split_window < 2 || typ.is(threshold) {
+            self.fragments.clear();
+            return;
+        }
+        if self.fragments.len() == self.split_window - 1 {
+            self.fragments.pop_front();
+        }
+        self.fragments.push_back(fragment);
+    }
+
+    fn remember(&mut self, typ: Type) {
+        if self.window == 0 {
+            return;
+        }
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(typ);
+    }
+}
+
+/// Replaces every byte of the words with a space, so spans of the blanked sentence are
+/// spans of the original one
+fn blank(sentence: &str, words: &[Range<usize>]) -> String {
+    let mut blanked = sentence.to_owned();
+    for word in words {
+        blanked.replace_range(word.clone(), &" ".repeat(word.len()));
+    }
+    blanked
+}
+
+/// Puts the words of the original back into the sentence censored after blanking them
+fn restore(censored: &str, original: &str, words: &[Range<usize>]) -> String {
+    // Censoring keeps the number of characters, rule matches are masked once per
+    // character too, so characters of the censored sentence are at the positions of the
+    // blanked one
+    let blanked = blank(original, words);
+    let mut restored = String::with_capacity(original.len());
+    for ((offset, _), c) in blanked.char_indices().zip(censored.chars()) {
+        match words.iter().find(|word| word.contains(&offset)) {
+            Some(word) if word.start == offset => restored.push_str(&original[word.clone()]),
+            Some(_) => {}
+            None => restored.push(c),
+        }
+    }
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_offender_is_strict() {
+        let censor = Censor::new();
+        let mut context = UserContext::new().with_strict_after(2);
+
+        for _ in 0..2 {
+            assert!(!context.is_strict());
+            context
+                .censor(&censor, String::from("fuck you"), Box::new([]), None)
+                .unwrap();
+        }
+
+        assert_eq!(context.offences(), 2);
+        assert!(context.is_strict());
+    }
+
+    #[test]
+    fn offences_leave_window() {
+        let censor = Censor::new();
+        let mut context = UserContext::new().with_window(2).with_strict_after(1);
+
+        context
+            .censor(&censor, String::from("fuck you"), Box::new([]), None)
+            .unwrap();
+        assert!(context.is_strict());
+
+        for _ in 0..2 {
+            context
+                .censor(&censor, String::from("hello there"), Box::new([]), None)
+                .unwrap();
+        }
+        assert!(!context.is_strict());
+        assert_eq!(context.recent().count(), 2);
+    }
+
+    #[test]
+    fn severity_trend() {
+        let censor = Censor::new();
+        let mut context = UserContext::new();
+        assert_eq!(context.trend(), Trend::Steady);
+
+        for message in ["hello", "oh shit", "fuck you"] {
+            context
+                .censor(&censor, String::from(message), Box::new([]), None)
+                .unwrap();
+        }
+        assert_eq!(context.trend(), Trend::Worsening);
+
+        for _ in 0..8 {
+            context
+                .censor(&censor, String::from("sorry"), Box::new([]), None)
+                .unwrap();
+        }
+        assert_eq!(context.trend(), Trend::Improving);
+    }
+
+    #[test]
+    fn channel_toxicity() {
+        let censor = Censor::new();
+        let mut health = ChannelHealth::new().with_smoothing(0.5);
+        let severe = censor
+            .censor(String::from("fuck"), Box::new([]), None)
+            .unwrap();
+        let clean = censor
+            .censor(String::from("hello"), Box::new([]), None)
+            .unwrap();
+
+        assert_eq!(health.record("general", &severe), 0.5);
+        assert_eq!(health.record("general", &severe), 0.75);
+        assert_eq!(health.record("general", &clean), 0.375);
+        assert_eq!(health.worst(5), vec![(String::from("general"), 0.375)]);
+    }
+
+    #[test]
+    fn split_word_is_evasive() {
+        let censor = Censor::new();
+        let mut context = UserContext::new();
+
+        let first = context
+            .censor(&censor, String::from("fu"), Box::new([]), None)
+            .unwrap();
+        let second = context
+            .censor(&censor, String::from("ck"), Box::new([]), None)
+            .unwrap();
+
+        assert!(!first.typ.is(Type::EVASIVE));
+        assert!(second.typ.is(Type::EVASIVE));
+    }
+
+    #[test]
+    fn split_word_reaches_hook_and_strict_mode() {
+        let (sender, reports) = std::sync::mpsc::channel();
+        let censor = Censor::new()
+            .with_strict(true)
+            .with_hook(Type::MODERATE_OR_HIGHER, std::sync::Mutex::new(sender));
+        let mut context = UserContext::new();
+
+        context
+            .censor(&censor, String::from("fu"), Box::new([]), None)
+            .unwrap();
+        let second = context.censor(&censor, String::from("ck"), Box::new([]), None);
+
+        assert!(matches!(second, Err(Error::ContentRejected(_))));
+        assert!(reports
+            .try_recv()
+            .unwrap()
+            .reasons
+            .contains(&crate::report::Reason::Evasive));
+    }
+
+    #[test]
+    fn allowed_words_in_shadow_mode() {
+        let mut context = UserContext::new().with_allowed_words(["Dick"]);
+
+        let censored = context
+            .censor(
+                &Censor::new().with_shadow(true),
+                String::from("Dick fuck"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "Dick fuck");
+        assert_eq!(censored.shadow.as_deref(), Some("Dick f***"));
+        assert!(!censored.valid);
+    }
+
+    #[test]
+    fn split_window_disabled() {
+        let censor = Censor::new();
+        let mut context = UserContext::new().with_split_window(0);
+
+        context
+            .censor(&censor, String::from("fu"), Box::new([]), None)
+            .unwrap();
+        let second = context
+            .censor(&censor, String::from("ck"), Box::new([]), None)
+            .unwrap();
+
+        assert!(!second.typ.is(Type::EVASIVE));
+    }
+
+    #[test]
+    fn trust_changes_threshold() {
+        let censor = Censor::new();
+        let mut context = UserContext::new().with_trust(Trust::New);
+        assert_eq!(context.threshold(&censor), Type::MILD_OR_HIGHER);
+
+        context.set_trust(Trust::Verified);
+        let censored = context
+            .censor(&censor, String::from("oh shit"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "oh shit");
+
+        let censored = context
+            .censor(&censor, String::from("fuck"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "f***");
+    }
+
+    #[test]
+    fn first_messages_on_probation() {
+        let censor = Censor::new();
+        let mut context = UserContext::new().with_probation(1);
+
+        let first = context
+            .censor(
+                &censor,
+                String::from("https://example.net"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+        let second = context
+            .censor(
+                &censor,
+                String::from("https://example.net"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(first.censored, "*******************");
+        assert_eq!(second.censored, "https://example.net");
+        assert!(!context.on_probation());
+    }
+
+    #[test]
+    fn restore_state() {
+        let censor = Censor::new();
+        let mut context = UserContext::new()
+            .with_strict_after(1)
+            .with_trust(Trust::Verified);
+        context
+            .censor(&censor, String::from("fuck you"), Box::new([]), None)
+            .unwrap();
+
+        let mut saved = Vec::new();
+        crate::state::save_state(&context, &mut saved).unwrap();
+        let restored: UserContext = crate::state::load_state(saved.as_slice()).unwrap();
+
+        assert!(restored.is_strict());
+        assert_eq!(restored.trust(), Trust::Verified);
+    }
+
+    #[test]
+    fn allowed_words_of_user() {
+        let censor = Censor::new();
+        let mut owner = UserContext::new().with_allowed_words(["Shit"]);
+        let mut other = UserContext::new();
+
+        let own = owner
+            .censor(&censor, String::from("oh shit, fuck"), Box::new([]), None)
+            .unwrap();
+        let foreign = other
+            .censor(&censor, String::from("oh shit"), Box::new([]), None)
+            .unwrap();
+
+        assert_eq!(own.censored, "oh shit, f***");
+        assert_eq!(own.original, "oh shit, fuck");
+        assert_eq!(foreign.censored, "oh s***");
+    }
+
+    #[test]
+    fn non_ascii_allowed_words() {
+        let mut context = UserContext::new().with_allowed_words(["Müller"]);
+
+        let censored = context
+            .censor(
+                &Censor::new(),
+                String::from("Müller fuck"),
+                Box::new([]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "Müller f***");
+        assert_eq!(censored.spans.len(), 1);
+        assert_eq!(censored.spans[0].range(), 9..12);
+        assert_eq!(&censored.original[9..12], "uck");
+    }
+
+    #[test]
+    fn allowed_words_after_multibyte_matches() {
+        let mut context = UserContext::new().with_allowed_words(["Müller"]);
+
+        let censored = context
+            .censor(
+                &Censor::new(),
+                String::from("żółw@x.pl Müller fuck"),
+                Box::new([CensorTypes::Email]),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(censored.censored, "********* Müller f***");
+        let spans: Vec<_> = censored
+            .spans
+            .iter()
+            .map(|span| &censored.original[span.range()])
+            .collect();
+        assert_eq!(spans, ["żółw@x.pl", "uck"]);
+    }
+
+    #[test]
+    fn empty_message_is_blocked() {
+        let mut context = UserContext::new();
+        let censored = context
+            .censor(&Censor::new(), String::from("  "), Box::new([]), None)
+            .unwrap();
+
+        assert!(censored.blocked.is_some());
+    }
+}
@@ -0,0 +1,176 @@
+//! Detection of repeated messages per user
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of recent messages remembered for every user
+const HISTORY: usize = 20;
+
+/// Default minimal similarity of near duplicates
+const DEFAULT_SIMILARITY: f64 = 0.9;
+
+/// Default number of allowed copies of a message within the window
+const DEFAULT_MAX_REPEATS: usize = 2;
+
+#[derive(Debug)]
+struct Seen {
+    at: Instant,
+    hash: u64,
+    normalized: String,
+}
+
+/// Flags users repeating identical or nearly identical messages
+///
+/// Messages are compared after normalization (case, punctuation and whitespace are
+/// ignored), identical ones by hash and the rest by similarity of their character bigrams.
+///
+/// Attach it to a censor with [`crate::censor::Censor::with_duplicate_guard`], then repeated
+/// messages censored with [`crate::censor::Censor::censor_user`] are typed as spam.
+///
+/// Requires a clock, so it can't be used on `wasm32-unknown-unknown`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use little_censor::duplicate::DuplicateGuard;
+///
+/// let guard = DuplicateGuard::new(Duration::from_secs(60)).with_max_repeats(1);
+///
+/// assert!(!guard.hit("user", "Buy cheap gold now!"));
+/// assert!(guard.hit("user", "buy cheap gold now"));
+/// assert!(!guard.hit("other", "buy cheap gold now"));
+/// ```
+#[derive(Debug)]
+pub struct DuplicateGuard {
+    window: Duration,
+    max_repeats: usize,
+    similarity: f64,
+    users: Mutex<HashMap<String, VecDeque<Seen>>>,
+}
+
+impl DuplicateGuard {
+    /// Compares messages sent within `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            max_repeats: DEFAULT_MAX_REPEATS,
+            similarity: DEFAULT_SIMILARITY,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets number of copies of a message allowed within the window, 2 by default
+    pub fn with_max_repeats(mut self, repeats: usize) -> Self {
+        self.max_repeats = repeats;
+        self
+    }
+
+    /// Sets minimal similarity (`0.0..=1.0`) of near duplicates, 0.9 by default
+    pub fn with_similarity(mut self, similarity: f64) -> Self {
+        self.similarity = similarity;
+        self
+    }
+
+    /// Records message of the user sent now, returns whether it repeats previous ones
+    pub fn hit(&self, user: &str, message: &str) -> bool {
+        self.hit_at(user, message, Instant::now())
+    }
+
+    /// Records message of the user sent at given time, returns whether it repeats previous ones
+    pub fn hit_at(&self, user: &str, message: &str, at: Instant) -> bool {
+        let normalized = normalize(message);
+        let mut hasher = DefaultHasher::new();
+        normalized.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        let seen = users.entry(user.to_owned()).or_default();
+        seen.retain(|seen| at.saturating_duration_since(seen.at) < self.window);
+
+        let copies = seen
+            .iter()
+            .filter(|seen| {
+                seen.hash == hash || similarity(&seen.normalized, &normalized) >= self.similarity
+            })
+            .count();
+
+        if seen.len() == HISTORY {
+            seen.pop_front();
+        }
+        seen.push_back(Seen {
+            at,
+            hash,
+            normalized,
+        });
+
+        copies >= self.max_repeats
+    }
+
+    /// Forgets messages of the user
+    pub fn forget(&self, user: &str) {
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(user);
+    }
+}
+
+/// Lowercase alphanumeric characters separated by single spaces
+fn normalize(message: &str) -> String {
+    message
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Dice coefficient of character bigrams
+fn similarity(a: &str, b: &str) -> f64 {
+    fn bigrams(text: &str) -> HashSet<(char, char)> {
+        let chars: Vec<char> = text.chars().collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    if a == b {
+        return 1.0;
+    }
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize("  Hello,   WORLD!! "), "hello world");
+    }
+
+    #[test]
+    fn near_duplicates() {
+        let guard = DuplicateGuard::new(Duration::from_secs(60)).with_max_repeats(1);
+
+        assert!(!guard.hit("user", "join my server at discord for free nitro"));
+        assert!(guard.hit("user", "join my server at discord for free nitro!!1"));
+        assert!(!guard.hit("user", "what a nice weather today"));
+    }
+
+    #[test]
+    fn copies_leave_window() {
+        let guard = DuplicateGuard::new(Duration::from_secs(10)).with_max_repeats(1);
+        let start = Instant::now();
+
+        assert!(!guard.hit_at("user", "spam", start));
+        assert!(guard.hit_at("user", "spam", start + Duration::from_secs(5)));
+        assert!(!guard.hit_at("user", "spam", start + Duration::from_secs(20)));
+    }
+}
@@ -0,0 +1,638 @@
+//! Censoring email messages, e.g. support mailboxes before they're archived
+//!
+//! `text/plain` and `text/html` parts and selected headers of `.eml` messages are
+//! censored, other parts, headers and MIME boundaries are written as they were. Bodies
+//! are decoded from their transfer encoding and encoded back, so the message stays valid.
+//! Parts in charsets other than UTF-8 and ASCII are left as they are.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use regex::bytes::Regex;
+
+use crate::error::Error;
+use crate::html::{censor_html, HtmlPolicy};
+use crate::json::JsonOptions;
+
+/// Headers with addresses replaced by pseudonyms
+const ADDRESS_HEADERS: [&str; 5] = ["from", "to", "cc", "reply-to", "sender"];
+
+/// Domain of pseudonymous addresses, reserved so it never receives mail
+const PSEUDONYM_DOMAIN: &str = "pseudonym.invalid";
+
+/// Encoded word of a header, e.g. `=?UTF-8?B?SGk=?=`
+static ENCODED_WORD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"=\?([^?\s]+)\?([bBqQ])\?([^?\s]*)\?=").expect("valid regex"));
+
+/// Which parts of a message are censored and how
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::eml::{censor_eml, EmailPolicy};
+///
+/// let mut message = b"From: Jane <jane@example.net>\r\nSubject: fuck\r\n\r\nfuck off\r\n".to_vec();
+///
+/// censor_eml(&mut message, &EmailPolicy::new().with_pseudonyms(true)).unwrap();
+///
+/// let message = String::from_utf8(message).unwrap();
+/// assert!(message.starts_with("From: <"));
+/// assert!(message.ends_with("Subject: f***\r\n\r\nf*** off\r\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmailPolicy {
+    options: JsonOptions,
+    headers: Vec<String>,
+    pseudonyms: bool,
+}
+
+impl Default for EmailPolicy {
+    fn default() -> Self {
+        Self {
+            options: JsonOptions::new(),
+            headers: vec![String::from("subject")],
+            pseudonyms: false,
+        }
+    }
+}
+
+impl EmailPolicy {
+    /// Censors text bodies and the `Subject` header with a default censor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Censors text with the censor and rules of the options, key patterns aren't used
+    pub fn with_options(mut self, options: JsonOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Censors these headers instead of `Subject`, names are case insensitive
+    pub fn with_headers<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.headers = names
+            .into_iter()
+            .map(|name| name.as_ref().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Whether mailboxes of `From`, `To`, `Cc`, `Reply-To` and `Sender` are replaced by
+    /// pseudonymous addresses, the same address always gets the same pseudonym
+    pub fn with_pseudonyms(mut self, pseudonyms: bool) -> Self {
+        self.pseudonyms = pseudonyms;
+        self
+    }
+}
+
+/// Censors the message in place, returns number of modified headers and parts
+///
+/// # Errors
+///
+/// Returns `Error::Storage` if a censored part isn't encoded correctly, otherwise the
+/// same errors as [`crate::censor::Censor::censor`].
+pub fn censor_eml(message: &mut Vec<u8>, policy: &EmailPolicy) -> Result<usize, Error> {
+    let newline: &[u8] = match message.iter().position(|&byte| byte == b'\n') {
+        Some(index) if index > 0 && message[index - 1] == b'\r' => b"\r\n",
+        Some(_) => b"\n",
+        None => b"\r\n",
+    };
+    let mut censored = Vec::with_capacity(message.len());
+    let modified = Entity {
+        policy,
+        newline,
+        output: &mut censored,
+    }
+    .censor(message)?;
+    *message = censored;
+    Ok(modified)
+}
+
+/// Writer of a censored message
+struct Entity<'a> {
+    policy: &'a EmailPolicy,
+    /// Line ending used by the message
+    newline: &'a [u8],
+    output: &'a mut Vec<u8>,
+}
+
+impl Entity<'_> {
+    /// Writes a censored entity, its headers and body, returns number of modifications
+    fn censor(&mut self, entity: &[u8]) -> Result<usize, Error> {
+        let (headers, body) = split_headers(entity);
+        let mut modified = 0;
+        let mut content_type = String::new();
+        let mut encoding = String::new();
+
+        for field in fields(headers) {
+            let (name, value) = match field.iter().position(|&byte| byte == b':') {
+                Some(colon) => (&field[..colon], &field[colon + 1..]),
+                None => {
+                    self.output.extend_from_slice(field);
+                    continue;
+                }
+            };
+            let key = String::from_utf8_lossy(name).trim().to_ascii_lowercase();
+            match key.as_str() {
+                "content-type" => content_type = unfold(value),
+                "content-transfer-encoding" => encoding = unfold(value).to_ascii_lowercase(),
+                _ => {}
+            }
+
+            let replacement = if self.policy.headers.contains(&key) {
+                self.censor_header(value)?
+            } else if self.policy.pseudonyms && ADDRESS_HEADERS.contains(&key.as_str()) {
+                pseudonymize(value)
+            } else {
+                None
+            };
+            match replacement {
+                Some(replacement) => {
+                    modified += 1;
+                    self.output.extend_from_slice(name);
+                    self.output.extend_from_slice(b": ");
+                    self.output.extend_from_slice(replacement.as_bytes());
+                    self.output.extend_from_slice(self.newline);
+                }
+                None => self.output.extend_from_slice(field),
+            }
+        }
+
+        let (mime, parameters) = parse_content_type(&content_type);
+        let boundary = parameter(&parameters, "boundary");
+        let charset = parameter(&parameters, "charset").map(str::to_ascii_lowercase);
+        let plain = matches!(encoding.as_str(), "" | "7bit" | "8bit" | "binary");
+        match (mime.as_str(), boundary) {
+            (mime, Some(boundary)) if mime.starts_with("multipart/") => {
+                modified += self.censor_multipart(body, boundary)?;
+            }
+            ("message/rfc822", _) if plain => modified += self.censor(body)?,
+            ("" | "text/plain" | "text/html", _)
+                if charset
+                    .as_deref()
+                    .is_none_or(|charset| matches!(charset, "utf-8" | "us-ascii")) =>
+            {
+                match self.censor_text(body, &encoding, mime == "text/html")? {
+                    Some(censored) => {
+                        modified += 1;
+                        self.output.extend_from_slice(&censored);
+                    }
+                    None => self.output.extend_from_slice(body),
+                }
+            }
+            _ => self.output.extend_from_slice(body),
+        }
+        Ok(modified)
+    }
+
+    /// Censors every part of a multipart body, the preamble and epilogue are kept
+    fn censor_multipart(&mut self, body: &[u8], boundary: &str) -> Result<usize, Error> {
+        let delimiter = format!("--{boundary}");
+        let mut modified = 0;
+        let mut part: Option<usize> = None;
+        let mut position = 0;
+
+        for line in body.split_inclusive(|&byte| byte == b'\n') {
+            let start = position;
+            position += line.len();
+            let content = trim_newline(line);
+            if !content.starts_with(delimiter.as_bytes()) {
+                if part.is_none() {
+                    self.output.extend_from_slice(line);
+                }
+                continue;
+            }
+
+            // Line break before the delimiter belongs to it
+            if let Some(part_start) = part {
+                let text = &body[part_start..start];
+                let without_newline = trim_newline(text);
+                modified += self.censor(without_newline)?;
+                self.output
+                    .extend_from_slice(&text[without_newline.len()..]);
+            }
+            self.output.extend_from_slice(line);
+            if content[delimiter.len()..].starts_with(b"--") {
+                self.output.extend_from_slice(&body[position..]);
+                return Ok(modified);
+            }
+            part = Some(position);
+        }
+        // Unterminated multipart, the last part is censored to the end
+        if let Some(part_start) = part {
+            modified += self.censor(&body[part_start..])?;
+        }
+        Ok(modified)
+    }
+
+    /// Censored text body in its transfer encoding, `None` if nothing changed
+    fn censor_text(
+        &self,
+        body: &[u8],
+        encoding: &str,
+        html: bool,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let decoded = match encoding {
+            "quoted-printable" => decode_quoted_printable(body),
+            "base64" => {
+                let compact: Vec<u8> = body
+                    .iter()
+                    .copied()
+                    .filter(|byte| !byte.is_ascii_whitespace())
+                    .collect();
+                STANDARD.decode(compact).map_err(|_| Error::Storage)?
+            }
+            _ => body.to_vec(),
+        };
+        let mut text = match String::from_utf8(decoded) {
+            Ok(text) => text,
+            Err(_) => return Ok(None),
+        };
+
+        let options = &self.policy.options;
+        let modified = if html {
+            censor_html(&mut text, &HtmlPolicy::new().with_options(options.clone()))?
+        } else {
+            let mut censored = String::with_capacity(text.len());
+            let mut modified = 0;
+            for line in text.split_inclusive('\n') {
+                let content = line.trim_end_matches(['\r', '\n']);
+                let result = options.censor(content.to_owned())?;
+                if result.changed() {
+                    modified += 1;
+                }
+                censored.push_str(&result.censored);
+                censored.push_str(&line[content.len()..]);
+            }
+            text = censored;
+            modified
+        };
+        if modified == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(match encoding {
+            "quoted-printable" => encode_quoted_printable(&text, self.newline),
+            "base64" => {
+                let encoded = STANDARD.encode(text);
+                let mut wrapped = encoded
+                    .as_bytes()
+                    .chunks(76)
+                    .collect::<Vec<_>>()
+                    .join(self.newline);
+                // Line break before a boundary belongs to the boundary
+                if body.ends_with(b"\n") {
+                    wrapped.extend_from_slice(self.newline);
+                }
+                wrapped
+            }
+            _ => text.into_bytes(),
+        }))
+    }
+
+    /// Censored header value, `None` if nothing changed
+    fn censor_header(&self, value: &[u8]) -> Result<Option<String>, Error> {
+        let decoded = decode_words(&unfold(value));
+        let result = self.policy.options.censor(decoded)?;
+        if !result.changed() {
+            return Ok(None);
+        }
+        Ok(Some(encode_words(&result.censored, self.newline)))
+    }
+}
+
+/// Splits an entity into its headers, including the blank line after them, and body
+fn split_headers(entity: &[u8]) -> (&[u8], &[u8]) {
+    let mut position = 0;
+    for line in entity.split_inclusive(|&byte| byte == b'\n') {
+        position += line.len();
+        if trim_newline(line).is_empty() {
+            return entity.split_at(position);
+        }
+    }
+    (entity, &[])
+}
+
+/// Header fields with their folded lines, the blank line ending headers is a field too
+fn fields(headers: &[u8]) -> Vec<&[u8]> {
+    let mut fields: Vec<&[u8]> = Vec::new();
+    let mut start = 0;
+    let mut position = 0;
+    for line in headers.split_inclusive(|&byte| byte == b'\n') {
+        let folded = matches!(line.first(), Some(b' ' | b'\t')) && position > 0;
+        if !folded && position > start {
+            fields.push(&headers[start..position]);
+            start = position;
+        }
+        position += line.len();
+    }
+    if position > start {
+        fields.push(&headers[start..position]);
+    }
+    fields
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Header value on a single line, without surrounding whitespace
+fn unfold(value: &[u8]) -> String {
+    let value: Vec<u8> = value
+        .iter()
+        .copied()
+        .filter(|&byte| byte != b'\r' && byte != b'\n')
+        .collect();
+    String::from_utf8_lossy(&value).trim().to_owned()
+}
+
+/// Lowercase MIME type and parameters of a `Content-Type` value
+fn parse_content_type(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let mime = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let parameters = parts
+        .filter_map(|parameter| {
+            let (name, value) = parameter.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((name.trim().to_ascii_lowercase(), value.to_owned()))
+        })
+        .collect();
+    (mime, parameters)
+}
+
+fn parameter<'a>(parameters: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    parameters
+        .iter()
+        .find(|(parameter, _)| parameter == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Decodes encoded words in UTF-8 or ASCII, others are kept as they are
+fn decode_words(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut written = 0;
+    let mut previous_word = false;
+    for captures in ENCODED_WORD.captures_iter(bytes) {
+        let word = captures.get(0).expect("whole match");
+        let charset = String::from_utf8_lossy(&captures[1]).to_ascii_lowercase();
+        let text = &captures[3];
+        let text = match (charset.as_str(), &captures[2]) {
+            ("utf-8" | "us-ascii", b"B" | b"b") => STANDARD.decode(text).ok(),
+            ("utf-8" | "us-ascii", _) => Some(decode_q(text)),
+            _ => None,
+        };
+        let text = match text {
+            Some(text) => text,
+            None => {
+                previous_word = false;
+                continue;
+            }
+        };
+        // Whitespace between adjacent encoded words isn't part of the text
+        let gap = &bytes[written..word.start()];
+        if !(previous_word && gap.iter().all(u8::is_ascii_whitespace)) {
+            decoded.extend_from_slice(gap);
+        }
+        decoded.extend_from_slice(&text);
+        written = word.end();
+        previous_word = true;
+    }
+    decoded.extend_from_slice(&bytes[written..]);
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Decodes text of a `Q` encoded word
+fn decode_q(text: &[u8]) -> Vec<u8> {
+    let spaces: Vec<u8> = text
+        .iter()
+        .map(|&byte| if byte == b'_' { b' ' } else { byte })
+        .collect();
+    decode_quoted_printable(&spaces)
+}
+
+/// Header value, as `B` encoded words if it isn't ASCII
+fn encode_words(text: &str, newline: &[u8]) -> String {
+    if text.is_ascii() {
+        return text.to_owned();
+    }
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        // Keeps encoded words within 75 characters
+        if word.len() + c.len_utf8() > 45 {
+            words.push(format!("=?UTF-8?B?{}?=", STANDARD.encode(&word)));
+            word.clear();
+        }
+        word.push(c);
+    }
+    words.push(format!("=?UTF-8?B?{}?=", STANDARD.encode(&word)));
+    words.join(&format!("{} ", String::from_utf8_lossy(newline)))
+}
+
+fn decode_quoted_printable(body: &[u8]) -> Vec<u8> {
+    let hex = |byte: u8| (byte as char).to_digit(16);
+    let mut decoded = Vec::with_capacity(body.len());
+    let mut index = 0;
+    while index < body.len() {
+        let byte = body[index];
+        if byte != b'=' {
+            decoded.push(byte);
+            index += 1;
+            continue;
+        }
+        let rest = &body[index + 1..];
+        if rest.starts_with(b"\r\n") {
+            index += 3;
+        } else if rest.starts_with(b"\n") {
+            index += 2;
+        } else {
+            match (
+                rest.first().and_then(|&b| hex(b)),
+                rest.get(1).and_then(|&b| hex(b)),
+            ) {
+                (Some(high), Some(low)) => {
+                    decoded.push((high * 16 + low) as u8);
+                    index += 3;
+                }
+                _ => {
+                    decoded.push(byte);
+                    index += 1;
+                }
+            }
+        }
+    }
+    decoded
+}
+
+fn encode_quoted_printable(text: &str, newline: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']).as_bytes();
+        let mut length = 0;
+        for (index, &byte) in content.iter().enumerate() {
+            let last = index + 1 == content.len();
+            let literal = matches!(byte, b'!'..=b'<' | b'>'..=b'~')
+                || (matches!(byte, b' ' | b'\t') && !last);
+            let width = if literal { 1 } else { 3 };
+            // Soft line break keeps lines within 76 characters
+            if length + width > 75 {
+                encoded.push(b'=');
+                encoded.extend_from_slice(newline);
+                length = 0;
+            }
+            if literal {
+                encoded.push(byte);
+            } else {
+                encoded.extend_from_slice(format!("={byte:02X}").as_bytes());
+            }
+            length += width;
+        }
+        if line.ends_with('\n') {
+            encoded.extend_from_slice(newline);
+        }
+    }
+    encoded
+}
+
+/// Address list with every mailbox replaced by a pseudonym, `None` if it has none
+fn pseudonymize(value: &[u8]) -> Option<String> {
+    let value = decode_words(&unfold(value));
+    let mut mailboxes = Vec::new();
+    let mut changed = false;
+    for mailbox in split_mailboxes(&value) {
+        let mailbox = mailbox.trim();
+        let address = match (mailbox.rfind('<'), mailbox.rfind('>')) {
+            (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+            _ => mailbox,
+        };
+        if address.contains('@') {
+            changed = true;
+            mailboxes.push(format!("<{}>", pseudonym(address)));
+        } else {
+            mailboxes.push(mailbox.to_owned());
+        }
+    }
+    if changed {
+        Some(mailboxes.join(", "))
+    } else {
+        None
+    }
+}
+
+/// Splits an address list at commas outside of quotes and angle brackets
+fn split_mailboxes(value: &str) -> Vec<&str> {
+    let mut mailboxes = Vec::new();
+    let mut quoted = false;
+    let mut angle = false;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            ',' if !quoted && !angle => {
+                mailboxes.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    mailboxes.push(&value[start..]);
+    mailboxes
+}
+
+/// Pseudonymous address derived from the address with 64 bit FNV-1a, stable across
+/// builds and platforms
+fn pseudonym(address: &str) -> String {
+    let hash = address
+        .trim()
+        .to_lowercase()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}@{PSEUDONYM_DOMAIN}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::CensorTypes;
+
+    const MESSAGE: &str = "From: \"Doe, Jane\" <Jane@example.net>\r\n\
+To: support@example.com, undisclosed-recipients:;\r\n\
+Subject: =?UTF-8?Q?fuck_this_?=\r\n =?UTF-8?B?c3R1cGlkIGFwcA==?=\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+preamble fuck\r\n\
+--outer\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Caf=C3=A9 fuck, mail me at jane@example.net=\r\n please\r\n\
+--outer\r\n\
+Content-Type: text/html; charset=\"UTF-8\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+PHA+ZnVjazwvcD4=\r\n\
+--outer\r\n\
+Content-Type: application/octet-stream\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+ZnVjaw==\r\n\
+--outer--\r\n\
+epilogue fuck\r\n";
+
+    #[test]
+    fn censors_bodies_and_headers() {
+        let mut message = MESSAGE.as_bytes().to_vec();
+        let policy = EmailPolicy::new()
+            .with_options(JsonOptions::new().with_types([CensorTypes::Email]))
+            .with_pseudonyms(true);
+
+        let modified = censor_eml(&mut message, &policy).unwrap();
+
+        let jane = pseudonym("jane@example.net");
+        let support = pseudonym("support@example.com");
+        let expected = MESSAGE
+            .replace("\"Doe, Jane\" <Jane@example.net>", &format!("<{jane}>"))
+            .replace(
+                "support@example.com, undisclosed-recipients:;",
+                &format!("<{support}>, undisclosed-recipients:;"),
+            )
+            .replace(
+                "=?UTF-8?Q?fuck_this_?=\r\n =?UTF-8?B?c3R1cGlkIGFwcA==?=",
+                "f*** this stupid app",
+            )
+            .replace(
+                "Caf=C3=A9 fuck, mail me at jane@example.net=\r\n please",
+                "Caf=C3=A9 f***, mail me at **************** please",
+            )
+            .replace("PHA+ZnVjazwvcD4=", &STANDARD.encode("<p>f***</p>"));
+        assert_eq!(modified, 5);
+        assert_eq!(String::from_utf8(message).unwrap(), expected);
+    }
+
+    #[test]
+    fn quoted_printable_round_trip() {
+        let text = format!("{} café\r\nend =\n", "long ".repeat(20));
+        let encoded = encode_quoted_printable(&text, b"\r\n");
+
+        assert!(encoded
+            .split(|&byte| byte == b'\n')
+            .all(|line| line.len() <= 77));
+        assert_eq!(
+            decode_quoted_printable(&encoded),
+            text.replace('\n', "\r\n")
+                .replace("\r\r\n", "\r\n")
+                .as_bytes()
+        );
+    }
+}
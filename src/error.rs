@@ -1,24 +1,324 @@
 //! Error Module
+use std::fmt;
+
 use thiserror::Error as this_error;
 
-#[cfg(feature = "wasm")]
-use wasm_bindgen::prelude::wasm_bindgen;
+use crate::report::Report;
+
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::JsValue;
 
 #[derive(Debug, this_error)]
-#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("Word can't be empty")]
-    EmptyWord,
+    #[error("Word at index {index} can't be empty")]
+    EmptyWord {
+        /// Position of the word among the words given
+        index: usize,
+    },
 
     #[error("Must specify arguments")]
     NoArgs,
 
-    #[error("Provided Invalid Regex")]
-    InvalidRegex
+    #[error("Provided Invalid Regex `{pattern}`: {source}")]
+    InvalidRegex {
+        /// Pattern which failed to compile
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+
+    #[error("Pattern `{pattern}` exceeds the size limit of {limit} bytes")]
+    PatternTooLarge {
+        /// Pattern which failed to compile
+        pattern: String,
+        /// Size limit of the compiled pattern
+        limit: usize,
+    },
+
+    #[error("Censor budget exceeded")]
+    BudgetExceeded,
+
+    #[error("Profile doesn't exist")]
+    UnknownProfile,
+
+    #[error("Storage failed")]
+    Storage,
+
+    #[error("Storage backend failed: {0}")]
+    Backend(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Type doesn't exist")]
+    UnknownType,
+
+    #[error("I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed input at {at}")]
+    Parse {
+        /// Where the input is malformed
+        at: Position,
+    },
+
+    #[error("Word at index {index} is already in the word list")]
+    DuplicateWord {
+        /// Position of the word among the words given
+        index: usize,
+    },
+
+    #[error("Invalid word entry `{entry}`: {reason}")]
+    InvalidEntry {
+        /// Entry as it was written
+        entry: String,
+        /// What is wrong with it
+        reason: String,
+    },
+
+    #[error("Word at index {index} was rejected: {reason}")]
+    RejectedWord {
+        /// Position of the word among the words given
+        index: usize,
+        /// Rule of the validation it breaks
+        reason: String,
+    },
+
+    #[error("Content was rejected with the verdict {:?}", .0.verdict)]
+    ContentRejected(Report),
+
+    #[error("Input of {actual} bytes exceeds the limit of {limit} bytes")]
+    InputTooLong {
+        /// Maximum length in bytes
+        limit: usize,
+        /// Length of the input in bytes
+        actual: usize,
+    },
 }
 
-impl From<regex::Error> for super::Error {
-    fn from(_: regex::Error) -> Self {
-        Self::InvalidRegex
+impl Error {
+    /// Error of a pattern which failed to compile
+    pub(crate) fn invalid_regex(pattern: &str, source: regex::Error) -> Self {
+        match source {
+            regex::Error::CompiledTooBig(limit) => Self::PatternTooLarge {
+                pattern: pattern.to_owned(),
+                limit,
+            },
+            source => Self::InvalidRegex {
+                pattern: pattern.to_owned(),
+                source,
+            },
+        }
+    }
+
+    /// Error of JSON which couldn't be read, written or parsed
+    pub(crate) fn json(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            Self::Io(error.into())
+        } else {
+            Self::Parse {
+                at: Position::Line(error.line()),
+            }
+        }
+    }
+
+    /// Machine readable code of the error, e.g. `"EMPTY_WORD"`
+    ///
+    /// Codes are stable across releases and the same in every binding.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EmptyWord { .. } => "EMPTY_WORD",
+            Self::NoArgs => "NO_ARGS",
+            Self::InvalidRegex { .. } => "INVALID_REGEX",
+            Self::PatternTooLarge { .. } => "PATTERN_TOO_LARGE",
+            Self::BudgetExceeded => "BUDGET_EXCEEDED",
+            Self::UnknownProfile => "UNKNOWN_PROFILE",
+            Self::Storage | Self::Backend(_) => "STORAGE",
+            Self::UnknownType => "UNKNOWN_TYPE",
+            Self::Io(_) => "IO",
+            Self::Parse { .. } => "PARSE",
+            Self::InvalidEntry { .. } => "INVALID_ENTRY",
+            Self::DuplicateWord { .. } => "DUPLICATE_WORD",
+            Self::RejectedWord { .. } => "REJECTED_WORD",
+            Self::ContentRejected(_) => "CONTENT_REJECTED",
+            Self::InputTooLong { .. } => "INPUT_TOO_LONG",
+        }
+    }
+
+    /// Numeric code of the error, the value of the matching `LcStatus` of the C interface
+    ///
+    /// Like [`Error::code`], numbers are stable and never reused, 1 and 2 are statuses of
+    /// the C interface which aren't errors of the censor.
+    pub fn number(&self) -> u32 {
+        match self {
+            Self::EmptyWord { .. } => 3,
+            Self::NoArgs => 4,
+            Self::InvalidRegex { .. } => 5,
+            Self::BudgetExceeded => 6,
+            Self::UnknownProfile => 8,
+            Self::Storage | Self::Backend(_) => 9,
+            Self::UnknownType => 10,
+            Self::Io(_) => 11,
+            Self::Parse { .. } => 12,
+            Self::PatternTooLarge { .. } => 13,
+            Self::InvalidEntry { .. } => 14,
+            Self::DuplicateWord { .. } => 15,
+            Self::RejectedWord { .. } => 16,
+            Self::ContentRejected(_) => 17,
+            Self::InputTooLong { .. } => 18,
+        }
+    }
+}
+
+/// Keeps the source error, the pattern is left empty as it isn't known here
+impl From<regex::Error> for Error {
+    fn from(error: regex::Error) -> Self {
+        Self::invalid_regex("", error)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Backend(Box::new(error))
+    }
+}
+
+/// Position of malformed input, see [`Error::Parse`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// Line of a text, starting at 1
+    Line(usize),
+    /// Offset in bytes of binary data
+    Byte(usize),
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Line(line) => write!(f, "line {line}"),
+            Self::Byte(offset) => write!(f, "byte {offset}"),
+        }
+    }
+}
+
+/// Error thrown to JavaScript as an `Error` with machine readable `code` and its
+/// numeric `errno`, the `input` which caused it and `detail` of the underlying error,
+/// when known
+#[cfg(feature = "wasm-min")]
+#[derive(Debug)]
+pub struct WasmError {
+    code: &'static str,
+    number: Option<u32>,
+    message: String,
+    input: Option<String>,
+    detail: Option<String>,
+}
+
+#[cfg(feature = "wasm-min")]
+impl WasmError {
+    pub(crate) fn new(code: &'static str, message: impl ToString) -> Self {
+        Self {
+            code,
+            number: None,
+            message: message.to_string(),
+            input: None,
+            detail: None,
+        }
+    }
+
+    /// Attaches input which caused the error
+    pub(crate) fn with_input(mut self, input: impl Into<String>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+}
+
+#[cfg(feature = "wasm-min")]
+impl From<Error> for WasmError {
+    fn from(error: Error) -> Self {
+        let number = Some(error.number());
+        match &error {
+            Error::InvalidRegex { pattern, source } => {
+                let detail = Some(source.to_string());
+                Self {
+                    number,
+                    detail,
+                    ..Self::new(error.code(), &error).with_input(pattern.clone())
+                }
+            }
+            Error::PatternTooLarge { pattern: input, .. }
+            | Error::InvalidEntry { entry: input, .. } => {
+                let input = input.clone();
+                Self {
+                    number,
+                    ..Self::new(error.code(), &error).with_input(input)
+                }
+            }
+            _ => Self {
+                number,
+                ..Self::new(error.code(), error)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "wasm-min")]
+impl From<WasmError> for JsValue {
+    fn from(error: WasmError) -> Self {
+        let thrown = js_sys::Error::new(&error.message);
+        let _ = js_sys::Reflect::set(&thrown, &"code".into(), &error.code.into());
+        if let Some(number) = error.number {
+            let _ = js_sys::Reflect::set(&thrown, &"errno".into(), &number.into());
+        }
+        if let Some(input) = error.input {
+            let _ = js_sys::Reflect::set(&thrown, &"input".into(), &input.into());
+        }
+        if let Some(detail) = error.detail {
+            let _ = js_sys::Reflect::set(&thrown, &"detail".into(), &detail.into());
+        }
+        thrown.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regex_error_code() {
+        let pattern = String::from("(");
+        let error = Error::invalid_regex(&pattern, regex::Regex::new(&pattern).unwrap_err());
+
+        assert_eq!(error.code(), "INVALID_REGEX");
+        assert!(error
+            .to_string()
+            .starts_with("Provided Invalid Regex `(`: "));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn regex_error_converts() {
+        let pattern = String::from("(");
+        let error = Error::from(regex::Regex::new(&pattern).unwrap_err());
+
+        assert_eq!(error.code(), "INVALID_REGEX");
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(matches!(
+            Error::from(regex::Error::CompiledTooBig(64)),
+            Error::PatternTooLarge { limit: 64, .. }
+        ));
+    }
+
+    #[test]
+    fn pattern_too_large() {
+        let source = regex::RegexBuilder::new(r"\w{100}")
+            .size_limit(16)
+            .build()
+            .unwrap_err();
+
+        let error = Error::invalid_regex(r"\w{100}", source);
+
+        assert!(matches!(error, Error::PatternTooLarge { limit: 16, .. }));
+        assert_eq!(error.code(), "PATTERN_TOO_LARGE");
+        assert_eq!(error.number(), 13);
     }
 }
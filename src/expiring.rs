@@ -0,0 +1,174 @@
+//! Words of a censor which expire after a while, see [`crate::censor::Censor::add_words_for`]
+
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::phrase::Phrase;
+use crate::{words, AddSummary, Vulgar};
+
+/// Temporary words shared by clones of a censor
+///
+/// They're matched on top of the word list without changing it, so words they shadow
+/// keep their type and weight and get them back as soon as the temporary ones expire.
+#[derive(Debug, Default)]
+pub(crate) struct ExpiringWords {
+    words: RwLock<Vec<Expiring>>,
+}
+
+/// Temporary word, `at` is `None` if the duration is too long for the clock
+#[derive(Debug, Clone)]
+struct Expiring {
+    word: String,
+    phrase: Phrase,
+    weight: Option<u32>,
+    at: Option<Instant>,
+}
+
+impl Expiring {
+    fn expired(&self, now: Instant) -> bool {
+        self.at.is_some_and(|at| at <= now)
+    }
+}
+
+impl ExpiringWords {
+    /// Adds words for the given time, the time of words which are already temporary is
+    /// replaced
+    pub(crate) fn add(
+        &self,
+        vulgars: Vec<Vulgar>,
+        duration: Duration,
+    ) -> Result<AddSummary, Error> {
+        let mut added = Vec::with_capacity(vulgars.len());
+        for (index, vulgar) in vulgars.into_iter().enumerate() {
+            let phrase = Phrase::word(&vulgar.word, vulgar.word_type)
+                .map_err(|_| Error::EmptyWord { index })?;
+            added.push((vulgar, phrase));
+        }
+
+        let now = Instant::now();
+        let at = now.checked_add(duration);
+        let mut summary = AddSummary::default();
+        let mut words = self.words.write().unwrap_or_else(|e| e.into_inner());
+        words.retain(|expiring| !expiring.expired(now));
+        for (vulgar, phrase) in added {
+            match words
+                .iter()
+                .position(|other| other.phrase.same_terms(&phrase))
+            {
+                Some(existing) => {
+                    words.remove(existing);
+                    summary.replaced += 1;
+                }
+                None => summary.added += 1,
+            }
+            words.push(Expiring {
+                word: vulgar.word,
+                phrase,
+                weight: vulgar.weight,
+                at,
+            });
+        }
+        Ok(summary)
+    }
+
+    /// Phrases of words which haven't expired yet, expired ones are dropped
+    pub(crate) fn active(&self) -> Vec<Phrase> {
+        let words = self.words.read().unwrap_or_else(|e| e.into_inner());
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        if !words.iter().any(|expiring| expiring.expired(now)) {
+            return words
+                .iter()
+                .map(|expiring| expiring.phrase.clone())
+                .collect();
+        }
+        drop(words);
+
+        let mut words = self.words.write().unwrap_or_else(|e| e.into_inner());
+        words.retain(|expiring| !expiring.expired(now));
+        words
+            .iter()
+            .map(|expiring| expiring.phrase.clone())
+            .collect()
+    }
+
+    /// Weight of a temporary word which hasn't expired yet, 1 if it has none
+    pub(crate) fn weight(&self, word: &str) -> Option<u32> {
+        let list = self.words.read().unwrap_or_else(|e| e.into_inner());
+        if list.is_empty() {
+            return None;
+        }
+        let (key, now) = (words::weight_key(word), Instant::now());
+        list.iter()
+            .find(|expiring| !expiring.expired(now) && words::weight_key(&expiring.word) == key)
+            .map(|expiring| expiring.weight.unwrap_or(1))
+    }
+
+    /// Words which haven't expired yet with the time they expire at, the earliest first
+    pub(crate) fn pending(&self) -> Vec<(String, Instant)> {
+        let now = Instant::now();
+        let mut pending: Vec<_> = self
+            .words
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|expiring| !expiring.expired(now))
+            .filter_map(|expiring| Some((expiring.word.clone(), expiring.at?)))
+            .collect();
+        pending.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustrict::Type;
+
+    use crate::censor::Censor;
+    use crate::Vulgar;
+
+    use super::*;
+
+    #[test]
+    fn expired_words_restore_weights() {
+        let vulgar = |word: &str, weight| Vulgar {
+            word: String::from(word),
+            word_type: Type::INAPPROPRIATE,
+            weight,
+        };
+        let censor = Censor::new()
+            .with_words(vec![vulgar("lingering", Some(2))])
+            .unwrap();
+        let clone = censor.clone();
+
+        let summary = censor
+            .add_words_for(
+                vec![vulgar("embargoed", None), vulgar("lingering", Some(7))],
+                Duration::from_secs(3600),
+            )
+            .unwrap();
+        assert_eq!((summary.added, summary.replaced), (2, 0));
+        let censored = clone
+            .censor(String::from("embargoed lingering"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "e******** l********");
+        assert_eq!(censored.score(), 8);
+        assert_eq!(clone.expiring_words().len(), 2);
+
+        censor
+            .add_words_for(vec![vulgar("lingering", Some(7))], Duration::ZERO)
+            .unwrap();
+        let censored = censor
+            .censor(String::from("embargoed lingering"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.score(), 3);
+        assert_eq!(censor.expiring_words()[0].0, "embargoed");
+        assert!(matches!(
+            censor.add_words_for(vec![vulgar(" ", None)], Duration::ZERO),
+            Err(Error::EmptyWord { index: 0 })
+        ));
+    }
+}
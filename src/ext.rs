@@ -0,0 +1,79 @@
+//! Censoring methods on strings, like `CensorStr` of rustrict
+
+use rustrict::Type;
+
+use crate::censor::{Censor, CensorTypes, Censored};
+use crate::error::Error;
+
+/// Rules masking personal information
+const PII: [CensorTypes; 3] = [CensorTypes::Link, CensorTypes::IP, CensorTypes::Email];
+
+/// Censors strings in place of calling [`Censor::censor`]
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::Censor;
+/// use little_censor::LittleCensor;
+///
+/// let censor = Censor::new().with_replacement('#');
+///
+/// assert_eq!("fuck".censor_with(&censor).unwrap().censored(), "f###");
+/// assert_eq!(
+///     "fuck me@example.net".scrub_pii().unwrap(),
+///     "fuck **************"
+/// );
+/// ```
+pub trait LittleCensor {
+    /// Censors the text with the censor, without additional types of censoring
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    fn censor_with(&self, censor: &Censor) -> Result<Censored, Error>;
+
+    /// Masks links, IP and email addresses, vulgar words are kept
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    fn scrub_pii(&self) -> Result<String, Error>;
+}
+
+impl LittleCensor for str {
+    fn censor_with(&self, censor: &Censor) -> Result<Censored, Error> {
+        censor.censor(self.to_owned(), Box::new([]), None)
+    }
+
+    fn scrub_pii(&self) -> Result<String, Error> {
+        let censor = Censor::new().with_threshold(Type::NONE);
+        let censored = censor.censor(self.to_owned(), Box::new(PII), None)?;
+        Ok(censored.censored)
+    }
+}
+
+impl LittleCensor for String {
+    fn censor_with(&self, censor: &Censor) -> Result<Censored, Error> {
+        self.as_str().censor_with(censor)
+    }
+
+    fn scrub_pii(&self) -> Result<String, Error> {
+        self.as_str().scrub_pii()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrubs_only_pii() {
+        let text = String::from("fuck, see https://example.net from 10.0.0.1");
+
+        assert_eq!(
+            text.scrub_pii().unwrap(),
+            "fuck, see ******************* from ********"
+        );
+        assert!(!text.censor_with(&Censor::new()).unwrap().valid);
+    }
+}
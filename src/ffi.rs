@@ -0,0 +1,259 @@
+//! C interface for embedding the censor in other languages
+//!
+//! The header is checked in as `include/little_censor.h` and regenerated with
+//! `scripts/generate-header.sh`, builds with the `ffi` feature generate it into `OUT_DIR`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use crate::censor::{censor, CensorTypes};
+use crate::error::Error;
+use crate::{add_words, Type, Vulgar};
+
+/// Masks links
+pub const LC_LINK: u32 = 1;
+/// Masks IP addresses
+pub const LC_IP: u32 = 1 << 1;
+/// Masks email addresses
+pub const LC_EMAIL: u32 = 1 << 2;
+/// Masks matches of the custom pattern
+pub const LC_CUSTOM: u32 = 1 << 3;
+
+/// Outcome of a call
+///
+/// Values of errors are the numbers of `Error::number`, they never change.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LcStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    EmptyWord = 3,
+    NoArgs = 4,
+    InvalidRegex = 5,
+    BudgetExceeded = 6,
+    UnknownProfile = 8,
+    Storage = 9,
+    UnknownType = 10,
+    Io = 11,
+    Parse = 12,
+    PatternTooLarge = 13,
+    InvalidEntry = 14,
+    DuplicateWord = 15,
+    RejectedWord = 16,
+    ContentRejected = 17,
+    InputTooLong = 18,
+}
+
+impl From<Error> for LcStatus {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::EmptyWord { .. } => Self::EmptyWord,
+            Error::NoArgs => Self::NoArgs,
+            Error::InvalidRegex { .. } => Self::InvalidRegex,
+            Error::BudgetExceeded => Self::BudgetExceeded,
+            Error::UnknownProfile => Self::UnknownProfile,
+            Error::Storage | Error::Backend(_) => Self::Storage,
+            Error::UnknownType => Self::UnknownType,
+            Error::Io(_) => Self::Io,
+            Error::Parse { .. } => Self::Parse,
+            Error::PatternTooLarge { .. } => Self::PatternTooLarge,
+            Error::InvalidEntry { .. } => Self::InvalidEntry,
+            Error::DuplicateWord { .. } => Self::DuplicateWord,
+            Error::RejectedWord { .. } => Self::RejectedWord,
+            Error::ContentRejected(_) => Self::ContentRejected,
+            Error::InputTooLong { .. } => Self::InputTooLong,
+        }
+    }
+}
+
+/// Result of `lc_censor`, has to be released with `lc_free_result`
+#[repr(C)]
+#[derive(Debug)]
+pub struct LcResult {
+    pub status: LcStatus,
+    /// Censored sentence, null unless the status is `Ok`
+    pub censored: *mut c_char,
+    /// Whether nothing had to be censored
+    pub valid: bool,
+    /// Bits of the type of vulgar words found
+    pub typ: u32,
+}
+
+impl LcResult {
+    fn failed(status: LcStatus) -> *mut LcResult {
+        Box::into_raw(Box::new(LcResult {
+            status,
+            censored: ptr::null_mut(),
+            valid: false,
+            typ: 0,
+        }))
+    }
+}
+
+/// Borrows C string, `Ok(None)` for null
+unsafe fn read_str<'a>(text: *const c_char) -> Result<Option<&'a str>, LcStatus> {
+    if text.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map(Some)
+        .map_err(|_| LcStatus::InvalidUtf8)
+}
+
+/// Censors `sentence` with additional types given as `LC_*` bits
+///
+/// `arg` is the pattern for `LC_CUSTOM` and may be null otherwise.
+///
+/// # Safety
+///
+/// `sentence` and `arg` have to be null or valid NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn lc_censor(
+    sentence: *const c_char,
+    types: u32,
+    arg: *const c_char,
+) -> *mut LcResult {
+    let (sentence, arg) = match (read_str(sentence), read_str(arg)) {
+        (Ok(Some(sentence)), Ok(arg)) => (sentence, arg),
+        (Ok(None), _) => return LcResult::failed(LcStatus::NullPointer),
+        (Err(status), _) | (_, Err(status)) => return LcResult::failed(status),
+    };
+    let types: Box<[CensorTypes]> = [
+        (LC_LINK, CensorTypes::Link),
+        (LC_IP, CensorTypes::IP),
+        (LC_EMAIL, CensorTypes::Email),
+        (LC_CUSTOM, CensorTypes::Custom),
+    ]
+    .into_iter()
+    .filter(|(bit, _)| types & bit != 0)
+    .map(|(_, typ)| typ)
+    .collect();
+
+    let censored = match censor(sentence.to_owned(), types, arg.map(str::to_owned)) {
+        Ok(censored) => censored,
+        Err(error) => return LcResult::failed(error.into()),
+    };
+    // Sentence came from a C string, so it has no interior NUL
+    let text = CString::new(censored.censored).unwrap_or_default();
+    Box::into_raw(Box::new(LcResult {
+        status: LcStatus::Ok,
+        censored: text.into_raw(),
+        valid: censored.valid,
+        typ: censored.typ.bits(),
+    }))
+}
+
+/// Releases result of `lc_censor`
+///
+/// # Safety
+///
+/// `result` has to be null or returned by `lc_censor` and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn lc_free_result(result: *mut LcResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = Box::from_raw(result);
+    if !result.censored.is_null() {
+        drop(CString::from_raw(result.censored));
+    }
+}
+
+/// Adds `len` words of the same type, `typ` of 0 means inappropriate words
+///
+/// # Safety
+///
+/// `words` has to point to `len` valid NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn lc_add_words(
+    words: *const *const c_char,
+    len: usize,
+    typ: u32,
+) -> LcStatus {
+    if words.is_null() && len > 0 {
+        return LcStatus::NullPointer;
+    }
+    let word_type = match typ {
+        0 => Type::default(),
+        bits => Type::from_bits_truncate(bits),
+    };
+
+    let mut vulgars = Vec::with_capacity(len);
+    for i in 0..len {
+        match read_str(*words.add(i)) {
+            Ok(Some(word)) => vulgars.push(Vulgar {
+                word: word.to_owned(),
+                word_type,
+                weight: None,
+            }),
+            Ok(None) => return LcStatus::NullPointer,
+            Err(status) => return status,
+        }
+    }
+    match add_words(vulgars) {
+        Ok(_) => LcStatus::Ok,
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Position;
+
+    #[test]
+    fn censor_through_ffi() {
+        let sentence = CString::new("fuck https://example.net").unwrap();
+
+        unsafe {
+            let result = lc_censor(sentence.as_ptr(), LC_LINK, ptr::null());
+            assert_eq!((*result).status, LcStatus::Ok);
+            assert_eq!(
+                CStr::from_ptr((*result).censored).to_str().unwrap(),
+                "f*** *******************"
+            );
+            assert!(!(*result).valid);
+            lc_free_result(result);
+        }
+    }
+
+    #[test]
+    fn ffi_errors() {
+        unsafe {
+            let result = lc_censor(ptr::null(), 0, ptr::null());
+            assert_eq!((*result).status, LcStatus::NullPointer);
+            lc_free_result(result);
+
+            let empty = CString::new("").unwrap();
+            let words = [empty.as_ptr()];
+            assert_eq!(lc_add_words(words.as_ptr(), 1, 0), LcStatus::EmptyWord);
+        }
+    }
+
+    #[test]
+    fn status_matches_error_number() {
+        for error in [
+            Error::EmptyWord { index: 0 },
+            Error::NoArgs,
+            Error::BudgetExceeded,
+            Error::Storage,
+            Error::Parse {
+                at: Position::Line(1),
+            },
+        ] {
+            let number = error.number();
+            assert_eq!(LcStatus::from(error) as u32, number);
+        }
+    }
+
+    #[test]
+    fn checked_in_header_is_current() {
+        assert!(
+            include_str!(concat!(env!("OUT_DIR"), "/little_censor.h"))
+                == include_str!("../include/little_censor.h"),
+            "include/little_censor.h is outdated, run scripts/generate-header.sh"
+        );
+    }
+}
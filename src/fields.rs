@@ -0,0 +1,70 @@
+//! Censoring every text field of a struct at once
+//!
+//! With the `derive` feature [`CensorFields`] can be derived with `#[derive(Censor)]`,
+//! which censors every `String`, `Option<String>` and `Vec<String>` field. Fields are
+//! skipped with `#[censor(skip)]` and censored with additional types of censoring
+//! with `#[censor(types(Link, Email))]`.
+
+use crate::censor::{Censor, CensorTypes};
+use crate::error::Error;
+
+/// Struct whose text fields can be censored in place
+pub trait CensorFields {
+    /// Censors every text field, returns whether anything was censored
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    fn censor_fields(&mut self, censor: &Censor) -> Result<bool, Error>;
+}
+
+/// Field censored by [`CensorFields`]
+pub trait CensorField {
+    /// Censors the field in place, returns whether anything was censored
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    fn censor_field(&mut self, censor: &Censor, types: &[CensorTypes]) -> Result<bool, Error>;
+}
+
+impl CensorField for String {
+    fn censor_field(&mut self, censor: &Censor, types: &[CensorTypes]) -> Result<bool, Error> {
+        let censored = censor.censor(std::mem::take(self), types.into(), None)?;
+        let changed = censored.changed();
+        *self = censored.censored;
+        Ok(changed)
+    }
+}
+
+impl<T: CensorField> CensorField for Option<T> {
+    fn censor_field(&mut self, censor: &Censor, types: &[CensorTypes]) -> Result<bool, Error> {
+        match self {
+            Some(field) => field.censor_field(censor, types),
+            None => Ok(false),
+        }
+    }
+}
+
+impl<T: CensorField> CensorField for Vec<T> {
+    fn censor_field(&mut self, censor: &Censor, types: &[CensorTypes]) -> Result<bool, Error> {
+        let mut changed = false;
+        for field in self {
+            changed |= field.censor_field(censor, types)?;
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn censor_nested_fields() {
+        let mut fields = vec![Some(String::from("fuck")), None];
+
+        assert!(fields.censor_field(&Censor::new(), &[]).unwrap());
+        assert_eq!(fields, vec![Some(String::from("f***")), None]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Rate limiting of messages per user
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Flags users sending more than `limit` messages within `period`
+///
+/// Attach it to a censor with [`crate::censor::Censor::with_flood_guard`], then messages
+/// censored with [`crate::censor::Censor::censor_user`] during a burst are typed as spam.
+///
+/// Requires a clock, so it can't be used on `wasm32-unknown-unknown`. Recent messages
+/// are kept when the guard is saved with serde, see [`crate::state::save_state`].
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use little_censor::flood::FloodGuard;
+///
+/// let guard = FloodGuard::new(2, Duration::from_secs(10));
+///
+/// assert!(!guard.hit("user"));
+/// assert!(!guard.hit("user"));
+/// assert!(guard.hit("user"));
+/// assert!(!guard.hit("other"));
+/// ```
+#[derive(Debug)]
+pub struct FloodGuard {
+    limit: usize,
+    period: Duration,
+    users: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl FloodGuard {
+    /// Allows at most `limit` messages of every user within `period`
+    pub fn new(limit: usize, period: Duration) -> Self {
+        Self {
+            limit,
+            period,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records message of the user sent now, returns whether it is a part of a flood
+    pub fn hit(&self, user: &str) -> bool {
+        self.hit_at(user, Instant::now())
+    }
+
+    /// Records message of the user sent at given time, returns whether it is a part of a flood
+    pub fn hit_at(&self, user: &str, at: Instant) -> bool {
+        let mut users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        let times = users.entry(user.to_owned()).or_default();
+
+        while matches!(times.front(), Some(&first) if at.saturating_duration_since(first) >= self.period)
+        {
+            times.pop_front();
+        }
+        times.push_back(at);
+
+        times.len() > self.limit
+    }
+
+    /// Forgets messages of the user
+    pub fn forget(&self, user: &str) {
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(user);
+    }
+
+    /// Forgets users who didn't send anything within the period
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, times| {
+                matches!(times.back(), Some(&last) if now.saturating_duration_since(last) < self.period)
+            });
+    }
+}
+
+/// Serialized form of the guard, with wall clock times of messages
+#[derive(Serialize, Deserialize)]
+struct FloodState {
+    limit: usize,
+    period: Duration,
+    users: HashMap<String, Vec<SystemTime>>,
+}
+
+impl Serialize for FloodGuard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        FloodState {
+            limit: self.limit,
+            period: self.period,
+            users: users
+                .iter()
+                .map(|(user, times)| {
+                    let times = times
+                        .iter()
+                        .map(|&time| {
+                            wall.checked_sub(now.saturating_duration_since(time))
+                                .unwrap_or(SystemTime::UNIX_EPOCH)
+                        })
+                        .collect();
+                    (user.clone(), times)
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FloodGuard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = FloodState::deserialize(deserializer)?;
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let users = state
+            .users
+            .into_iter()
+            .map(|(user, times)| {
+                let times = times
+                    .into_iter()
+                    .filter_map(|time| {
+                        now.checked_sub(wall.duration_since(time).unwrap_or_default())
+                    })
+                    .collect();
+                (user, times)
+            })
+            .collect();
+
+        Ok(Self {
+            limit: state.limit,
+            period: state.period,
+            users: Mutex::new(users),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_ends_after_period() {
+        let guard = FloodGuard::new(1, Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert!(!guard.hit_at("user", start));
+        assert!(guard.hit_at("user", start + Duration::from_secs(1)));
+        assert!(!guard.hit_at("user", start + Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn restore_state() {
+        let guard = FloodGuard::new(1, Duration::from_secs(60));
+        guard.hit("user");
+
+        let json = serde_json::to_string(&guard).unwrap();
+        let restored: FloodGuard = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.limit, 1);
+        assert!(restored.hit("user"));
+    }
+
+    #[test]
+    fn forget_user() {
+        let guard = FloodGuard::new(0, Duration::from_secs(5));
+
+        assert!(guard.hit("user"));
+        guard.forget("user");
+        guard.prune();
+        assert!(guard.users.lock().unwrap().is_empty());
+    }
+}
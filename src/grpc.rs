@@ -0,0 +1,319 @@
+//! gRPC moderation service described in `proto/little_censor.proto`
+//!
+//! The `little-censor-grpc` binary serves [`ModerationService`], it can be added to
+//! another tonic server with [`server`] as well.
+
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::censor::{Censor, CensorTypes, Censored};
+use crate::error::Error;
+use crate::{type_name, Type, Vulgar};
+
+use proto::moderation_server::{Moderation, ModerationServer};
+use proto::{
+    AddWordsRequest, AddWordsResponse, AnalyzeResponse, CensorRequest, CensorResponse, Span,
+};
+
+/// Messages and service traits generated from `proto/little_censor.proto`
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("little_censor");
+}
+
+impl From<Error> for Status {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::BudgetExceeded => Status::resource_exhausted(error.to_string()),
+            Error::InputTooLong { .. } => Status::out_of_range(error.to_string()),
+            Error::Storage | Error::Backend(_) | Error::Io(_) => {
+                Status::internal(error.to_string())
+            }
+            _ => Status::invalid_argument(error.to_string()),
+        }
+    }
+}
+
+/// Moderation service censoring with a shared censor, the global word list by default
+///
+/// Words added with the `AddWords` call go into a new word list which is swapped in as a
+/// whole, calls in flight keep the previous one. The call is authenticated with an
+/// `authorization: Bearer <token>` header and rejected without a configured token.
+#[derive(Debug, Default, Clone)]
+pub struct ModerationService {
+    /// Censor calls are made with, replaced when words are added
+    censor: Arc<RwLock<Arc<Censor>>>,
+    /// Censor without added words, the word list is built on top of it
+    base: Censor,
+    /// Words added with `AddWords` so far
+    added: Arc<Mutex<Vec<Vulgar>>>,
+    token: Option<Arc<str>>,
+}
+
+impl ModerationService {
+    /// Creates service censoring with the global word list, `AddWords` is disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Censors with the given censor, words added later replace its own word list
+    pub fn with_censor(mut self, censor: Censor) -> Self {
+        self.censor = Arc::new(RwLock::new(Arc::new(censor.clone())));
+        self.base = censor;
+        self
+    }
+
+    /// Allows `AddWords` calls authenticated with the token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(Arc::from(token.into()));
+        self
+    }
+
+    fn current(&self) -> Arc<Censor> {
+        Arc::clone(&self.censor.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Rejects requests without the configured bearer token, all of them when there's none
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or_else(|| Status::permission_denied("AddWords is disabled without a token"))?;
+        let given = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match given {
+            Some(given) if same(given.as_bytes(), token.as_bytes()) => Ok(()),
+            _ => Err(Status::unauthenticated("Invalid token")),
+        }
+    }
+
+    /// Builds a word list with every added word and swaps it in
+    fn add(&self, vulgars: Vec<Vulgar>) -> Result<(), Error> {
+        let mut added = self.added.lock().unwrap_or_else(|e| e.into_inner());
+        let mut words = added.clone();
+        words.extend(vulgars);
+        let censor = self.base.clone().with_words(words.clone())?;
+        *self.censor.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(censor);
+        *added = words;
+        Ok(())
+    }
+}
+
+/// Compares in time independent of where the tokens differ
+fn same(given: &[u8], token: &[u8]) -> bool {
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Service ready to be added to a tonic server, `AddWords` is disabled
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+/// tonic::transport::Server::builder()
+///     .add_service(little_censor::grpc::server())
+///     .serve("127.0.0.1:50051".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn server() -> ModerationServer<ModerationService> {
+    ModerationServer::new(ModerationService::new())
+}
+
+/// Parses names of additional types of censoring, e.g. `"link"`
+fn rules(names: &[String]) -> Result<Box<[CensorTypes]>, Status> {
+    names
+        .iter()
+        .map(|name| match name.as_str() {
+            "link" => Ok(CensorTypes::Link),
+            "ip" => Ok(CensorTypes::IP),
+            "email" => Ok(CensorTypes::Email),
+            "custom" => Ok(CensorTypes::Custom),
+            _ => Err(Status::invalid_argument(format!(
+                "Censor type {name} doesn't exist"
+            ))),
+        })
+        .collect()
+}
+
+fn censor(censor: &Censor, request: CensorRequest) -> Result<Censored, Status> {
+    let types = rules(&request.rules)?;
+    Ok(censor.censor(request.text, types, request.arg)?)
+}
+
+fn spans(censored: &Censored) -> Vec<Span> {
+    censored
+        .spans
+        .iter()
+        .map(|span| Span {
+            start: span.start as u32,
+            end: span.end as u32,
+            kind: span.kind.name().to_owned(),
+        })
+        .collect()
+}
+
+fn verdict(censored: &Censored) -> String {
+    format!("{:?}", censored.verdict).to_lowercase()
+}
+
+impl From<Censored> for CensorResponse {
+    fn from(censored: Censored) -> Self {
+        Self {
+            spans: spans(&censored),
+            verdict: verdict(&censored),
+            r#type: type_name::format(censored.typ),
+            valid: censored.valid,
+            truncated: censored.truncated,
+            pii_redacted: censored.pii_redacted(),
+            profanity_censored: censored.profanity_censored(),
+            censored: censored.censored,
+        }
+    }
+}
+
+type CensorStream = Pin<Box<dyn Stream<Item = Result<CensorResponse, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Moderation for ModerationService {
+    async fn censor(
+        &self,
+        request: Request<CensorRequest>,
+    ) -> Result<Response<CensorResponse>, Status> {
+        Ok(Response::new(
+            censor(&self.current(), request.into_inner())?.into(),
+        ))
+    }
+
+    async fn analyze(
+        &self,
+        request: Request<CensorRequest>,
+    ) -> Result<Response<AnalyzeResponse>, Status> {
+        let censored = censor(&self.current(), request.into_inner())?;
+        Ok(Response::new(AnalyzeResponse {
+            valid: censored.valid,
+            r#type: type_name::format(censored.typ),
+            pii_redacted: censored.pii_redacted(),
+            profanity_censored: censored.profanity_censored(),
+            spans: spans(&censored),
+            verdict: verdict(&censored),
+        }))
+    }
+
+    async fn add_words(
+        &self,
+        request: Request<AddWordsRequest>,
+    ) -> Result<Response<AddWordsResponse>, Status> {
+        self.authorize(&request)?;
+        let words = request.into_inner().words;
+        let mut vulgars = Vec::with_capacity(words.len());
+        for word in words {
+            let word_type = match word.r#type.as_str() {
+                "" => Type::default(),
+                name => type_name::parse(name)?,
+            };
+            vulgars.push(Vulgar::new(word.word, Some(word_type)));
+        }
+        let added = vulgars.len() as u32;
+        self.add(vulgars)?;
+        Ok(Response::new(AddWordsResponse { added }))
+    }
+
+    type CensorStreamStream = CensorStream;
+
+    async fn censor_stream(
+        &self,
+        request: Request<Streaming<CensorRequest>>,
+    ) -> Result<Response<Self::CensorStreamStream>, Status> {
+        let service = self.clone();
+        let responses = request
+            .into_inner()
+            .map(move |request| Ok(censor(&service.current(), request?)?.into()));
+        Ok(Response::new(Box::pin(responses)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn censor_and_analyze() {
+        let request = CensorRequest {
+            text: String::from("fuck me@example.net"),
+            rules: vec![String::from("email")],
+            arg: None,
+        };
+
+        let censored = ModerationService::new()
+            .censor(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        let analyzed = ModerationService::new()
+            .analyze(Request::new(request))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(censored.censored, "f*** **************");
+        assert_eq!(censored.spans, analyzed.spans);
+        assert!(!analyzed.valid);
+    }
+
+    #[tokio::test]
+    async fn unknown_rule() {
+        let request = CensorRequest {
+            text: String::from("text"),
+            rules: vec![String::from("phone")],
+            arg: None,
+        };
+
+        let status = ModerationService::new()
+            .censor(Request::new(request))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn added_words_require_token() {
+        let service = ModerationService::new().with_token("secret");
+        let words = || AddWordsRequest {
+            words: vec![proto::Word {
+                word: String::from("grpcword"),
+                r#type: String::new(),
+            }],
+        };
+
+        let status = service.add_words(Request::new(words())).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(words());
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer secret".parse().unwrap());
+        let added = service.add_words(request).await.unwrap().into_inner();
+        assert_eq!(added.added, 1);
+        let request = CensorRequest {
+            text: String::from("a grpcword"),
+            rules: Vec::new(),
+            arg: None,
+        };
+        let censored = service.censor(Request::new(request)).await.unwrap();
+        assert_eq!(censored.into_inner().censored, "a g*******");
+    }
+}
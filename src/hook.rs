@@ -0,0 +1,92 @@
+//! Notifications about severe messages
+
+use std::fmt;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+use rustrict::Type;
+
+use crate::report::ModerationReport;
+
+/// Receiver of reports about messages above the threshold of
+/// [`crate::censor::Censor::with_hook`]
+///
+/// Called synchronously while censoring, so slow work should be moved elsewhere,
+/// e.g. by sending the report through a channel.
+pub trait Hook: Send + Sync {
+    fn notify(&self, report: &ModerationReport);
+}
+
+impl<F> Hook for F
+where
+    F: Fn(&ModerationReport) + Send + Sync,
+{
+    fn notify(&self, report: &ModerationReport) {
+        self(report)
+    }
+}
+
+impl Hook for Mutex<Sender<ModerationReport>> {
+    fn notify(&self, report: &ModerationReport) {
+        let sender = self.lock().unwrap_or_else(|e| e.into_inner());
+        if sender.send(report.clone()).is_err() {
+            log::warn!("Receiver of moderation reports was dropped");
+        }
+    }
+}
+
+/// Hook together with the threshold of messages it's notified about
+pub(crate) struct Alert {
+    pub(crate) threshold: Type,
+    pub(crate) hook: Box<dyn Hook>,
+}
+
+impl fmt::Debug for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Alert")
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+
+    use super::*;
+    use crate::censor::Censor;
+
+    #[test]
+    fn closure_hook() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let censor = Censor::new().with_hook(Type::SEVERE, move |_: &ModerationReport| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        censor
+            .censor(String::from("oh shit"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        censor
+            .censor(String::from("fuck"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn channel_hook() {
+        let (sender, receiver) = mpsc::channel();
+        let censor = Censor::new().with_hook(Type::SEVERE, Mutex::new(sender));
+
+        censor
+            .censor_user("1234", String::from("fuck"), Box::new([]), None)
+            .unwrap();
+
+        let report = receiver.try_recv().unwrap();
+        assert_eq!(report.user_id.as_deref(), Some("1234"));
+        assert_eq!(report.original, "fuck");
+    }
+}
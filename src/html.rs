@@ -0,0 +1,341 @@
+//! Censoring HTML fragments, e.g. user generated rich content
+//!
+//! Text nodes and selected attributes are censored, everything else is written exactly
+//! as it was: tags, comments, contents of `<script>` and `<style>` and character
+//! references like `&amp;`. Masks which aren't valid in HTML text, like `<`, are written
+//! as character references.
+
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::json::JsonOptions;
+
+/// Elements whose contents aren't text
+const RAW_TEXT: [&str; 2] = ["script", "style"];
+
+/// Which parts of an HTML fragment are censored and how
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::html::{censor_html, HtmlPolicy};
+///
+/// let mut comment = String::from(r#"<p title="fuck">Fuck &amp; <b>fuck</b></p>"#);
+///
+/// censor_html(&mut comment, &HtmlPolicy::new()).unwrap();
+///
+/// assert_eq!(comment, r#"<p title="f***">F*** &amp; <b>f***</b></p>"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HtmlPolicy {
+    options: JsonOptions,
+    attributes: Vec<String>,
+}
+
+impl Default for HtmlPolicy {
+    fn default() -> Self {
+        Self {
+            options: JsonOptions::new(),
+            attributes: vec![String::from("alt"), String::from("title")],
+        }
+    }
+}
+
+impl HtmlPolicy {
+    /// Censors text nodes and `alt` and `title` attributes with a default censor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Censors text with the censor and rules of the options, key patterns aren't used
+    pub fn with_options(mut self, options: JsonOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Censors values of these attributes instead of `alt` and `title`, names are case
+    /// insensitive
+    pub fn with_attributes<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.attributes = names
+            .into_iter()
+            .map(|name| name.as_ref().to_ascii_lowercase())
+            .collect();
+        self
+    }
+}
+
+/// Censors the fragment in place, returns number of modified text nodes and attributes
+///
+/// # Errors
+///
+/// Returns the same errors as [`crate::censor::Censor::censor`].
+pub fn censor_html(text: &mut String, policy: &HtmlPolicy) -> Result<usize, Error> {
+    let mut censored = String::with_capacity(text.len());
+    let mut written = 0;
+    let mut modified = 0;
+
+    for range in pieces(text, &policy.attributes) {
+        if let Some(replacement) = censor_piece(&text[range.clone()], &policy.options)? {
+            censored.push_str(&text[written..range.start]);
+            censored.push_str(&replacement);
+            written = range.end;
+            modified += 1;
+        }
+    }
+    censored.push_str(&text[written..]);
+
+    *text = censored;
+    Ok(modified)
+}
+
+/// Ranges of text nodes and selected attribute values, in order
+fn pieces(html: &str, attributes: &[String]) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    let mut position = 0;
+
+    while position < html.len() {
+        let start = html[position..]
+            .find('<')
+            .map_or(html.len(), |index| position + index);
+        if start > position {
+            pieces.push(position..start);
+        }
+        if start == html.len() {
+            break;
+        }
+
+        let rest = &html[start..];
+        let after = rest[1..].chars().next();
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            position = comment
+                .find("-->")
+                .map_or(html.len(), |index| start + 4 + index + 3);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") || rest.starts_with("</") {
+            position = rest.find('>').map_or(html.len(), |index| start + index + 1);
+        } else if after.is_some_and(|c| c.is_ascii_alphabetic()) {
+            let (name, end) = tag(html, start, attributes, &mut pieces);
+            position = end;
+            if RAW_TEXT.contains(&name.as_str()) {
+                let lowercase = html[end..].to_ascii_lowercase();
+                position = lowercase
+                    .find(&format!("</{name}"))
+                    .map_or(html.len(), |index| end + index);
+            }
+        } else {
+            // Bracket which doesn't open a tag is text
+            let end = html[start + 1..]
+                .find('<')
+                .map_or(html.len(), |index| start + 1 + index);
+            pieces.push(start..end);
+            position = end;
+        }
+    }
+
+    // Adjacent text, e.g. split at a lone bracket, is censored at once
+    pieces.dedup_by(|next, previous| {
+        let adjacent = previous.end == next.start;
+        if adjacent {
+            previous.end = next.end;
+        }
+        adjacent
+    });
+    pieces
+}
+
+/// Reads the start tag at `start`, adds ranges of selected attribute values
+///
+/// Returns lowercase name of the element and position after the tag.
+fn tag(
+    html: &str,
+    start: usize,
+    attributes: &[String],
+    pieces: &mut Vec<Range<usize>>,
+) -> (String, usize) {
+    let bytes = html.as_bytes();
+    let ends_name = |c: u8| c.is_ascii_whitespace() || c == b'>' || c == b'/' || c == b'=';
+    let word_end = |from: usize| {
+        (from..html.len())
+            .find(|&index| ends_name(bytes[index]))
+            .unwrap_or(html.len())
+    };
+
+    let mut position = word_end(start + 1);
+    let name = html[start + 1..position].to_ascii_lowercase();
+    loop {
+        while position < html.len()
+            && (bytes[position].is_ascii_whitespace() || bytes[position] == b'/')
+        {
+            position += 1;
+        }
+        if position >= html.len() {
+            return (name, html.len());
+        }
+        if bytes[position] == b'>' {
+            return (name, position + 1);
+        }
+
+        let name_end = word_end(position + 1);
+        let attribute = html[position..name_end].to_ascii_lowercase();
+        position = name_end;
+        while position < html.len() && bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        if bytes.get(position) != Some(&b'=') {
+            continue;
+        }
+        position += 1;
+        while position < html.len() && bytes[position].is_ascii_whitespace() {
+            position += 1;
+        }
+
+        let value = match bytes.get(position) {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let end = html[position + 1..]
+                    .find(quote as char)
+                    .map_or(html.len(), |index| position + 1 + index);
+                let value = position + 1..end;
+                position = (end + 1).min(html.len());
+                value
+            }
+            _ => {
+                let end = (position..html.len())
+                    .find(|&index| bytes[index].is_ascii_whitespace() || bytes[index] == b'>')
+                    .unwrap_or(html.len());
+                let value = position..end;
+                position = end;
+                value
+            }
+        };
+        if attributes.contains(&attribute) {
+            pieces.push(value);
+        }
+    }
+}
+
+/// Part of a text node or attribute value
+enum Part<'a> {
+    Text(&'a str),
+    /// Character reference like `&amp;`, never censored
+    Reference(&'a str),
+}
+
+/// Splits text into text and character references
+fn parts(text: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        let length = rest[start + 1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .filter(|&length| length > 0 && rest[start + 1 + length..].starts_with(';'));
+        match length {
+            Some(length) => {
+                if start > 0 {
+                    parts.push(Part::Text(&rest[..start]));
+                }
+                parts.push(Part::Reference(&rest[start..start + length + 2]));
+                rest = &rest[start + length + 2..];
+            }
+            None => {
+                parts.push(Part::Text(&rest[..=start]));
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    parts
+}
+
+/// Censored text node or attribute value, `None` if nothing changed
+fn censor_piece(text: &str, options: &JsonOptions) -> Result<Option<String>, Error> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let parts = parts(text);
+    // References are read as a space, so they still separate words
+    let visible: String = parts
+        .iter()
+        .map(|part| match part {
+            Part::Text(text) => *text,
+            Part::Reference(_) => " ",
+        })
+        .collect();
+    let result = options.censor(visible.clone())?;
+    if result.valid {
+        return Ok(None);
+    }
+
+    // Censoring keeps the number of characters, so they're put back piece by piece
+    let mut censored = result.censored.chars();
+    let same_length = result.censored.chars().count() == visible.chars().count();
+    let mut output = String::with_capacity(text.len());
+    for part in parts {
+        match part {
+            Part::Reference(reference) => {
+                if same_length {
+                    censored.next();
+                }
+                output.push_str(reference);
+            }
+            Part::Text(text) if same_length => {
+                for (original, c) in text.chars().zip(censored.by_ref()) {
+                    push_escaped(&mut output, original, c);
+                }
+            }
+            Part::Text(text) => {
+                let result = options.censor(text.to_owned())?;
+                let mut original = text.chars();
+                for c in result.censored.chars() {
+                    push_escaped(&mut output, original.next().unwrap_or(c), c);
+                }
+            }
+        }
+    }
+    Ok(Some(output))
+}
+
+/// Writes a censored character, masks which would be read as markup become references
+fn push_escaped(output: &mut String, original: char, c: char) {
+    match c {
+        _ if c == original => output.push(c),
+        '<' => output.push_str("&lt;"),
+        '>' => output.push_str("&gt;"),
+        '&' => output.push_str("&amp;"),
+        '"' => output.push_str("&quot;"),
+        '\'' => output.push_str("&#39;"),
+        _ => output.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::Censor;
+
+    #[test]
+    fn censors_text_nodes_and_attributes() {
+        let mut html = String::from(
+            "<!-- fuck --><div class=fuck data-x='1 > 0'><img alt='fuck' src=\"fuck.png\">fu&shy;ck &lt;fuck&gt;\n<script>let fuck = 1 < 2;</script><STYLE>.fuck {}</STYLE><a title=fuck>a < b</a></div>",
+        );
+
+        let modified = censor_html(&mut html, &HtmlPolicy::new()).unwrap();
+
+        assert_eq!(modified, 3);
+        assert_eq!(
+            html,
+            "<!-- fuck --><div class=fuck data-x='1 > 0'><img alt='f***' src=\"fuck.png\">fu&shy;ck &lt;f***&gt;\n<script>let fuck = 1 < 2;</script><STYLE>.fuck {}</STYLE><a title=f***>a < b</a></div>"
+        );
+    }
+
+    #[test]
+    fn escapes_masks() {
+        let mut html = String::from("<p>fuck</p>");
+        let policy = HtmlPolicy::new()
+            .with_options(JsonOptions::new().with_censor(Censor::new().with_replacement('<')));
+
+        censor_html(&mut html, &policy).unwrap();
+
+        assert_eq!(html, "<p>f&lt;&lt;&lt;</p>");
+    }
+}
@@ -0,0 +1,265 @@
+//! Incremental censoring for live typing
+
+use std::ops::Range;
+
+use regex::Regex;
+
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::budget::{Budget, Tracker};
+use crate::censor::{censor_text, compile_custom, CensorTypes, Rules};
+use crate::error::Error;
+#[cfg(feature = "wasm-min")]
+use crate::error::WasmError;
+
+/// Number of whitespace separated words before the edit point that are censored again
+const DEFAULT_LOOKBACK: usize = 2;
+
+/// Censor state kept across successive versions of a message that is being typed.
+///
+/// Every update only censors the tail of the text starting a few words before the
+/// first changed character, everything before it is reused from the previous run.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::CensorTypes;
+/// use little_censor::incremental::IncrementalCensor;
+///
+/// let mut live = IncrementalCensor::new(Box::new([CensorTypes::IP]), None);
+///
+/// assert_eq!(live.update(String::from("my ip is 127.0.0")).unwrap(), None);
+/// assert_eq!(live.update(String::from("my ip is 127.0.0.1")).unwrap(), Some(9..18));
+/// assert_eq!(live.censored(), "my ip is *********");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen)]
+pub struct IncrementalCensor {
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+    /// Compiled argument of `CensorTypes::Custom`, kept from the first update on
+    custom: Option<Regex>,
+    lookback: usize,
+    text: String,
+    censored: String,
+}
+
+impl IncrementalCensor {
+    /// Creates empty state for the given censor types and argument
+    pub fn new(types: Box<[CensorTypes]>, arg: Option<String>) -> Self {
+        Self {
+            types,
+            arg,
+            custom: None,
+            lookback: DEFAULT_LOOKBACK,
+            text: String::new(),
+            censored: String::new(),
+        }
+    }
+
+    /// Sets how many words before the edit point are censored again on every update
+    pub fn with_lookback(mut self, words: usize) -> Self {
+        self.lookback = words;
+        self
+    }
+
+    /// Censors new version of the text
+    ///
+    /// Returns byte range of `text` whose censored form changed compared to the
+    /// previous update, or `None` when the new text didn't affect any masking.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`crate::censor::censor`].
+    pub fn update(&mut self, text: String) -> Result<Option<Range<usize>>, Error> {
+        let common = self
+            .text
+            .chars()
+            .zip(text.chars())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let anchor = self.anchor(&text, common);
+        let anchor_byte = byte_offset(&text, anchor);
+
+        let budget = Budget::new();
+        if self.custom.is_none() {
+            self.custom = compile_custom(&self.types, self.arg.as_deref())?;
+        }
+        let rules = Rules::new(&self.types, self.custom.clone(), None);
+        let tail = censor_text(
+            &text[anchor_byte..],
+            &rules,
+            &mut Tracker::new(&budget),
+            None,
+        )?
+        .censored;
+        let censored: String = self
+            .censored
+            .chars()
+            .take(anchor)
+            .chain(tail.chars())
+            .collect();
+
+        // What user would see if the new characters didn't change any masking
+        let expected: Vec<char> = self
+            .censored
+            .chars()
+            .take(common)
+            .chain(text.chars().skip(common))
+            .collect();
+        let current: Vec<char> = censored.chars().collect();
+
+        let first = current
+            .iter()
+            .zip(expected.iter())
+            .position(|(new, old)| new != old);
+        let last = current
+            .iter()
+            .zip(expected.iter())
+            .rposition(|(new, old)| new != old);
+
+        self.text = text;
+        self.censored = censored;
+
+        Ok(match (first, last) {
+            (Some(first), Some(last)) => {
+                Some(byte_offset(&self.text, first)..byte_offset(&self.text, last + 1))
+            }
+            _ => None,
+        })
+    }
+
+    /// Last censored text
+    pub fn censored(&self) -> &str {
+        &self.censored
+    }
+
+    /// Last original text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Forgets all previous updates
+    pub fn reset(&mut self) {
+        self.text.clear();
+        self.censored.clear();
+    }
+
+    /// Char index from which the text has to be censored again
+    fn anchor(&self, text: &str, common: usize) -> usize {
+        let chars: Vec<char> = text.chars().take(common).collect();
+        let mut index = chars.len();
+        let mut words = 0;
+
+        while index > 0 {
+            // Skip the word and whitespace before it
+            while index > 0 && !chars[index - 1].is_whitespace() {
+                index -= 1;
+            }
+            if words == self.lookback {
+                break;
+            }
+            while index > 0 && chars[index - 1].is_whitespace() {
+                index -= 1;
+            }
+            words += 1;
+        }
+
+        index
+    }
+}
+
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen]
+impl IncrementalCensor {
+    #[wasm_bindgen(constructor)]
+    pub fn wasm_new(types: Box<[CensorTypes]>, arg: Option<String>) -> Self {
+        Self::new(types, arg)
+    }
+
+    /// Returns `[start, end]` of changed region or `undefined`
+    #[wasm_bindgen(js_name = update)]
+    pub fn wasm_update(&mut self, text: String) -> Result<Option<Box<[usize]>>, WasmError> {
+        let span = self.update(text).map_err(WasmError::from)?;
+        Ok(span.map(|span| Box::new([span.start, span.end]) as Box<[usize]>))
+    }
+
+    #[wasm_bindgen(getter = censored)]
+    pub fn wasm_censored(&self) -> String {
+        self.censored.clone()
+    }
+}
+
+/// Converts char index into byte offset
+fn byte_offset(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::censor;
+
+    #[test]
+    fn typing_matches_full_censor() {
+        let message = "go to https://example.net/ you fuck";
+        let mut live = IncrementalCensor::new(Box::new([CensorTypes::Link]), None);
+
+        for end in 1..=message.len() {
+            live.update(message[..end].to_owned()).unwrap();
+        }
+
+        let full = censor(message.to_owned(), Box::new([CensorTypes::Link]), None).unwrap();
+        assert_eq!(live.censored(), full.censored);
+    }
+
+    #[test]
+    fn returns_only_new_span() {
+        let mut live = IncrementalCensor::new(Box::new([]), None);
+
+        assert_eq!(live.update(String::from("hello fu")).unwrap(), None);
+        assert_eq!(
+            live.update(String::from("hello fuck")).unwrap(),
+            Some(7..10)
+        );
+        assert_eq!(live.update(String::from("hello fuck world")).unwrap(), None);
+        assert_eq!(live.censored(), "hello f*** world");
+    }
+
+    #[test]
+    fn handles_deletion() {
+        let mut live = IncrementalCensor::new(Box::new([]), None);
+
+        live.update(String::from("fuck ąę")).unwrap();
+        assert_eq!(live.update(String::from("fu")).unwrap(), Some(1..2));
+        assert_eq!(live.censored(), "fu");
+        assert_eq!(live.text(), "fu");
+    }
+
+    #[test]
+    fn compiles_custom_once() {
+        let mut live = IncrementalCensor::new(
+            Box::new([CensorTypes::Custom]),
+            Some(String::from("se+cret")),
+        );
+
+        live.update(String::from("my secret")).unwrap();
+        assert!(live.custom.is_some());
+        live.update(String::from("my secret is seecret")).unwrap();
+
+        assert_eq!(live.censored(), "my ****** is *******");
+    }
+
+    #[test]
+    fn missing_argument() {
+        let mut live = IncrementalCensor::new(Box::new([CensorTypes::Custom]), None);
+
+        assert!(matches!(
+            live.update(String::from("text")),
+            Err(Error::NoArgs)
+        ));
+    }
+}
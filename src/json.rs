@@ -0,0 +1,204 @@
+//! Censoring string values of JSON documents, e.g. webhook payloads
+
+use rustrict::Type;
+use serde_json::Value;
+
+use crate::censor::{Censor, CensorTypes, Censored};
+use crate::error::Error;
+
+/// Censor and rules applied to a JSON document, also used for YAML and TOML
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::CensorTypes;
+/// use little_censor::json::{censor_json, JsonOptions};
+/// use serde_json::json;
+///
+/// let mut payload = json!({ "user": { "email": "me@example.net", "id": "me@example.net" } });
+/// let options = JsonOptions::new()
+///     .with_types([CensorTypes::Email])
+///     .with_keys(["*email*"]);
+///
+/// let report = censor_json(&mut payload, &options).unwrap();
+///
+/// assert_eq!(payload["user"]["email"], "**************");
+/// assert_eq!(payload["user"]["id"], "me@example.net");
+/// assert_eq!(report.paths, vec!["/user/email"]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct JsonOptions {
+    censor: Censor,
+    types: Vec<CensorTypes>,
+    arg: Option<String>,
+    keys: Vec<String>,
+}
+
+impl JsonOptions {
+    /// Censors every string with a default censor without additional types
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Censors strings with this censor
+    pub fn with_censor(mut self, censor: Censor) -> Self {
+        self.censor = censor;
+        self
+    }
+
+    /// Sets additional types of censoring
+    pub fn with_types(mut self, types: impl IntoIterator<Item = CensorTypes>) -> Self {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    /// Sets argument for `CensorTypes::Custom`
+    pub fn with_arg(mut self, arg: String) -> Self {
+        self.arg = Some(arg);
+        self
+    }
+
+    /// Censors only strings under keys matching any of the patterns
+    ///
+    /// Patterns are case insensitive and `*` matches any part of the key, e.g. `"*email*"`.
+    pub fn with_keys<S: AsRef<str>>(mut self, patterns: impl IntoIterator<Item = S>) -> Self {
+        self.keys = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Whether strings aren't filtered by keys
+    pub(crate) fn selects_all(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether strings under this key are censored
+    pub(crate) fn selects(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.keys
+            .iter()
+            .any(|pattern| matches_pattern(pattern, &key))
+    }
+
+    /// Censors a single string
+    pub(crate) fn censor(&self, text: String) -> Result<Censored, Error> {
+        self.censor.censor(
+            text,
+            self.types.clone().into_boxed_slice(),
+            self.arg.clone(),
+        )
+    }
+}
+
+/// Strings modified by [`censor_json`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonReport {
+    /// JSON pointers of modified strings, e.g. `"/user/email"`
+    pub paths: Vec<String>,
+    /// Type of vulgar words found in all strings
+    pub typ: Type,
+}
+
+/// Censors string values of the document in place, returns which of them were modified
+///
+/// Object keys are left as they are.
+///
+/// # Errors
+///
+/// Returns the same errors as [`Censor::censor`].
+pub fn censor_json(value: &mut Value, options: &JsonOptions) -> Result<JsonReport, Error> {
+    let mut report = JsonReport {
+        paths: Vec::new(),
+        typ: Type::NONE,
+    };
+    let selected = options.selects_all();
+    censor_value(value, options, &mut String::new(), selected, &mut report)?;
+    Ok(report)
+}
+
+fn censor_value(
+    value: &mut Value,
+    options: &JsonOptions,
+    path: &mut String,
+    selected: bool,
+    report: &mut JsonReport,
+) -> Result<(), Error> {
+    let len = path.len();
+    match value {
+        Value::String(text) if selected => {
+            let censored = options.censor(std::mem::take(text))?;
+            if censored.changed() {
+                report.paths.push(path.clone());
+            }
+            report.typ |= censored.typ & !Type::SAFE;
+            *text = censored.censored;
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                path.push_str(&format!("/{index}"));
+                censor_value(item, options, path, selected, report)?;
+                path.truncate(len);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                let selected = selected || options.selects(key);
+                censor_value(field, options, path, selected, report)?;
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether lowercase key matches the pattern where `*` matches any part
+fn matches_pattern(pattern: &str, key: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match key.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn key_patterns() {
+        assert!(matches_pattern("*email*", "contact_email_address"));
+        assert!(matches_pattern("user_*", "user_name"));
+        assert!(!matches_pattern("user_*", "name_user_"));
+        assert!(matches_pattern("name", "name"));
+    }
+
+    #[test]
+    fn censor_whole_document() {
+        let mut payload = json!({ "a/b": ["fuck", 1, { "c": "fine" }] });
+
+        let report = censor_json(&mut payload, &JsonOptions::new()).unwrap();
+
+        assert_eq!(payload, json!({ "a/b": ["f***", 1, { "c": "fine" }] }));
+        assert_eq!(report.paths, vec!["/a~1b/0"]);
+    }
+}
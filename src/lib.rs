@@ -1,20 +1,134 @@
-use rustrict::Trie;
+use crate::phrase::Phrase;
 
+#[cfg(feature = "actix")]
+pub mod actix;
+pub mod audit;
+#[cfg(any(feature = "tower", feature = "actix"))]
+mod body;
+pub mod budget;
+#[cfg(feature = "build-helper")]
+pub mod build_helper;
+pub mod category;
 pub mod censor;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod context;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod duplicate;
+#[cfg(feature = "eml")]
+pub mod eml;
 pub mod error;
-pub use rustrict::Type;
-
-#[cfg(feature = "wasm")]
-use wasm_bindgen::prelude::wasm_bindgen;
+mod expiring;
+pub mod ext;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fields;
+pub mod flood;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hook;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod incremental;
+pub mod json;
+pub mod link;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod metrics;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "napi")]
+pub mod napi;
+pub mod pack;
+mod phrase;
+pub mod policy;
+pub mod profile;
+#[cfg(feature = "redis")]
+pub mod redis_store;
+pub mod report;
+pub mod reputation;
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
+pub mod snapshot;
+pub mod span;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod state;
+pub mod stats;
+pub mod store;
+pub mod stream;
+#[cfg(feature = "subtitles")]
+pub mod subtitles;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
+pub mod type_name;
+pub mod username;
+pub mod validation;
 #[cfg(feature = "wasm")]
-use wasm_bindgen::JsError;
+pub mod wasm;
+mod words;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+pub use rustrict::Type;
 
 pub use error::Error;
+#[cfg(feature = "wasm-min")]
+use error::WasmError;
+pub use ext::LittleCensor;
+/// Implements [`fields::CensorFields`] for a struct
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::Censor;
+/// use little_censor::fields::CensorFields;
+///
+/// #[derive(little_censor::Censor)]
+/// struct Profile {
+///     name: String,
+///     #[censor(types(Link))]
+///     bio: Option<String>,
+///     #[censor(skip)]
+///     id: String,
+///     age: u32,
+/// }
+///
+/// let mut profile = Profile {
+///     name: String::from("fuck"),
+///     bio: Some(String::from("https://example.net")),
+///     id: String::from("fuck"),
+///     age: 30,
+/// };
+///
+/// assert!(profile.censor_fields(&Censor::new()).unwrap());
+/// assert_eq!(profile.name, "f***");
+/// assert_eq!(profile.bio.as_deref(), Some("*******************"));
+/// assert_eq!(profile.id, "fuck");
+/// ```
+#[cfg(feature = "derive")]
+pub use little_censor_derive::Censor;
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::prelude::wasm_bindgen;
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::JsValue;
+// Scaffolding generated in `mobile` expects its tag in the crate root
+#[cfg(feature = "uniffi")]
+use mobile::UniFfiTag;
 
 /// Type of the vulgar word
-#[cfg(feature = "wasm")]
-#[wasm_bindgen(js_name = "Type")]
-#[derive(Default)]
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen(js_name = "Type"))]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum JsType {
     Profane,
     Offensive,
@@ -33,8 +147,8 @@ pub enum JsType {
     Any,
 }
 
-#[cfg(feature = "wasm")]
-#[wasm_bindgen]
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen)]
 impl JsType {
     fn into(self) -> Type {
         match self {
@@ -56,21 +170,221 @@ impl JsType {
     }
 }
 
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+impl JsType {
+    const ALL: [JsType; 14] = [
+        Self::Profane,
+        Self::Offensive,
+        Self::Sexual,
+        Self::Mean,
+        Self::Evasive,
+        Self::Spam,
+        Self::Safe,
+        Self::Mild,
+        Self::Moderate,
+        Self::Severe,
+        Self::MildOrHigher,
+        Self::ModerateOrHigher,
+        Self::Inappropriate,
+        Self::Any,
+    ];
+
+    /// Flag with the value of a JavaScript `Type`
+    #[cfg(feature = "wasm-min")]
+    fn from_number(value: f64) -> Option<JsType> {
+        Self::ALL
+            .into_iter()
+            .find(|&flag| f64::from(flag as u32) == value)
+    }
+
+    /// Flag equal to the type, `None` for combinations like `Type::SEXUAL & Type::SEVERE`
+    pub fn from_type(typ: Type) -> Option<JsType> {
+        Self::ALL
+            .into_iter()
+            .find(|&flag| JsType::into(flag) == typ)
+    }
+
+    /// Lowercase name of the flag, e.g. `"mild_or_higher"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Profane => "profane",
+            Self::Offensive => "offensive",
+            Self::Sexual => "sexual",
+            Self::Mean => "mean",
+            Self::Evasive => "evasive",
+            Self::Spam => "spam",
+            Self::Safe => "safe",
+            Self::Mild => "mild",
+            Self::Moderate => "moderate",
+            Self::Severe => "severe",
+            Self::MildOrHigher => "mild_or_higher",
+            Self::ModerateOrHigher => "moderate_or_higher",
+            Self::Inappropriate => "inappropriate",
+            Self::Any => "any",
+        }
+    }
+
+    /// Combines flags into a single type
+    ///
+    /// Categories and severities are joined separately and then intersected, so
+    /// `[Sexual, Severe]` is a severe sexual word. Missing part means any category
+    /// or severity, `Safe` alone gives a safe word.
+    pub(crate) fn combine(flags: &[JsType]) -> Type {
+        if flags.iter().all(|&flag| flag == Self::Safe) && !flags.is_empty() {
+            return Type::SAFE;
+        }
+
+        let (severities, categories): (Vec<JsType>, Vec<JsType>) = flags
+            .iter()
+            .filter(|&&flag| flag != Self::Safe)
+            .partition(|flag| {
+                matches!(
+                    flag,
+                    Self::Mild
+                        | Self::Moderate
+                        | Self::Severe
+                        | Self::MildOrHigher
+                        | Self::ModerateOrHigher
+                )
+            });
+        let join = |flags: Vec<JsType>| {
+            flags
+                .into_iter()
+                .map(JsType::into)
+                .reduce(|a, b| a | b)
+                .unwrap_or(Type::ANY)
+        };
+        join(categories) & join(severities)
+    }
+
+    /// Type combined from names of flags, e.g. `["sexual", "severe"]`
+    #[cfg(any(feature = "napi", feature = "uniffi"))]
+    pub(crate) fn combine_names(names: &[String]) -> Result<Type, Error> {
+        let flags = names
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<Vec<JsType>, Error>>()?;
+        Ok(Self::combine(&flags))
+    }
+
+    /// Names of every single flag set in the type
+    #[cfg(any(feature = "napi", feature = "uniffi"))]
+    pub(crate) fn names(typ: Type) -> Vec<String> {
+        Self::flags(typ)
+            .into_iter()
+            .map(|flag| flag.to_string())
+            .collect()
+    }
+
+    /// Every single flag set in the type
+    pub(crate) fn flags(typ: Type) -> Vec<JsType> {
+        if typ.is(Type::SAFE) {
+            return vec![Self::Safe];
+        }
+        [
+            Self::Profane,
+            Self::Offensive,
+            Self::Sexual,
+            Self::Mean,
+            Self::Evasive,
+            Self::Spam,
+            Self::Mild,
+            Self::Moderate,
+            Self::Severe,
+        ]
+        .into_iter()
+        .filter(|&flag| typ.is(JsType::into(flag)))
+        .collect()
+    }
+}
+
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+impl From<JsType> for Type {
+    fn from(flag: JsType) -> Self {
+        JsType::into(flag)
+    }
+}
+
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+impl std::str::FromStr for JsType {
+    type Err = Error;
+
+    /// Parses name of the flag, e.g. `"sexual"` or `"mild_or_higher"`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|flag| flag.name() == name)
+            .ok_or(Error::UnknownType)
+    }
+}
+
+#[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+impl std::fmt::Display for JsType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses name of a type, e.g. `"sexual"` or `"mild_or_higher"`
+///
+/// # Errors
+///
+/// Throws an Error with code `UNKNOWN_TYPE` and the name as `input` if there is
+/// no type with this name.
+///
+/// # Examples
+///
+/// parse_type("sexual") === Type.Sexual;
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "parse_type")]
+pub fn parse_type(name: &str) -> Result<JsType, WasmError> {
+    name.parse::<JsType>()
+        .map_err(|error| WasmError::from(error).with_input(name))
+}
+
+/// Name of a type, the reverse of `parse_type`
+///
+/// # Examples
+///
+/// type_to_string(Type.MildOrHigher) === "mild_or_higher";
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "type_to_string")]
+pub fn type_to_string(typ: JsType) -> String {
+    typ.to_string()
+}
+
 /// A struct representing a vulgar word with its associated type.
 ///
+/// Words separated by whitespace form a phrase, `~N` between them allows up to `N`
+/// other words, e.g. `"kill ~2 yourself"`.
+///
 /// # Examples
 ///
 /// new Vulgar("VulgarWord", Type.Inappropriate);
-#[derive(Default, Debug, PartialEq, Eq)]
-#[cfg(feature = "wasm")]
+/// new Vulgar("VulgarWord", [Type.Sexual, Type.Severe]);
+/// Vulgar.with_types("VulgarWord", [Type.Sexual, Type.Severe]);
+/// Vulgar.near("kill", "yourself", 2, Type.Severe);
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen]
 pub struct Vulgar {
     word: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type", with = "type_name"))]
     word_type: Type,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    weight: Option<u32>,
 }
 
 /// A struct representing a vulgar word with its associated type.
 ///
+/// Words separated by whitespace form a phrase, `~N` between them allows up to `N`
+/// other words, e.g. `"kill ~2 yourself"`.
+///
 /// # Examples
 ///
 /// ```
@@ -78,33 +392,329 @@ pub struct Vulgar {
 ///
 /// let vulgar_word = Vulgar::new(String::from("VulgarWord"), Some(Type::INAPPROPRIATE));
 ///
-/// assert_eq!(vulgar_word, Vulgar { word: "VulgarWord".to_owned(), word_type: Type::INAPPROPRIATE });
+/// assert_eq!(
+///     vulgar_word,
+///     Vulgar { word: "VulgarWord".to_owned(), word_type: Type::INAPPROPRIATE, weight: None }
+/// );
 /// ```
-#[derive(Default, Debug, PartialEq, Eq)]
-#[cfg(not(feature = "wasm"))]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg(not(feature = "wasm-min"))]
 pub struct Vulgar {
     pub word: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type", with = "type_name"))]
     pub word_type: Type,
+    /// How much the word counts towards [`censor::Censored::score`], 1 without a weight
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub weight: Option<u32>,
 }
 
-#[cfg(feature = "wasm")]
+/// Vulgar word known at compile time, e.g. generated by the `build_helper` module
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::{add_static_words, StaticVulgar, Type};
+///
+/// static WORDS: &[StaticVulgar] = &[
+///     StaticVulgar::new("static_word", Type::INAPPROPRIATE.bits(), None),
+///     StaticVulgar::new("static_spam", Type::SPAM.bits(), Some(2)),
+/// ];
+///
+/// assert_eq!(add_static_words(WORDS).unwrap().added, 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticVulgar {
+    pub word: &'static str,
+    pub word_type: Type,
+    /// How much the word counts towards [`censor::Censored::score`], 1 without a weight
+    pub weight: Option<u32>,
+}
+
+impl StaticVulgar {
+    /// Word of the type given by its bits, unknown bits are dropped
+    pub const fn new(word: &'static str, type_bits: u32, weight: Option<u32>) -> Self {
+        Self {
+            word,
+            word_type: Type::from_bits_truncate(type_bits),
+            weight,
+        }
+    }
+}
+
+impl From<&StaticVulgar> for Vulgar {
+    fn from(vulgar: &StaticVulgar) -> Self {
+        Self {
+            word: vulgar.word.to_owned(),
+            word_type: vulgar.word_type,
+            weight: vulgar.weight,
+        }
+    }
+}
+
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen]
+extern "C" {
+    /// Single type or flags combined like in `Vulgar.with_types`
+    #[wasm_bindgen(typescript_type = "Type | Type[]")]
+    pub type TypeOrTypes;
+}
+
+#[cfg(feature = "wasm-min")]
 impl Vulgar {
-    #[wasm_bindgen(constructor)]
     pub fn new(word: String, word_type: Option<JsType>) -> Self {
         Self {
             word,
             word_type: word_type.unwrap_or_default().into(),
+            weight: None,
         }
     }
 }
 
-#[cfg(not(feature = "wasm"))]
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen]
 impl Vulgar {
+    /// Word of the type, flags of an array are combined like in `Vulgar.with_types`
+    ///
+    /// # Errors
+    ///
+    /// Throws an Error with code `UNKNOWN_TYPE` if any of the values isn't a `Type`.
+    #[wasm_bindgen(constructor)]
+    pub fn new_w(word: String, types: Option<TypeOrTypes>) -> Result<Vulgar, WasmError> {
+        let value = types.map_or(JsValue::UNDEFINED, JsValue::from);
+        let flag = |value: JsValue| {
+            value
+                .as_f64()
+                .and_then(JsType::from_number)
+                .ok_or(Error::UnknownType)
+        };
+        let word_type = if value.is_undefined() || value.is_null() {
+            Type::default()
+        } else if js_sys::Array::is_array(&value) {
+            let flags = js_sys::Array::from(&value)
+                .iter()
+                .map(flag)
+                .collect::<Result<Vec<JsType>, Error>>()?;
+            JsType::combine(&flags)
+        } else {
+            flag(value)?.into()
+        };
+        Ok(Self {
+            word,
+            word_type,
+            weight: None,
+        })
+    }
+
+    /// Safe word, never censored even if the word list contains it
+    pub fn safe(word: String) -> Self {
+        Self {
+            word,
+            word_type: Type::SAFE,
+            weight: None,
+        }
+    }
+
+    /// Word of every given type, e.g. `[Type.Sexual, Type.Severe]`
+    pub fn with_types(word: String, types: Box<[JsType]>) -> Self {
+        Self {
+            word,
+            word_type: JsType::combine(&types),
+            weight: None,
+        }
+    }
+
+    /// Sets how much the word counts towards the `score` of censored messages
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Rule matching `first` followed by `second` with at most `within` words between them
+    pub fn near(first: &str, second: &str, within: usize, word_type: Option<JsType>) -> Self {
+        Self::new(format!("{first} ~{within} {second}"), word_type)
+    }
+    #[wasm_bindgen(getter)]
+    pub fn word(&self) -> String {
+        self.word.clone()
+    }
+
+    /// Every flag of the word's type
+    #[wasm_bindgen(getter)]
+    pub fn types(&self) -> Vec<JsType> {
+        JsType::flags(self.word_type)
+    }
+}
+
+#[cfg(not(feature = "wasm-min"))]
+impl Vulgar {
+    /// Word of the type, e.g. `Type::SEXUAL & Type::SEVERE`, inappropriate by default
     pub fn new(word: String, word_type: Option<Type>) -> Self {
         Self {
             word,
             word_type: word_type.unwrap_or_default(),
+            weight: None,
+        }
+    }
+
+    /// Safe word, never censored even if the word list contains it, see [`add_words`]
+    pub fn safe(word: impl Into<String>) -> Self {
+        Self {
+            word: word.into(),
+            word_type: Type::SAFE,
+            weight: None,
+        }
+    }
+
+    /// Sets how much the word counts towards [`censor::Censored::score`]
+    ///
+    /// Lets terms of the same type weigh differently, e.g. a slur more than a mild
+    /// swear word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::{Type, Vulgar};
+    ///
+    /// let vulgar = Vulgar::new(String::from("slur"), Some(Type::SEVERE)).with_weight(10);
+    ///
+    /// assert_eq!(vulgar.weight, Some(10));
+    /// ```
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Word of every given type, joined with `|`
+    ///
+    /// Each type can already be a combination, inappropriate when there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::{Type, Vulgar};
+    ///
+    /// let vulgar = Vulgar::with_types("word", [Type::SEXUAL & Type::SEVERE, Type::MEAN]);
+    ///
+    /// assert!(vulgar.word_type.is(Type::MEAN & Type::MILD));
+    /// assert!(!vulgar.word_type.is(Type::SEXUAL & Type::MILD));
+    /// ```
+    pub fn with_types(word: impl Into<String>, types: impl IntoIterator<Item = Type>) -> Self {
+        let word_type = types.into_iter().reduce(|all, typ| all | typ);
+        Self::new(word.into(), word_type)
+    }
+
+    /// Rule matching `first` followed by `second` with at most `within` words between them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::{Vulgar, Type};
+    ///
+    /// let rule = Vulgar::near("kill", "yourself", 2, Some(Type::MEAN & Type::SEVERE));
+    ///
+    /// assert_eq!(rule.word, "kill ~2 yourself");
+    /// ```
+    pub fn near(first: &str, second: &str, within: usize, word_type: Option<Type>) -> Self {
+        Self::new(format!("{first} ~{within} {second}"), word_type)
+    }
+}
+
+impl std::str::FromStr for Vulgar {
+    type Err = Error;
+
+    /// Parses an entry of a word list, the word optionally followed by `:` and its type
+    ///
+    /// The type is written like [`type_name::format`] does, e.g. `"slur:SEVERE|OFFENSIVE"`,
+    /// without it the word is inappropriate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use little_censor::{Type, Vulgar};
+    ///
+    /// let vulgar: Vulgar = "slur:SEVERE|OFFENSIVE".parse().unwrap();
+    ///
+    /// assert_eq!(vulgar, Vulgar::new(String::from("slur"), Some(Type::OFFENSIVE & Type::SEVERE)));
+    /// ```
+    fn from_str(entry: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| Error::InvalidEntry {
+            entry: entry.to_owned(),
+            reason,
+        };
+        let (word, word_type) = match entry.rsplit_once(':') {
+            Some((word, name)) => {
+                let word_type = type_name::parse(name).map_err(|_| {
+                    invalid(format!(
+                        "unknown type `{}`, expected names like `SEVERE|OFFENSIVE`",
+                        name.trim()
+                    ))
+                })?;
+                (word, word_type)
+            }
+            None => (entry, Type::default()),
+        };
+        let word = word.trim();
+        if word.is_empty() {
+            return Err(invalid(String::from("word is empty")));
+        }
+        if Phrase::parse(word, word_type).is_err() {
+            return Err(invalid(String::from("phrase has no words")));
+        }
+        Ok(Self {
+            word: word.to_owned(),
+            word_type,
+            weight: None,
+        })
+    }
+}
+
+impl TryFrom<&str> for Vulgar {
+    type Error = Error;
+
+    fn try_from(entry: &str) -> Result<Self, Self::Error> {
+        entry.parse()
+    }
+}
+
+/// Number of words added by [`add_words`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AddSummary {
+    /// Words which weren't in the word list
+    pub added: usize,
+    /// Words whose type was overwritten or merged
+    pub replaced: usize,
+    /// Words kept as they were, see [`DuplicatePolicy::KeepExisting`]
+    pub skipped: usize,
+}
+
+/// What [`add_words_with`] does with words which are already in the word list
+///
+/// Words of the default list count as well, and so do earlier words of the same call.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Fails with `Error::DuplicateWord`
+    Error,
+    /// Replaces the type, the last entry of a word wins
+    #[default]
+    Overwrite,
+    /// Keeps the type the word already has
+    KeepExisting,
+    /// Joins both types with `|`
+    MergeTypes,
+}
+
+impl DuplicatePolicy {
+    /// New type of a word at `index` which already has a type, `None` to keep it
+    fn resolve(self, index: usize, existing: Type, new: Type) -> Result<Option<Type>, Error> {
+        match self {
+            Self::Error => Err(Error::DuplicateWord { index }),
+            Self::Overwrite => Ok(Some(new)),
+            Self::KeepExisting => Ok(None),
+            Self::MergeTypes => Ok(Some(existing | new)),
         }
     }
 }
@@ -112,7 +722,16 @@ impl Vulgar {
 /// Adds a collection of vulgar words to the Trie.
 ///
 /// This function takes a vector of `Vulgar` instances and adds each word to the Trie
-/// data structure with its corresponding word type.
+/// data structure with its corresponding word type. Phrases are matched separately
+/// after the single words. Returns how many words were new and how many replaced
+/// existing ones, see [`add_words_with`] for other ways of handling those.
+///
+/// # Safe words
+///
+/// Words of `Type::SAFE`, e.g. from [`Vulgar::safe`], whitelist false positives: they
+/// are never censored, even if the default list or an earlier call added them. Adding
+/// the word again with another type blocks it again, the last entry of a word wins.
+/// A safe phrase replaces the blocking phrase of the same words.
 ///
 /// # Arguments
 ///
@@ -122,7 +741,8 @@ impl Vulgar {
 ///
 /// Returns an `Err` variant if any of the following conditions are met:
 ///
-/// * The word in any `Vulgar` instance is empty, resulting in an `Error::EmptyWord`.
+/// * The word in any `Vulgar` instance is empty, resulting in an `Error::EmptyWord`
+///   with the index of the word. Words before it are added, the rest aren't.
 ///
 /// # Examples
 ///
@@ -134,24 +754,277 @@ impl Vulgar {
 ///     Vulgar::new("bad_word2".to_string(), None),
 /// ];
 ///
-/// assert_eq!(add_words(vulgars).unwrap(), ());
+/// assert_eq!(add_words(vulgars).unwrap().added, 2);
+///
+/// add_words(vec![Vulgar::safe("scunthorpe")]).unwrap();
 /// ```
-pub fn add_words(vulgars: Vec<Vulgar>) -> Result<(), Error> {
-    unsafe {
-        for vulgar in vulgars {
-            if vulgar.word.is_empty() {
-                return Err(Error::EmptyWord);
-            }
-            Trie::customize_default().set(&vulgar.word, vulgar.word_type);
+pub fn add_words(vulgars: Vec<Vulgar>) -> Result<AddSummary, Error> {
+    add_words_with(vulgars, DuplicatePolicy::Overwrite)
+}
+
+/// Adds a collection of vulgar words to the Trie, words which are already in the word
+/// list are handled by the policy
+///
+/// # Errors
+///
+/// Returns `Error::EmptyWord` if any of the words is empty or `Error::DuplicateWord`
+/// if it's already in the word list and the policy is [`DuplicatePolicy::Error`], both
+/// with the index of the word. Words before it are added, the rest aren't.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::{add_words, add_words_with, DuplicatePolicy, Type, Vulgar};
+///
+/// add_words(vec![Vulgar::new(String::from("imported1"), Some(Type::MEAN))]).unwrap();
+///
+/// let vulgars = vec![
+///     Vulgar::new(String::from("imported1"), Some(Type::SPAM)),
+///     Vulgar::new(String::from("imported2"), None),
+/// ];
+/// let summary = add_words_with(vulgars, DuplicatePolicy::KeepExisting).unwrap();
+///
+/// assert_eq!((summary.added, summary.replaced, summary.skipped), (1, 0, 1));
+/// ```
+pub fn add_words_with(vulgars: Vec<Vulgar>, policy: DuplicatePolicy) -> Result<AddSummary, Error> {
+    let mut summary = AddSummary::default();
+    for (index, vulgar) in vulgars.into_iter().enumerate() {
+        match add_word(index, vulgar, policy)? {
+            Added::New => summary.added += 1,
+            Added::Replaced => summary.replaced += 1,
+            Added::Skipped => summary.skipped += 1,
         }
     }
+    Ok(summary)
+}
+
+/// Adds words known at compile time to the Trie, see [`StaticVulgar`]
+///
+/// # Errors
+///
+/// Returns the same errors as [`add_words`].
+pub fn add_static_words(vulgars: &[StaticVulgar]) -> Result<AddSummary, Error> {
+    add_words(vulgars.iter().map(Vulgar::from).collect())
+}
+
+/// Adds every valid word of a collection to the Trie, returns errors of the skipped ones
+///
+/// Unlike [`add_words`] it doesn't stop at the first invalid word, which is useful for
+/// bulk imports where a few broken entries shouldn't reject the rest. Each error
+/// identifies its word, e.g. `Error::EmptyWord` carries the index of the word.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::{add_valid_words, Vulgar, error::Error};
+///
+/// let vulgars = vec![
+///     Vulgar::new("bad_word3".to_string(), None),
+///     Vulgar::new(String::new(), None),
+/// ];
+///
+/// let errors = add_valid_words(vulgars);
+///
+/// assert!(matches!(errors[..], [Error::EmptyWord { index: 1 }]));
+/// ```
+pub fn add_valid_words(vulgars: Vec<Vulgar>) -> Vec<Error> {
+    vulgars
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, vulgar)| add_word(index, vulgar, DuplicatePolicy::Overwrite).err())
+        .collect()
+}
+
+/// How a single word was added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Added {
+    New,
+    Replaced,
+    Skipped,
+}
+
+/// Adds a single word at `index` of the given words to the Trie or the phrases
+fn add_word(index: usize, vulgar: Vulgar, policy: DuplicatePolicy) -> Result<Added, Error> {
+    if vulgar.word.is_empty() {
+        return Err(Error::EmptyWord { index });
+    }
+    let phrase =
+        Phrase::parse(&vulgar.word, vulgar.word_type).map_err(|_| Error::EmptyWord { index })?;
+    if let Some(mut phrase) = phrase {
+        let mut phrases = phrase::PHRASES.write().unwrap_or_else(|e| e.into_inner());
+        let added = match phrases.iter().position(|other| other.same_terms(&phrase)) {
+            Some(existing) => match policy.resolve(index, phrases[existing].typ, phrase.typ)? {
+                Some(typ) => {
+                    phrase.typ = typ;
+                    phrases.remove(existing);
+                    Added::Replaced
+                }
+                None => return Ok(Added::Skipped),
+            },
+            None => Added::New,
+        };
+        words::record(&words::phrase_key(&vulgar.word), None, Some(phrase.typ));
+        words::set_weight(&vulgar.word, vulgar.weight);
+        phrases.push(phrase);
+        return Ok(added);
+    }
+
+    words::write_trie(|trie| {
+        let previous = trie.remove(&vulgar.word);
+        let resolved = match previous {
+            Some(previous) => policy.resolve(index, previous, vulgar.word_type),
+            None => Ok(Some(vulgar.word_type)),
+        };
+        match resolved {
+            Ok(Some(word_type)) => {
+                trie.set(&vulgar.word, word_type);
+                words::record(&vulgar.word, previous, Some(word_type));
+                words::set_weight(&vulgar.word, vulgar.weight);
+                Ok(if previous.is_some() {
+                    Added::Replaced
+                } else {
+                    Added::New
+                })
+            }
+            _ => {
+                if let Some(previous) = previous {
+                    trie.set(&vulgar.word, previous);
+                }
+                resolved.map(|_| Added::Skipped)
+            }
+        }
+    })
+}
+
+/// Prometheus counters of every censor in the text format, e.g. for a `/metrics` endpoint
+///
+/// Covers processed messages, matches by rule, messages by type, latency and hits of
+/// the pattern cache, see [`metrics::gather`].
+#[cfg(feature = "metrics")]
+pub fn metrics() -> String {
+    metrics::gather()
+}
+
+/// Marks words as safe, so they are never censored even if the default list contains them
+///
+/// # Errors
+///
+/// Returns `Error::EmptyWord` if any of the words is empty.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::add_safe_words;
+///
+/// add_safe_words(vec![String::from("scunthorpe")]).unwrap();
+/// ```
+pub fn add_safe_words(words: Vec<String>) -> Result<(), Error> {
+    add_words(words.into_iter().map(Vulgar::safe).collect())?;
     Ok(())
 }
 
+/// Removes words and phrases from the word list, including the default ones
+///
+/// Returns number of words which were in the list.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::{add_words, remove_words, Vulgar};
+///
+/// add_words(vec![Vulgar::new(String::from("removed_word"), None)]).unwrap();
+///
+/// assert_eq!(remove_words(vec![String::from("removed_word")]), 1);
+/// ```
+pub fn remove_words(words: Vec<String>) -> usize {
+    let mut removed = 0;
+    for word in words {
+        if word.trim().contains(char::is_whitespace) {
+            let phrase = match Phrase::parse(&word, Type::NONE) {
+                Ok(Some(phrase)) => phrase,
+                _ => continue,
+            };
+            let mut phrases = phrase::PHRASES.write().unwrap_or_else(|e| e.into_inner());
+            let count = phrases.len();
+            phrases.retain(|other| !other.same_terms(&phrase));
+            if phrases.len() < count {
+                removed += 1;
+                words::record(&words::phrase_key(&word), None, None);
+                words::set_weight(&word, None);
+            }
+            continue;
+        }
+        if let Some(previous) = words::write_trie(|trie| trie.remove(&word)) {
+            removed += 1;
+            words::record(&word, Some(previous), None);
+            words::set_weight(&word, None);
+        }
+    }
+    removed
+}
+
+/// Words and phrases added with [`add_words`] or [`add_safe_words`], sorted by word
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::{add_words, list_words, Vulgar};
+///
+/// add_words(vec![Vulgar::new(String::from("listed_word"), None)]).unwrap();
+///
+/// assert!(list_words().iter().any(|vulgar| vulgar.word == "listed_word"));
+/// ```
+pub fn list_words() -> Vec<Vulgar> {
+    words::CHANGES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter_map(|(word, change)| {
+            change.current.map(|word_type| Vulgar {
+                word: word.clone(),
+                word_type,
+                weight: words::weight(word),
+            })
+        })
+        .collect()
+}
+
+/// Reverts every change made to the word list, restoring the default one
+pub fn reset_custom_words() {
+    // Taken out first, `add_word` locks the trie before the changes
+    let changes = std::mem::take(&mut *words::CHANGES.lock().unwrap_or_else(|e| e.into_inner()));
+    words::write_trie(|trie| {
+        for (word, change) in &changes {
+            if word.contains(' ') {
+                continue;
+            }
+            match change.original {
+                Some(typ) => trie.set(word, typ),
+                None => {
+                    trie.remove(word);
+                }
+            }
+        }
+    });
+    pack::LOADED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+    words::WEIGHTS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+    phrase::PHRASES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
 /// Adds a collection of vulgar words to the Trie.
 ///
 /// This function takes a vector of `Vulgar` instances and adds each word to the Trie
-/// data structure with its corresponding word type.
+/// data structure with its corresponding word type. Words of `Type.Safe` are never
+/// censored, even if the default list contains them.
 ///
 /// # Arguments
 ///
@@ -159,20 +1032,76 @@ pub fn add_words(vulgars: Vec<Vulgar>) -> Result<(), Error> {
 ///
 /// # Errors
 ///
-/// Returns an Error if any of the following conditions are met:
+/// Throws an Error with the word as `input` and `code` set to:
 ///
-/// * The word in any `Vulgar` instance is empty, resulting in an `Error::EmptyWord`.
+/// * `EMPTY_WORD` if the word in any `Vulgar` instance is empty.
 ///
 /// # Examples
 ///
-/// add_words([new Vulgar("moron", Type.Inappropriate)]);
-#[cfg(feature = "wasm")]
+/// add_words([new Vulgar("moron", Type.Inappropriate), Vulgar.safe("scunthorpe")]);
+#[cfg(feature = "wasm-min")]
 #[wasm_bindgen(js_name = "add_words")]
-pub fn add_words_w(vulgars: Box<[Vulgar]>) -> Result<(), JsError> {
-    add_words(vulgars.into_vec())?;
+pub fn add_words_w(vulgars: Box<[Vulgar]>) -> Result<(), WasmError> {
+    for (index, vulgar) in vulgars.into_vec().into_iter().enumerate() {
+        let word = vulgar.word.clone();
+        add_word(index, vulgar, DuplicatePolicy::Overwrite)
+            .map_err(|error| WasmError::from(error).with_input(word))?;
+    }
     Ok(())
 }
 
+/// Marks words as safe, so they are never censored
+///
+/// # Errors
+///
+/// Throws an Error with code `EMPTY_WORD` if any of the words is empty.
+///
+/// # Examples
+///
+/// add_safe_words(["scunthorpe"]);
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "add_safe_words")]
+pub fn add_safe_words_w(words: Box<[String]>) -> Result<(), WasmError> {
+    for (index, word) in words.into_vec().into_iter().enumerate() {
+        add_word(
+            index,
+            Vulgar::safe(word.clone()),
+            DuplicatePolicy::Overwrite,
+        )
+        .map_err(|error| WasmError::from(error).with_input(word))?;
+    }
+    Ok(())
+}
+
+/// Removes words and phrases from the word list, returns number of removed ones
+///
+/// # Examples
+///
+/// remove_words(["moron", "kill ~2 yourself"]);
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "remove_words")]
+pub fn remove_words_w(words: Box<[String]>) -> usize {
+    remove_words(words.into_vec())
+}
+
+/// Words and phrases added to the word list
+///
+/// # Examples
+///
+/// for (const vulgar of list_words()) console.log(vulgar.word, vulgar.types);
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "list_words")]
+pub fn list_words_w() -> Box<[Vulgar]> {
+    list_words().into_boxed_slice()
+}
+
+/// Reverts every change made to the word list
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "reset_custom_words")]
+pub fn reset_custom_words_w() {
+    reset_custom_words()
+}
+
 #[cfg(test)]
 mod tests {
     use rustrict::CensorStr;
@@ -187,7 +1116,8 @@ mod tests {
             vulgar_new,
             Vulgar {
                 word: String::new(),
-                word_type: Type::INAPPROPRIATE
+                word_type: Type::INAPPROPRIATE,
+                weight: None,
             }
         );
 
@@ -195,7 +1125,8 @@ mod tests {
             vulgar_default,
             Vulgar {
                 word: String::new(),
-                word_type: Type::INAPPROPRIATE
+                word_type: Type::INAPPROPRIATE,
+                weight: None,
             }
         );
     }
@@ -208,14 +1139,164 @@ mod tests {
         add_words(vec![empty]).expect("This word is empty");
     }
 
+    #[test]
+    #[cfg(not(feature = "wasm-min"))]
+    fn vulgar_with_types() {
+        let vulgar = Vulgar::with_types("word", [Type::SPAM & Type::MILD, Type::MEAN]);
+
+        assert_eq!(vulgar.word_type, (Type::SPAM & Type::MILD) | Type::MEAN);
+        assert_eq!(
+            Vulgar::with_types("word", []).word_type,
+            Type::INAPPROPRIATE
+        );
+    }
+
+    #[test]
+    fn safe_entries() {
+        let censored = || {
+            censor::censor(String::from("a glorp"), Box::new([]), None)
+                .unwrap()
+                .censored
+        };
+
+        add_words(vec![Vulgar::new(String::from("glorp"), None)]).unwrap();
+        assert_eq!(censored(), "a g****");
+
+        add_words(vec![Vulgar::safe(String::from("glorp"))]).unwrap();
+        assert_eq!(censored(), "a glorp");
+        assert_eq!(
+            list_words()
+                .into_iter()
+                .find(|vulgar| vulgar.word == "glorp")
+                .map(|vulgar| vulgar.word_type),
+            Some(Type::SAFE)
+        );
+
+        add_words(vec![
+            Vulgar::safe(String::from("glorp")),
+            Vulgar::new(String::from("glorp"), None),
+        ])
+        .unwrap();
+        assert_eq!(censored(), "a g****");
+    }
+
+    #[test]
+    fn weighted_words() {
+        add_words(vec![
+            Vulgar::new(String::from("weighty"), None).with_weight(5)
+        ])
+        .unwrap();
+
+        let censored = censor::censor(String::from("weighty, fuck"), Box::new([]), None).unwrap();
+
+        assert_eq!(censored.score, 6);
+        assert_eq!(
+            list_words()
+                .into_iter()
+                .find(|vulgar| vulgar.word == "weighty")
+                .and_then(|vulgar| vulgar.weight),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn duplicate_policies() {
+        let word = |word_type| Vulgar {
+            word: String::from("policy_word"),
+            word_type,
+            weight: None,
+        };
+        let current = || {
+            list_words()
+                .into_iter()
+                .find(|vulgar| vulgar.word == "policy_word")
+                .map(|vulgar| vulgar.word_type)
+        };
+
+        let summary = add_words(vec![word(Type::MEAN), word(Type::SPAM)]).unwrap();
+        assert_eq!(
+            summary,
+            AddSummary {
+                added: 1,
+                replaced: 1,
+                skipped: 0
+            }
+        );
+        assert_eq!(current(), Some(Type::SPAM));
+
+        let summary = add_words_with(vec![word(Type::MEAN)], DuplicatePolicy::KeepExisting);
+        assert_eq!(summary.unwrap().skipped, 1);
+        assert_eq!(current(), Some(Type::SPAM));
+
+        add_words_with(vec![word(Type::MEAN)], DuplicatePolicy::MergeTypes).unwrap();
+        assert_eq!(current(), Some(Type::SPAM | Type::MEAN));
+
+        assert!(matches!(
+            add_words_with(
+                vec![Vulgar::safe(String::from("policy_new")), word(Type::MEAN)],
+                DuplicatePolicy::Error
+            ),
+            Err(Error::DuplicateWord { index: 1 })
+        ));
+        assert_eq!(current(), Some(Type::SPAM | Type::MEAN));
+    }
+
+    #[test]
+    fn parse_entries() {
+        let vulgar = Vulgar::try_from(" kill ~2 yourself : mean|severe").unwrap();
+        assert_eq!(vulgar.word, "kill ~2 yourself");
+        assert_eq!(vulgar.word_type, Type::MEAN & Type::SEVERE);
+        assert_eq!(
+            "word".parse::<Vulgar>().unwrap().word_type,
+            Type::INAPPROPRIATE
+        );
+
+        let error = "slur:HUGE".parse::<Vulgar>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid word entry `slur:HUGE`: unknown type `HUGE`, expected names like `SEVERE|OFFENSIVE`"
+        );
+        assert!(matches!(
+            " :SEVERE".parse::<Vulgar>(),
+            Err(Error::InvalidEntry { .. })
+        ));
+    }
+
+    #[test]
+    fn empty_word_index() {
+        let words = || {
+            vec![
+                Vulgar::new(String::from("indexed_word1"), None),
+                Vulgar::new(String::from("indexed_word2"), None),
+                Vulgar::new(String::new(), None),
+                Vulgar::new(String::from("indexed_word3"), None),
+                Vulgar::new(String::new(), None),
+            ]
+        };
+
+        assert!(matches!(
+            add_words(words()),
+            Err(Error::EmptyWord { index: 2 })
+        ));
+        assert!(!String::from("indexed_word3").is_inappropriate());
+
+        let errors = add_valid_words(words());
+
+        assert!(matches!(
+            errors[..],
+            [Error::EmptyWord { index: 2 }, Error::EmptyWord { index: 4 }]
+        ));
+        assert!(String::from("indexed_word3").is_inappropriate());
+    }
+
     #[test]
     fn add_words_to_dict() {
-        #[cfg(feature = "wasm")]
+        #[cfg(feature = "wasm-min")]
         let words = vec![
             Vulgar::new(String::from("bad_word1"), Some(JsType::Sexual)),
             Vulgar::new(String::from("bad_word2"), None),
         ];
-        #[cfg(not(feature = "wasm"))]
+        #[cfg(not(feature = "wasm-min"))]
         let words = vec![
             Vulgar::new(String::from("bad_word1"), Some(Type::SEXUAL)),
             Vulgar::new(String::from("bad_word2"), None),
@@ -227,4 +1308,56 @@ mod tests {
 
         assert!(word.is_inappropriate());
     }
+
+    #[test]
+    #[cfg(any(feature = "wasm-min", feature = "napi", feature = "uniffi"))]
+    fn type_round_trip() {
+        for flag in JsType::ALL {
+            assert_eq!(flag.to_string().parse::<JsType>().unwrap(), flag);
+            assert_eq!(JsType::from_type(flag.into()), Some(flag));
+        }
+        assert_eq!(" Sexual".parse::<JsType>().unwrap(), JsType::Sexual);
+        assert!(matches!("lewd".parse::<JsType>(), Err(Error::UnknownType)));
+        assert_eq!(JsType::from_type(Type::SEXUAL & Type::SEVERE), None);
+    }
+
+    /// Build with `wasm-pack build --release` first, then run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn wasm_bundle_size() {
+        const MAX_SIZE: u64 = 2 * 1024 * 1024;
+
+        let size = std::fs::metadata("pkg/little_censor_bg.wasm")
+            .expect("Build the wasm package first")
+            .len();
+        println!("little_censor_bg.wasm: {} KiB", size / 1024);
+
+        assert!(size <= MAX_SIZE, "wasm bundle grew to {size} bytes");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn vulgar_json() {
+        let vulgar: Vulgar =
+            serde_json::from_str(r#"{"word": "noob", "type": "mean|mild"}"#).unwrap();
+
+        assert_eq!(vulgar.word_type, Type::MEAN & Type::MILD);
+        assert_eq!(
+            serde_json::to_string(&vulgar).unwrap(),
+            r#"{"word":"noob","type":"MEAN|MILD"}"#
+        );
+    }
+
+    #[test]
+    fn remove_and_list_words() {
+        add_words(vec![Vulgar::new(String::from("bad_word3"), None)]).unwrap();
+        assert!(list_words().iter().any(|vulgar| vulgar.word == "bad_word3"));
+
+        assert_eq!(
+            remove_words(vec![String::from("bad_word3"), String::from("bad_word3")]),
+            1
+        );
+        assert!(!String::from("bad_word3").is_inappropriate());
+        assert!(list_words().iter().all(|vulgar| vulgar.word != "bad_word3"));
+    }
 }
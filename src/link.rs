@@ -0,0 +1,126 @@
+//! Rate limiting of links per user
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Allows every user to post at most `limit` links within `period`
+///
+/// Attach it to a censor with [`crate::censor::Censor::with_link_guard`], then links above
+/// the limit in messages censored with [`crate::censor::Censor::censor_user`] are masked
+/// and the messages are typed as spam, even when `CensorTypes::Link` isn't requested.
+///
+/// Only allowed links count towards the limit. Requires a clock, so it can't be used on
+/// `wasm32-unknown-unknown`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use little_censor::link::LinkGuard;
+///
+/// let guard = LinkGuard::new(3, Duration::from_secs(10 * 60));
+///
+/// assert_eq!(guard.allow("user", 2), 2);
+/// assert_eq!(guard.allow("user", 2), 1);
+/// assert_eq!(guard.allow("user", 1), 0);
+/// assert_eq!(guard.allow("other", 1), 1);
+/// ```
+#[derive(Debug)]
+pub struct LinkGuard {
+    limit: usize,
+    period: Duration,
+    users: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl LinkGuard {
+    /// Allows at most `limit` links of every user within `period`
+    pub fn new(limit: usize, period: Duration) -> Self {
+        Self {
+            limit,
+            period,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records links posted by the user now, returns how many of them are allowed
+    pub fn allow(&self, user: &str, links: usize) -> usize {
+        self.allow_at(user, links, Instant::now())
+    }
+
+    /// Records links posted by the user at given time, returns how many of them are allowed
+    pub fn allow_at(&self, user: &str, links: usize, at: Instant) -> usize {
+        let mut users = self.users.lock().unwrap_or_else(|e| e.into_inner());
+        let times = users.entry(user.to_owned()).or_default();
+
+        while matches!(times.front(), Some(&first) if at.saturating_duration_since(first) >= self.period)
+        {
+            times.pop_front();
+        }
+
+        let allowed = links.min(self.limit.saturating_sub(times.len()));
+        times.extend(std::iter::repeat_n(at, allowed));
+        allowed
+    }
+
+    /// Number of links the user posted within the period
+    pub fn posted(&self, user: &str) -> usize {
+        let now = Instant::now();
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(user)
+            .map(|times| {
+                times
+                    .iter()
+                    .filter(|&&time| now.saturating_duration_since(time) < self.period)
+                    .count()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Forgets links of the user
+    pub fn forget(&self, user: &str) {
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(user);
+    }
+
+    /// Forgets users who didn't post links within the period
+    pub fn prune(&self) {
+        let now = Instant::now();
+        self.users
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, times| {
+                matches!(times.back(), Some(&last) if now.saturating_duration_since(last) < self.period)
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_resets_after_period() {
+        let guard = LinkGuard::new(2, Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert_eq!(guard.allow_at("user", 3, start), 2);
+        assert_eq!(guard.allow_at("user", 1, start + Duration::from_secs(1)), 0);
+        assert_eq!(guard.allow_at("user", 3, start + Duration::from_secs(6)), 2);
+    }
+
+    #[test]
+    fn forget_user() {
+        let guard = LinkGuard::new(1, Duration::from_secs(5));
+
+        guard.allow("user", 1);
+        assert_eq!(guard.posted("user"), 1);
+        guard.forget("user");
+        guard.prune();
+        assert!(guard.users.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Censoring markdown documents, e.g. user posts and comments
+//!
+//! Only source ranges of visible text are replaced, so markup, whitespace and link
+//! syntax are written exactly as they were. Masks which would be read as markup, like
+//! the `*` of `f***`, are escaped.
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+
+use crate::error::Error;
+use crate::json::JsonOptions;
+
+/// Extensions of CommonMark parsed like GitHub does
+const EXTENSIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_FOOTNOTES)
+    .union(Options::ENABLE_STRIKETHROUGH)
+    .union(Options::ENABLE_TASKLISTS);
+
+/// Which parts of a markdown document are censored and how
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::markdown::{censor_markdown, MarkdownPolicy};
+///
+/// let mut post = String::from("**What the fuck** is `fuck()`?");
+///
+/// censor_markdown(&mut post, &MarkdownPolicy::new()).unwrap();
+///
+/// assert_eq!(post, r"**What the f\*\*\*** is `fuck()`?");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MarkdownPolicy {
+    options: JsonOptions,
+    code: bool,
+    urls: bool,
+}
+
+impl MarkdownPolicy {
+    /// Censors visible text with a default censor, code and link URLs are kept
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Censors text with the censor and rules of the options, key patterns aren't used
+    pub fn with_options(mut self, options: JsonOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether code spans and code blocks are censored too
+    pub fn with_code(mut self, code: bool) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Whether destinations of links and images are censored too
+    pub fn with_urls(mut self, urls: bool) -> Self {
+        self.urls = urls;
+        self
+    }
+}
+
+/// Censored part of the document
+struct Piece {
+    range: Range<usize>,
+    /// Whether masks are escaped, they're literal in code and URLs
+    escape: bool,
+}
+
+/// Censors the document in place, returns number of modified pieces of text
+///
+/// # Errors
+///
+/// Returns the same errors as [`crate::censor::Censor::censor`].
+pub fn censor_markdown(text: &mut String, policy: &MarkdownPolicy) -> Result<usize, Error> {
+    let mut pieces = Vec::new();
+    let mut code_block = false;
+    let mut autolink = false;
+
+    let mut events = Parser::new_ext(text, EXTENSIONS).into_offset_iter();
+    for (event, range) in events.by_ref() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_block = true,
+            Event::End(TagEnd::CodeBlock) => code_block = false,
+            Event::Start(Tag::Link {
+                link_type: LinkType::Autolink | LinkType::Email,
+                ..
+            }) => autolink = true,
+            Event::End(TagEnd::Link) => autolink = false,
+            Event::Start(
+                Tag::Link {
+                    link_type: LinkType::Inline,
+                    dest_url,
+                    ..
+                }
+                | Tag::Image {
+                    link_type: LinkType::Inline,
+                    dest_url,
+                    ..
+                },
+            ) if policy.urls => {
+                let start = text[range.clone()]
+                    .rfind("](")
+                    .map(|index| range.start + index);
+                pieces.extend(destination(text, start, range.end, &dest_url));
+            }
+            Event::Text(_) if code_block || autolink => {
+                let censored = if code_block { policy.code } else { policy.urls };
+                if censored {
+                    pieces.push(Piece {
+                        range,
+                        escape: false,
+                    });
+                }
+            }
+            Event::Text(_) => pieces.push(Piece {
+                range,
+                escape: true,
+            }),
+            Event::Code(_) if policy.code => {
+                let ticks =
+                    text[range.clone()].len() - text[range.clone()].trim_start_matches('`').len();
+                pieces.push(Piece {
+                    range: range.start + ticks..range.end - ticks,
+                    escape: false,
+                });
+            }
+            _ => {}
+        }
+    }
+    if policy.urls {
+        for (_, definition) in events.reference_definitions().iter() {
+            let span = definition.span.clone();
+            let start = text[span.clone()]
+                .find("]:")
+                .map(|index| span.start + index);
+            pieces.extend(destination(text, start, span.end, &definition.dest));
+        }
+    }
+
+    // Replaced from the end, so ranges of earlier pieces stay valid
+    pieces.sort_by_key(|piece| piece.range.start);
+    let mut modified = 0;
+    for piece in pieces.into_iter().rev() {
+        let original = &text[piece.range.clone()];
+        let censored = policy.options.censor(original.to_owned())?;
+        if censored.valid {
+            continue;
+        }
+        modified += 1;
+        let replacement = if piece.escape {
+            escape(original, &censored.censored)
+        } else {
+            censored.censored
+        };
+        text.replace_range(piece.range, &replacement);
+    }
+    Ok(modified)
+}
+
+/// Range of a link destination written after `start` and before `end`
+fn destination(text: &str, start: Option<usize>, end: usize, url: &str) -> Option<Piece> {
+    let start = start?;
+    if url.is_empty() {
+        return None;
+    }
+    let index = text[start..end].find(url)?;
+    Some(Piece {
+        range: start + index..start + index + url.len(),
+        escape: false,
+    })
+}
+
+/// Escapes punctuation the censor put into the text
+fn escape(original: &str, censored: &str) -> String {
+    let mut original = original.chars();
+    let mut escaped = String::with_capacity(censored.len());
+    for c in censored.chars() {
+        if original.next() != Some(c) && c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POST: &str = "# Fuck\n\n> [fuck it](https://fuck.example/) and [more][ref]\n\n```\nfuck\n```\n\n<https://fuck.example/>\n\n[ref]: https://fuck.example/ref\n";
+
+    #[test]
+    fn keeps_code_and_urls() {
+        let mut post = String::from(POST);
+
+        let modified = censor_markdown(&mut post, &MarkdownPolicy::new()).unwrap();
+
+        assert_eq!(modified, 2);
+        assert_eq!(
+            post,
+            "# F\\*\\*\\*\n\n> [f\\*\\*\\* it](https://fuck.example/) and [more][ref]\n\n```\nfuck\n```\n\n<https://fuck.example/>\n\n[ref]: https://fuck.example/ref\n"
+        );
+    }
+
+    #[test]
+    fn censors_code_and_urls_by_policy() {
+        let mut post = String::from(POST);
+        let policy = MarkdownPolicy::new().with_code(true).with_urls(true);
+
+        let modified = censor_markdown(&mut post, &policy).unwrap();
+
+        assert_eq!(modified, 6);
+        assert_eq!(
+            post,
+            "# F\\*\\*\\*\n\n> [f\\*\\*\\* it](https://f***.example/) and [more][ref]\n\n```\nf***\n```\n\n<https://f***.example/>\n\n[ref]: https://f***.example/ref\n"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Measurements of the censoring process
+//!
+//! With the `metrics` feature every censor additionally exports Prometheus counters,
+//! see [`gather`].
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "metrics")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "metrics")]
+use prometheus::{
+    Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+#[cfg(feature = "metrics")]
+use rustrict::Type;
+
+#[cfg(feature = "metrics")]
+use crate::censor::Pass;
+#[cfg(feature = "metrics")]
+use crate::span::SpanKind;
+#[cfg(feature = "metrics")]
+use crate::type_name::{CATEGORIES, SEVERITIES};
+
+/// Time spent in every stage of censoring a single sentence
+///
+/// Recorded only when enabled with [`crate::censor::Censor::with_metrics`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StageTimings {
+    /// Masking links, IPs, emails and custom patterns
+    pub regex: Duration,
+    /// Censoring vulgar words
+    pub profanity: Duration,
+    /// Merging results of previous stages into the final sentence
+    pub rebuild: Duration,
+}
+
+impl StageTimings {
+    /// Sum of all stages
+    pub fn total(&self) -> Duration {
+        self.regex + self.profanity + self.rebuild
+    }
+}
+
+/// Measures consecutive stages, does nothing when disabled
+pub(crate) struct Stopwatch {
+    last: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            // Reading the clock panics on targets without one, so only do it when needed
+            last: enabled.then(Instant::now),
+        }
+    }
+
+    /// Returns time elapsed since previous lap
+    pub(crate) fn lap(&mut self) -> Duration {
+        match self.last.as_mut() {
+            Some(last) => {
+                let now = Instant::now();
+                let elapsed = now - *last;
+                *last = now;
+                elapsed
+            }
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// Prometheus collectors shared by every censor
+#[cfg(feature = "metrics")]
+struct Collectors {
+    registry: Registry,
+    messages: IntCounter,
+    matches: IntCounterVec,
+    types: IntCounterVec,
+    latency: Histogram,
+    cache: IntCounterVec,
+}
+
+#[cfg(feature = "metrics")]
+static COLLECTORS: Lazy<Collectors> = Lazy::new(|| {
+    let registry = Registry::new();
+    let messages = IntCounter::new(
+        "little_censor_messages_total",
+        "Successfully censored messages",
+    )
+    .expect("valid metric");
+    let matches = IntCounterVec::new(
+        Opts::new("little_censor_matches_total", "Masked matches by rule"),
+        &["rule"],
+    )
+    .expect("valid metric");
+    let types = IntCounterVec::new(
+        Opts::new(
+            "little_censor_types_total",
+            "Messages by category and highest severity of vulgar words found",
+        ),
+        &["category", "severity"],
+    )
+    .expect("valid metric");
+    let latency = Histogram::with_opts(HistogramOpts::new(
+        "little_censor_latency_seconds",
+        "Time spent censoring a message",
+    ))
+    .expect("valid metric");
+    let cache = IntCounterVec::new(
+        Opts::new(
+            "little_censor_cache_total",
+            "Custom patterns reused from the cache or compiled",
+        ),
+        &["result"],
+    )
+    .expect("valid metric");
+
+    for collector in [
+        Box::new(messages.clone()) as Box<dyn prometheus::core::Collector>,
+        Box::new(matches.clone()),
+        Box::new(types.clone()),
+        Box::new(latency.clone()),
+        Box::new(cache.clone()),
+    ] {
+        registry.register(collector).expect("unique metric");
+    }
+    Collectors {
+        registry,
+        messages,
+        matches,
+        types,
+        latency,
+        cache,
+    }
+});
+
+/// Accounts for a single censored message
+#[cfg(feature = "metrics")]
+pub(crate) fn observe(pass: &Pass, cache_hit: Option<bool>, latency: Duration) {
+    let collectors = &*COLLECTORS;
+    collectors.messages.inc();
+    for &(rule, matches) in &pass.rule_matches {
+        collectors
+            .matches
+            .with_label_values(&[SpanKind::Rule(rule).name()])
+            .inc_by(matches as u64);
+    }
+    if pass.profanity_matches > 0 {
+        collectors
+            .matches
+            .with_label_values(&[SpanKind::Profanity.name()])
+            .inc_by(pass.profanity_matches as u64);
+    }
+    for (category, flag) in CATEGORIES {
+        let severity = SEVERITIES
+            .iter()
+            .rev()
+            .find(|(_, severity)| pass.typ & flag & *severity != Type::NONE);
+        if let Some((severity, _)) = severity {
+            collectors
+                .types
+                .with_label_values(&[category, severity])
+                .inc();
+        }
+    }
+    collectors.latency.observe(latency.as_secs_f64());
+    match cache_hit {
+        Some(true) => collectors.cache.with_label_values(&["hit"]).inc(),
+        Some(false) => collectors.cache.with_label_values(&["miss"]).inc(),
+        None => {}
+    }
+}
+
+/// Registry with counters of every censor, e.g. to merge them into another registry
+#[cfg(feature = "metrics")]
+pub fn registry() -> &'static Registry {
+    &COLLECTORS.registry
+}
+
+/// Counters of every censor in the Prometheus text format
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::Censor;
+///
+/// Censor::new().censor(String::from("fuck"), Box::new([]), None).unwrap();
+///
+/// assert!(little_censor::metrics::gather().contains("little_censor_messages_total"));
+/// ```
+#[cfg(feature = "metrics")]
+pub fn gather() -> String {
+    let families = COLLECTORS.registry.gather();
+    TextEncoder::new()
+        .encode_to_string(&families)
+        .expect("text encoding doesn't fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_stopwatch() {
+        let mut stopwatch = Stopwatch::new(false);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert_eq!(stopwatch.lap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn total_time() {
+        let timings = StageTimings {
+            regex: Duration::from_millis(1),
+            profanity: Duration::from_millis(2),
+            rebuild: Duration::from_millis(3),
+        };
+
+        assert_eq!(timings.total(), Duration::from_millis(6));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn prometheus_counters() {
+        use crate::censor::{Censor, CensorTypes};
+
+        Censor::new()
+            .censor(
+                String::from("fuck 127.0.0.1"),
+                Box::new([CensorTypes::IP]),
+                None,
+            )
+            .unwrap();
+
+        let text = gather();
+        assert!(
+            text.contains("little_censor_matches_total{rule=\"ip\"}"),
+            "{text}"
+        );
+        assert!(text.contains("little_censor_matches_total{rule=\"profanity\"}"));
+        assert!(text.contains("little_censor_latency_seconds_bucket"));
+    }
+}
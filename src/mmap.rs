@@ -0,0 +1,194 @@
+//! Censoring large plain text files, e.g. multi-gigabyte log exports
+//!
+//! The input is memory mapped and split into chunks ending at line breaks, so no line is
+//! cut in half. Chunks are censored in parallel and written to the output in order, at
+//! most one chunk per thread is kept in memory.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread;
+
+use memmap2::Mmap;
+
+use crate::error::Error;
+use crate::json::JsonOptions;
+
+/// Size of chunks used unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 4 << 20;
+
+/// Censors lines of a file into another one
+///
+/// # Examples
+///
+/// ```no_run
+/// use little_censor::censor::CensorTypes;
+/// use little_censor::json::JsonOptions;
+/// use little_censor::mmap::FileScrubber;
+///
+/// let options = JsonOptions::new().with_types([CensorTypes::Email]);
+/// let redactions = FileScrubber::new(options)
+///     .scrub("export.log".as_ref(), "export.censored.log".as_ref())
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileScrubber {
+    options: JsonOptions,
+    chunk_size: usize,
+    jobs: Option<usize>,
+}
+
+impl FileScrubber {
+    /// Censors lines with the censor and rules of the options, key patterns aren't used
+    pub fn new(options: JsonOptions) -> Self {
+        Self {
+            options,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            jobs: None,
+        }
+    }
+
+    /// Splits the file into chunks of about `bytes` instead of 4 MiB
+    ///
+    /// Chunks are extended to the end of their last line.
+    pub fn with_chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes.max(1);
+        self
+    }
+
+    /// Censors this many chunks at once instead of one per CPU
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs.max(1));
+        self
+    }
+
+    /// Censors every line of `input` into `output`, returns number of redactions
+    ///
+    /// Line endings are kept as they are. The input must not be modified while it's
+    /// censored, it's read through a memory map.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Storage` if the input can't be read or isn't UTF-8, the output
+    /// can't be written or both are the same file, otherwise the same errors as
+    /// [`crate::censor::Censor::censor`].
+    pub fn scrub(&self, input: &Path, output: &Path) -> Result<usize, Error> {
+        let file = File::open(input).map_err(|_| Error::Storage)?;
+        if output
+            .canonicalize()
+            .is_ok_and(|output| input.canonicalize().is_ok_and(|input| input == output))
+        {
+            return Err(Error::Storage);
+        }
+        let mut writer = BufWriter::new(File::create(output).map_err(|_| Error::Storage)?);
+        if file.metadata().map_err(|_| Error::Storage)?.len() == 0 {
+            return Ok(0);
+        }
+        // SAFETY: the file isn't modified while it's mapped, as documented above
+        let map = unsafe { Mmap::map(&file) }.map_err(|_| Error::Storage)?;
+
+        let jobs = self
+            .jobs
+            .or_else(|| thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1);
+        let chunks = chunks(&map, self.chunk_size);
+        let mut redactions = 0;
+        for batch in chunks.chunks(jobs) {
+            let results: Vec<Result<(Vec<u8>, usize), Error>> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|chunk| scope.spawn(|| self.censor_chunk(chunk)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap_or(Err(Error::Storage)))
+                    .collect()
+            });
+            for result in results {
+                let (censored, count) = result?;
+                redactions += count;
+                writer.write_all(&censored).map_err(|_| Error::Storage)?;
+            }
+        }
+        writer.flush().map_err(|_| Error::Storage)?;
+        Ok(redactions)
+    }
+
+    /// Censors lines of a chunk, returns censored bytes and number of redactions
+    fn censor_chunk(&self, chunk: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+        let text = std::str::from_utf8(chunk).map_err(|_| Error::Storage)?;
+        let mut censored = String::with_capacity(text.len());
+        let mut redactions = 0;
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\r', '\n']);
+            let result = self.options.censor(content.to_owned())?;
+            redactions += result.spans.len();
+            censored.push_str(&result.censored);
+            censored.push_str(&line[content.len()..]);
+        }
+        Ok((censored.into_bytes(), redactions))
+    }
+}
+
+/// Splits bytes into chunks of at least `size` bytes ending with a line break, the last
+/// one ends with the bytes
+fn chunks(bytes: &[u8], size: usize) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        let end = match bytes.get(start + size..) {
+            Some(rest) => rest
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map_or(bytes.len(), |index| start + size + index + 1),
+            None => bytes.len(),
+        };
+        chunks.push(&bytes[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::CensorTypes;
+
+    #[test]
+    fn chunks_end_at_lines() {
+        assert_eq!(
+            chunks(b"one\ntwo\nthree", 2),
+            [&b"one\n"[..], b"two\n", b"three"]
+        );
+        assert_eq!(chunks(b"one\ntwo\n", 100), [&b"one\ntwo\n"[..]]);
+    }
+
+    #[test]
+    fn scrubs_in_order() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("little-censor-mmap-{}.log", std::process::id()));
+        let output = dir.join(format!("little-censor-mmap-{}.out", std::process::id()));
+        let lines: String = (0..1000)
+            .map(|index| format!("{index} fuck me@example.net\r\n"))
+            .collect();
+        std::fs::write(&input, &lines).unwrap();
+
+        let redactions = FileScrubber::new(JsonOptions::new().with_types([CensorTypes::Email]))
+            .with_chunk_size(64)
+            .with_jobs(3)
+            .scrub(&input, &output)
+            .unwrap();
+
+        let expected: String = (0..1000)
+            .map(|index| format!("{index} f*** **************\r\n"))
+            .collect();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), expected);
+        assert_eq!(redactions, 2000);
+        assert!(matches!(
+            FileScrubber::new(JsonOptions::new()).scrub(&input, &input),
+            Err(Error::Storage)
+        ));
+        std::fs::remove_file(input).unwrap();
+        std::fs::remove_file(output).unwrap();
+    }
+}
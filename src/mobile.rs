@@ -0,0 +1,147 @@
+//! UniFFI bindings for Kotlin and Swift, described in `src/little_censor.udl`
+
+// Lint is triggered by the generated scaffolding
+#![allow(clippy::empty_line_after_doc_comments)]
+
+use crate::censor::{utf16_offset, CensorTypes, Censored};
+use crate::{JsType, Type, Vulgar};
+
+pub use crate::error::Error as CensorError;
+
+uniffi::include_scaffolding!("little_censor");
+
+/// Additional type of censoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    Link,
+    Ip,
+    Email,
+    Custom,
+}
+
+impl From<Rule> for CensorTypes {
+    fn from(rule: Rule) -> Self {
+        match rule {
+            Rule::Link => Self::Link,
+            Rule::Ip => Self::IP,
+            Rule::Email => Self::Email,
+            Rule::Custom => Self::Custom,
+        }
+    }
+}
+
+/// Vulgar word, `types` are names of flags combined into a single type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    pub word: String,
+    pub types: Option<Vec<String>>,
+}
+
+/// Censored region with UTF-16 offsets, as used by Kotlin strings and Swift `utf16` views
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CensoredSpan {
+    pub start: u32,
+    pub end: u32,
+    pub kind: String,
+}
+
+/// Result of censoring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CensorResult {
+    pub original: String,
+    pub censored: String,
+    pub valid: bool,
+    pub truncated: bool,
+    pub pii_redacted: bool,
+    pub profanity_censored: bool,
+    pub types: Vec<String>,
+    pub spans: Vec<CensoredSpan>,
+    pub verdict: String,
+}
+
+impl From<Censored> for CensorResult {
+    fn from(censored: Censored) -> Self {
+        let spans = censored
+            .spans
+            .iter()
+            .map(|span| CensoredSpan {
+                start: utf16_offset(&censored.original, span.start),
+                end: utf16_offset(&censored.original, span.end),
+                kind: span.kind.name().to_owned(),
+            })
+            .collect();
+        Self {
+            types: JsType::names(censored.typ),
+            verdict: format!("{:?}", censored.verdict).to_lowercase(),
+            pii_redacted: censored.pii_redacted(),
+            profanity_censored: censored.profanity_censored(),
+            spans,
+            original: censored.original,
+            censored: censored.censored,
+            valid: censored.valid,
+            truncated: censored.truncated,
+        }
+    }
+}
+
+fn add_words(words: Vec<Word>) -> Result<(), CensorError> {
+    let mut vulgars = Vec::with_capacity(words.len());
+    for word in words {
+        let word_type = match word.types {
+            Some(types) => JsType::combine_names(&types)?,
+            None => Type::default(),
+        };
+        vulgars.push(Vulgar {
+            word: word.word,
+            word_type,
+            weight: None,
+        });
+    }
+    crate::add_words(vulgars)?;
+    Ok(())
+}
+
+fn add_safe_words(words: Vec<String>) -> Result<(), CensorError> {
+    crate::add_safe_words(words)
+}
+
+fn remove_words(words: Vec<String>) -> u32 {
+    crate::remove_words(words) as u32
+}
+
+fn list_words() -> Vec<Word> {
+    crate::list_words()
+        .into_iter()
+        .map(|vulgar| Word {
+            types: Some(JsType::names(vulgar.word_type)),
+            word: vulgar.word,
+        })
+        .collect()
+}
+
+fn reset_custom_words() {
+    crate::reset_custom_words()
+}
+
+fn censor(
+    sentence: String,
+    rules: Vec<Rule>,
+    arg: Option<String>,
+) -> Result<CensorResult, CensorError> {
+    let types = rules.into_iter().map(CensorTypes::from).collect();
+    Ok(crate::censor::censor(sentence, types, arg)?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn censor_result() {
+        let result = censor(String::from("fuck you"), vec![Rule::Link], None).unwrap();
+
+        assert_eq!(result.censored, "f*** you");
+        assert_eq!(result.spans[0].kind, "profanity");
+        assert!(result.types.contains(&String::from("severe")));
+    }
+}
@@ -0,0 +1,201 @@
+//! Native Node.js addon with the same functions as the wasm bindings
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Task};
+use napi_derive::napi;
+
+use crate::censor::{utf16_offset, CensorTypes, Censored};
+use crate::error::Error;
+use crate::{JsType, Type, Vulgar};
+
+impl From<Error> for napi::Error {
+    fn from(error: Error) -> Self {
+        napi::Error::from_reason(error.to_string())
+    }
+}
+
+/// Vulgar word, `types` are names combined like in `Vulgar.with_types`
+#[napi(object)]
+pub struct Word {
+    pub word: String,
+    #[napi(ts_type = "Array<string> | undefined")]
+    pub types: Option<Vec<String>>,
+}
+
+/// Censored region with UTF-16 offsets
+#[napi(object)]
+pub struct CensoredSpan {
+    pub start: u32,
+    pub end: u32,
+    pub kind: String,
+}
+
+/// Result of censoring
+#[napi(object)]
+pub struct CensorResult {
+    pub original: String,
+    pub censored: String,
+    pub valid: bool,
+    pub truncated: bool,
+    pub pii_redacted: bool,
+    pub profanity_censored: bool,
+    pub types: Vec<String>,
+    pub spans: Vec<CensoredSpan>,
+    pub verdict: String,
+}
+
+impl From<Censored> for CensorResult {
+    fn from(censored: Censored) -> Self {
+        let spans = censored
+            .spans
+            .iter()
+            .map(|span| CensoredSpan {
+                start: utf16_offset(&censored.original, span.start),
+                end: utf16_offset(&censored.original, span.end),
+                kind: span.kind.name().to_owned(),
+            })
+            .collect();
+        Self {
+            types: JsType::names(censored.typ),
+            verdict: format!("{:?}", censored.verdict).to_lowercase(),
+            pii_redacted: censored.pii_redacted(),
+            profanity_censored: censored.profanity_censored(),
+            spans,
+            original: censored.original,
+            censored: censored.censored,
+            valid: censored.valid,
+            truncated: censored.truncated,
+        }
+    }
+}
+
+/// Parses names of additional types of censoring, e.g. `"link"`
+fn rules(names: Vec<String>) -> napi::Result<Box<[CensorTypes]>> {
+    names
+        .iter()
+        .map(|name| match name.as_str() {
+            "link" => Ok(CensorTypes::Link),
+            "ip" => Ok(CensorTypes::IP),
+            "email" => Ok(CensorTypes::Email),
+            "custom" => Ok(CensorTypes::Custom),
+            _ => Err(napi::Error::from_reason(format!(
+                "Censor type {name} doesn't exist"
+            ))),
+        })
+        .collect()
+}
+
+/// Adds words to the word list
+///
+/// # Errors
+///
+/// Throws an Error if any of the words is empty.
+#[napi]
+pub fn add_words(words: Vec<Word>) -> napi::Result<()> {
+    let mut vulgars = Vec::with_capacity(words.len());
+    for word in words {
+        let word_type = match word.types {
+            Some(types) => JsType::combine_names(&types)?,
+            None => Type::default(),
+        };
+        vulgars.push(Vulgar {
+            word: word.word,
+            word_type,
+            weight: None,
+        });
+    }
+    crate::add_words(vulgars)?;
+    Ok(())
+}
+
+/// Marks words as safe, so they are never censored
+///
+/// # Errors
+///
+/// Throws an Error if any of the words is empty.
+#[napi]
+pub fn add_safe_words(words: Vec<String>) -> napi::Result<()> {
+    crate::add_safe_words(words)?;
+    Ok(())
+}
+
+/// Removes words and phrases from the word list, returns number of removed ones
+#[napi]
+pub fn remove_words(words: Vec<String>) -> u32 {
+    crate::remove_words(words) as u32
+}
+
+/// Words and phrases added to the word list
+#[napi]
+pub fn list_words() -> Vec<Word> {
+    crate::list_words()
+        .into_iter()
+        .map(|vulgar| Word {
+            types: Some(JsType::names(vulgar.word_type)),
+            word: vulgar.word,
+        })
+        .collect()
+}
+
+/// Reverts every change made to the word list
+#[napi]
+pub fn reset_custom_words() {
+    crate::reset_custom_words()
+}
+
+/// Censors given string
+///
+/// # Errors
+///
+/// Throws the same errors as the wasm `censor` or if any of the types doesn't exist.
+#[napi]
+pub fn censor(
+    sentence: String,
+    #[napi(ts_arg_type = "Array<'link' | 'ip' | 'email' | 'custom'>")] types: Vec<String>,
+    arg: Option<String>,
+) -> napi::Result<CensorResult> {
+    Ok(crate::censor::censor(sentence, rules(types)?, arg)?.into())
+}
+
+/// Censoring run on the libuv thread pool
+pub struct CensorTask {
+    sentence: Option<String>,
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+}
+
+impl Task for CensorTask {
+    type Output = Censored;
+    type JsValue = CensorResult;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let sentence = self.sentence.take().unwrap_or_default();
+        Ok(crate::censor::censor(
+            sentence,
+            self.types.clone(),
+            self.arg.clone(),
+        )?)
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Censors given string on a worker thread, returns a Promise
+///
+/// # Errors
+///
+/// Throws if any of the types doesn't exist, the Promise rejects with errors of `censor`.
+#[napi(ts_return_type = "Promise<CensorResult>")]
+pub fn censor_async(
+    sentence: String,
+    #[napi(ts_arg_type = "Array<'link' | 'ip' | 'email' | 'custom'>")] types: Vec<String>,
+    arg: Option<String>,
+) -> napi::Result<AsyncTask<CensorTask>> {
+    Ok(AsyncTask::new(CensorTask {
+        sentence: Some(sentence),
+        types: rules(types)?,
+        arg,
+    }))
+}
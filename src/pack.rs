@@ -0,0 +1,251 @@
+//! Word packs, word lists serialized separately from the program
+//!
+//! Packs come in two formats:
+//!
+//! * binary, written by [`write_pack`], starting with `LCWP` and a version byte,
+//!   followed by entries of little endian `u32` type bits, `u32` word length and
+//!   the UTF-8 word; since version 2 the type is followed by a byte which is 1 if a
+//!   `u32` weight comes next and 0 otherwise,
+//! * JSON, an array of `{"word": "...", "type": bits}` objects with an optional
+//!   `"weight"`.
+
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[cfg(feature = "wasm-min")]
+use crate::error::WasmError;
+
+use crate::error::{Error, Position};
+use crate::snapshot::{fingerprint, PackInfo};
+use crate::{add_words, Type, Vulgar};
+
+const MAGIC: &[u8] = b"LCWP";
+const VERSION: u8 = 2;
+/// Version without weights, still read
+const UNWEIGHTED: u8 = 1;
+
+/// Packs loaded with [`load_pack`] since the word list was last reset
+pub(crate) static LOADED: Lazy<Mutex<Vec<PackInfo>>> = Lazy::new(Default::default);
+
+/// Word of a JSON pack
+#[derive(Deserialize)]
+struct PackWord {
+    word: String,
+    #[serde(rename = "type")]
+    typ: Type,
+    #[serde(default)]
+    weight: Option<u32>,
+}
+
+/// Reads words of a pack in either format
+///
+/// # Errors
+///
+/// Returns `Error::Parse` if the pack is malformed, with the line of a JSON pack or the
+/// offset of the broken entry of a binary one.
+pub fn read_pack(bytes: &[u8]) -> Result<Vec<Vulgar>, Error> {
+    match bytes.strip_prefix(MAGIC) {
+        Some([VERSION, ..]) => read_binary(bytes, true),
+        Some([UNWEIGHTED, ..]) => read_binary(bytes, false),
+        Some(_) => Err(Error::Parse {
+            at: Position::Byte(MAGIC.len()),
+        }),
+        None => {
+            let words: Vec<PackWord> = serde_json::from_slice(bytes).map_err(Error::json)?;
+            Ok(words
+                .into_iter()
+                .map(|word| Vulgar {
+                    word: word.word,
+                    word_type: word.typ,
+                    weight: word.weight,
+                })
+                .collect())
+        }
+    }
+}
+
+/// Reads entries of a binary pack, which follow its magic and version
+fn read_binary(pack: &[u8], weighted: bool) -> Result<Vec<Vulgar>, Error> {
+    let mut vulgars = Vec::new();
+    let mut entries = &pack[MAGIC.len() + 1..];
+    while !entries.is_empty() {
+        let (vulgar, rest) = read_entry(entries, weighted).ok_or(Error::Parse {
+            at: Position::Byte(pack.len() - entries.len()),
+        })?;
+        vulgars.push(vulgar);
+        entries = rest;
+    }
+    Ok(vulgars)
+}
+
+/// Entry at the start of `entries` and the entries after it
+fn read_entry(entries: &[u8], weighted: bool) -> Option<(Vulgar, &[u8])> {
+    let (typ, mut rest) = read_u32(entries)?;
+    let mut weight = None;
+    if weighted {
+        let (&flag, after) = rest.split_first()?;
+        rest = after;
+        match flag {
+            0 => {}
+            1 => {
+                let (value, after) = read_u32(rest)?;
+                (weight, rest) = (Some(value), after);
+            }
+            _ => return None,
+        }
+    }
+    let (len, rest) = read_u32(rest)?;
+    let word = rest.get(..len as usize)?;
+    let vulgar = Vulgar {
+        word: String::from_utf8(word.to_vec()).ok()?,
+        word_type: Type::from_bits_truncate(typ),
+        weight,
+    };
+    Some((vulgar, &rest[len as usize..]))
+}
+
+fn read_u32(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let (number, rest) = bytes.split_first_chunk::<4>()?;
+    Some((u32::from_le_bytes(*number), rest))
+}
+
+/// Serializes words into a binary pack
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::pack::{read_pack, write_pack};
+/// use little_censor::Vulgar;
+///
+/// let pack = write_pack(&[Vulgar::new(String::from("noob"), None)]);
+///
+/// assert_eq!(read_pack(&pack).unwrap()[0].word, "noob");
+/// ```
+pub fn write_pack(vulgars: &[Vulgar]) -> Vec<u8> {
+    let mut pack = MAGIC.to_vec();
+    pack.push(VERSION);
+    for vulgar in vulgars {
+        pack.extend_from_slice(&vulgar.word_type.bits().to_le_bytes());
+        match vulgar.weight {
+            Some(weight) => {
+                pack.push(1);
+                pack.extend_from_slice(&weight.to_le_bytes());
+            }
+            None => pack.push(0),
+        }
+        pack.extend_from_slice(&(vulgar.word.len() as u32).to_le_bytes());
+        pack.extend_from_slice(vulgar.word.as_bytes());
+    }
+    pack
+}
+
+/// Adds words of a pack to the word list, returns their number
+///
+/// # Errors
+///
+/// Returns `Error::Parse` if the pack is malformed or `Error::EmptyWord` if
+/// any of its words is empty.
+pub fn load_pack(bytes: &[u8]) -> Result<usize, Error> {
+    let vulgars = read_pack(bytes)?;
+    let count = vulgars.len();
+    add_words(vulgars)?;
+    LOADED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(info(bytes, count));
+    Ok(count)
+}
+
+/// Format and fingerprint of a pack with this many words, for snapshots
+pub(crate) fn info(bytes: &[u8], words: usize) -> PackInfo {
+    PackInfo {
+        format: bytes
+            .strip_prefix(MAGIC)
+            .and_then(|rest| rest.first().copied()),
+        fingerprint: fingerprint(bytes),
+        words,
+    }
+}
+
+/// Adds words of a pack given as `Uint8Array` to the word list, returns their number
+///
+/// # Errors
+///
+/// Throws an Error with code `PARSE` if the pack is malformed or `EMPTY_WORD`
+/// if any of its words is empty.
+///
+/// # Examples
+///
+/// const response = await fetch("https://cdn.example.net/words.lcwp");
+/// load_word_pack(new Uint8Array(await response.arrayBuffer()));
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "load_word_pack")]
+pub fn load_pack_w(bytes: &[u8]) -> Result<usize, WasmError> {
+    Ok(load_pack(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_pack() {
+        let json = format!(
+            r#"[{{"word": "noob", "type": {0}}}, {{"word": "scrub", "type": {0}, "weight": 4}}]"#,
+            Type::MILD.bits()
+        );
+        let vulgars = read_pack(json.as_bytes()).unwrap();
+
+        assert_eq!(vulgars[0].word, "noob");
+        assert_eq!(vulgars[0].word_type, Type::MILD);
+        assert_eq!((vulgars[0].weight, vulgars[1].weight), (None, Some(4)));
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let vulgar = |word: &str, weight| Vulgar {
+            word: String::from(word),
+            word_type: Type::MILD,
+            weight,
+        };
+        let vulgars = [vulgar("noob", None), vulgar("scrub", Some(4))];
+        assert_eq!(read_pack(&write_pack(&vulgars)).unwrap(), vulgars);
+
+        let mut unweighted = MAGIC.to_vec();
+        unweighted.push(UNWEIGHTED);
+        unweighted.extend_from_slice(&Type::MILD.bits().to_le_bytes());
+        unweighted.extend_from_slice(&4_u32.to_le_bytes());
+        unweighted.extend_from_slice(b"noob");
+        assert_eq!(read_pack(&unweighted).unwrap(), [vulgar("noob", None)]);
+        assert_eq!(info(&unweighted, 1).format, Some(UNWEIGHTED));
+    }
+
+    #[test]
+    fn truncated_pack() {
+        let pack = write_pack(&[Vulgar::new(String::from("noob"), None)]);
+
+        assert!(matches!(
+            read_pack(&pack[..pack.len() - 1]),
+            Err(Error::Parse {
+                at: Position::Byte(5)
+            })
+        ));
+        assert!(matches!(
+            read_pack(b"LCWP\x09"),
+            Err(Error::Parse {
+                at: Position::Byte(4)
+            })
+        ));
+        assert!(matches!(
+            read_pack(b"[\n{\"word\": }]"),
+            Err(Error::Parse {
+                at: Position::Line(2)
+            })
+        ));
+    }
+}
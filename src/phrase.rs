@@ -0,0 +1,201 @@
+//! Multi-word phrases and proximity rules
+
+use std::ops::Range;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use rustrict::Type;
+
+use crate::error::Error;
+
+/// Phrases added with [`crate::add_words`]
+pub(crate) static PHRASES: Lazy<RwLock<Vec<Phrase>>> = Lazy::new(Default::default);
+
+/// Sequence of words, each within some distance of the previous one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Phrase {
+    /// Words with the number of other words allowed before them
+    terms: Vec<(String, usize)>,
+    pub(crate) typ: Type,
+}
+
+impl Phrase {
+    /// Parses word of a `Vulgar`, `None` if it is a single word for the trie
+    ///
+    /// Words are separated by whitespace, `~N` between them allows up to `N` other words.
+    pub(crate) fn parse(word: &str, typ: Type) -> Result<Option<Self>, Error> {
+        if !word.trim().contains(char::is_whitespace) {
+            return Ok(None);
+        }
+
+        let mut terms = Vec::new();
+        let mut gap = 0;
+        for part in word.split_whitespace() {
+            if let Some(distance) = part.strip_prefix('~').and_then(|n| n.parse().ok()) {
+                gap = distance;
+                continue;
+            }
+            for (token, _) in tokenize(part) {
+                terms.push((token, gap));
+                gap = 0;
+            }
+        }
+
+        if terms.is_empty() {
+            return Err(Error::EmptyWord { index: 0 });
+        }
+        Ok(Some(Self { terms, typ }))
+    }
+
+    /// Parses word of a `Vulgar` like [`Phrase::parse`], single words are matched as
+    /// phrases of one word
+    pub(crate) fn word(word: &str, typ: Type) -> Result<Self, Error> {
+        if let Some(phrase) = Self::parse(word, typ)? {
+            return Ok(phrase);
+        }
+        let terms: Vec<_> = tokenize(word)
+            .into_iter()
+            .map(|(token, _)| (token, 0))
+            .collect();
+        if terms.is_empty() {
+            return Err(Error::EmptyWord { index: 0 });
+        }
+        Ok(Self { terms, typ })
+    }
+
+    /// Whether both phrases match the same words
+    pub(crate) fn same_terms(&self, other: &Self) -> bool {
+        self.terms == other.terms
+    }
+
+    /// Byte ranges of words matched by every occurrence of the phrase
+    fn find(&self, tokens: &[(String, Range<usize>)]) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        for start in 0..tokens.len() {
+            if tokens[start].0 != self.terms[0].0 {
+                continue;
+            }
+
+            let mut matched = vec![start];
+            let mut last = start;
+            for (word, gap) in &self.terms[1..] {
+                let window = last + 1..(last + 2 + gap).min(tokens.len());
+                match window.into_iter().find(|&i| tokens[i].0 == *word) {
+                    Some(i) => {
+                        matched.push(i);
+                        last = i;
+                    }
+                    None => break,
+                }
+            }
+
+            if matched.len() == self.terms.len() {
+                found.extend(matched.into_iter().map(|i| tokens[i].1.clone()));
+            }
+        }
+        found
+    }
+}
+
+/// Masks words of matched phrases except their first characters
+///
+/// Returns combined type and number of matched phrases.
+pub(crate) fn censor_phrases<'a>(
+    text: &mut String,
+    phrases: impl IntoIterator<Item = &'a Phrase>,
+    threshold: Type,
+    replacement: char,
+) -> (Type, usize) {
+    let tokens = tokenize(text);
+    let mut typ = Type::NONE;
+    let mut matches = 0;
+    let mut masked = Vec::new();
+
+    for phrase in phrases {
+        let found = phrase.find(&tokens);
+        if found.is_empty() {
+            continue;
+        }
+        typ |= phrase.typ;
+        if phrase.typ.is(threshold) {
+            matches += 1;
+            masked.extend(found);
+        }
+    }
+
+    if !masked.is_empty() {
+        *text = text
+            .char_indices()
+            .map(|(i, c)| {
+                if masked.iter().any(|word| word.start < i && i < word.end) {
+                    replacement
+                } else {
+                    c
+                }
+            })
+            .collect();
+    }
+    (typ, matches)
+}
+
+/// Lowercase words with their byte ranges
+pub(crate) fn tokenize(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric() || c == '\'') {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                tokens.push((text[from..i].to_lowercase(), from..i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proximity() {
+        let phrase = Phrase::parse("kill ~3 your self", Type::MEAN)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            phrase.terms,
+            vec![
+                (String::from("kill"), 0),
+                (String::from("your"), 3),
+                (String::from("self"), 0)
+            ]
+        );
+        assert_eq!(Phrase::parse("word", Type::MEAN).unwrap(), None);
+        assert!(matches!(
+            Phrase::parse("~1 ~2", Type::MEAN),
+            Err(Error::EmptyWord { .. })
+        ));
+    }
+
+    #[test]
+    fn mask_phrases() {
+        let phrases = [
+            Phrase::parse("kill yourself", Type::MEAN & Type::SEVERE)
+                .unwrap()
+                .unwrap(),
+            Phrase::parse("steal ~2 password", Type::OFFENSIVE & Type::SEVERE)
+                .unwrap()
+                .unwrap(),
+        ];
+        let mut text = String::from("Kill yourself, I will steal your old password");
+
+        let (typ, matches) = censor_phrases(&mut text, &phrases, Type::INAPPROPRIATE, '*');
+
+        assert_eq!(text, "K*** y*******, I will s**** your old p*******");
+        assert_eq!(matches, 2);
+        assert!(typ.is(Type::MEAN) && typ.is(Type::OFFENSIVE));
+    }
+}
@@ -0,0 +1,271 @@
+//! Escalation of moderation actions for repeated infractions
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use rustrict::Type;
+
+use crate::censor::Censored;
+
+/// Recommended reaction to a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Nothing has to be done
+    Allow,
+    /// User should be warned
+    Warn,
+    /// User should be muted for given time
+    Mute(Duration),
+    /// User should be banned
+    Ban,
+}
+
+/// Thresholds of infractions after which actions are recommended
+///
+/// A message is an infraction when its type matches `per`. With `half_life` set, past
+/// infractions count less the older they are, see [`Escalation::score`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// Infractions after which user is warned
+    pub warn_at: usize,
+    /// Infractions after which user is muted
+    pub mute_at: usize,
+    /// Infractions after which user is banned
+    pub ban_at: usize,
+    /// Type of messages counted as infractions
+    pub per: Type,
+    /// Length of the mute
+    pub mute_for: Duration,
+    /// Time after which an infraction counts as half, `None` disables decay
+    pub half_life: Option<Duration>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            warn_at: 1,
+            mute_at: 3,
+            ban_at: 5,
+            per: Type::INAPPROPRIATE,
+            mute_for: Duration::from_secs(10 * 60),
+            half_life: None,
+        }
+    }
+}
+
+impl Policy {
+    /// Action for a user with given number of infractions
+    pub fn action(&self, infractions: usize) -> Action {
+        if infractions >= self.ban_at {
+            Action::Ban
+        } else if infractions >= self.mute_at {
+            Action::Mute(self.mute_for)
+        } else if infractions >= self.warn_at {
+            Action::Warn
+        } else {
+            Action::Allow
+        }
+    }
+}
+
+/// Counts infractions of every user and recommends actions according to the [`Policy`]
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::policy::{Action, Escalation, Policy};
+/// use little_censor::Type;
+///
+/// let mut escalation = Escalation::new(Policy {
+///     warn_at: 1,
+///     mute_at: 2,
+///     ban_at: 3,
+///     ..Default::default()
+/// });
+/// let slur = Type::OFFENSIVE & Type::SEVERE;
+///
+/// assert_eq!(escalation.record_type("user", slur), Action::Warn);
+/// assert!(matches!(escalation.record_type("user", slur), Action::Mute(_)));
+/// assert_eq!(escalation.record_type("user", Type::SAFE), Action::Allow);
+/// assert_eq!(escalation.record_type("user", slur), Action::Ban);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Escalation {
+    policy: Policy,
+    infractions: HashMap<String, Infractions>,
+}
+
+/// Infractions of a single user
+#[derive(Debug, Default, Clone, Copy)]
+struct Infractions {
+    count: usize,
+    /// Decayed count at the time of the last infraction
+    score: f64,
+    at: Option<Instant>,
+    banned: bool,
+}
+
+impl Escalation {
+    /// Creates engine without any history
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            infractions: HashMap::new(),
+        }
+    }
+
+    /// Policy used by this engine
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Accounts for a censored message of the user, returns recommended action
+    pub fn record(&mut self, user: &str, censored: &Censored) -> Action {
+        self.record_type(user, censored.typ)
+    }
+
+    /// Accounts for a message of given type, returns recommended action
+    ///
+    /// Messages which aren't infractions are allowed unless the user should be banned.
+    /// Once banned, user stays banned until pardoned even if the infractions decay.
+    pub fn record_type(&mut self, user: &str, typ: Type) -> Action {
+        let now = self.policy.half_life.map(|_| Instant::now());
+        self.record_type_at(user, typ, now)
+    }
+
+    /// Accounts for a message of given type sent at given time, see [`Escalation::record_type`]
+    pub fn record_type_at(&mut self, user: &str, typ: Type, at: Option<Instant>) -> Action {
+        if !typ.is(self.policy.per) {
+            return if self.is_banned(user) {
+                Action::Ban
+            } else {
+                Action::Allow
+            };
+        }
+
+        let score = self.score_at(user, at) + 1.0;
+        let action = self.policy.action(score.round() as usize);
+
+        let infractions = self.infractions.entry(user.to_owned()).or_default();
+        infractions.count += 1;
+        infractions.score = score;
+        infractions.at = at;
+        infractions.banned |= action == Action::Ban;
+        if infractions.banned {
+            Action::Ban
+        } else {
+            action
+        }
+    }
+
+    /// Number of infractions of the user, regardless of their age
+    pub fn infractions(&self, user: &str) -> usize {
+        self.infractions
+            .get(user)
+            .map(|infractions| infractions.count)
+            .unwrap_or_default()
+    }
+
+    /// Infractions of the user weighted by their age according to `Policy::half_life`
+    ///
+    /// Equals to the number of infractions when decay is disabled.
+    pub fn score(&self, user: &str) -> f64 {
+        let now = self.policy.half_life.map(|_| Instant::now());
+        self.score_at(user, now)
+    }
+
+    /// Decayed infractions of the user at given time, see [`Escalation::score`]
+    pub fn score_at(&self, user: &str, now: Option<Instant>) -> f64 {
+        let infractions = match self.infractions.get(user) {
+            Some(infractions) => infractions,
+            None => return 0.0,
+        };
+        match (self.policy.half_life, infractions.at, now) {
+            (Some(half_life), Some(at), Some(now)) if !half_life.is_zero() => {
+                let age = now.saturating_duration_since(at);
+                infractions.score * 0.5_f64.powf(age.as_secs_f64() / half_life.as_secs_f64())
+            }
+            _ => infractions.score,
+        }
+    }
+
+    /// Whether the user reached the ban threshold
+    pub fn is_banned(&self, user: &str) -> bool {
+        self.infractions
+            .get(user)
+            .map(|infractions| infractions.banned)
+            .unwrap_or_default()
+    }
+
+    /// Forgets infractions of the user
+    pub fn pardon(&mut self, user: &str) {
+        self.infractions.remove(user);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_by_infractions() {
+        let policy = Policy::default();
+
+        assert_eq!(policy.action(0), Action::Allow);
+        assert_eq!(policy.action(1), Action::Warn);
+        assert_eq!(policy.action(3), Action::Mute(policy.mute_for));
+        assert_eq!(policy.action(10), Action::Ban);
+    }
+
+    #[test]
+    fn banned_user_stays_banned() {
+        let mut escalation = Escalation::new(Policy {
+            ban_at: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(escalation.record_type("user", Type::SEVERE), Action::Ban);
+        assert_eq!(escalation.record_type("user", Type::SAFE), Action::Ban);
+
+        escalation.pardon("user");
+        assert_eq!(escalation.record_type("user", Type::SAFE), Action::Allow);
+    }
+
+    #[test]
+    fn counts_only_matching_type() {
+        let mut escalation = Escalation::new(Policy {
+            per: Type::SPAM,
+            ..Default::default()
+        });
+
+        escalation.record_type("user", Type::PROFANE & Type::SEVERE);
+        assert_eq!(escalation.infractions("user"), 0);
+        escalation.record_type("user", Type::SPAM & Type::MODERATE);
+        assert_eq!(escalation.infractions("user"), 1);
+    }
+
+    #[test]
+    fn infractions_decay() {
+        let mut escalation = Escalation::new(Policy {
+            half_life: Some(Duration::from_secs(60)),
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let slur = Type::OFFENSIVE & Type::SEVERE;
+
+        escalation.record_type_at("user", slur, Some(start));
+        escalation.record_type_at("user", slur, Some(start));
+        assert_eq!(escalation.score_at("user", Some(start)), 2.0);
+        assert_eq!(
+            escalation.score_at("user", Some(start + Duration::from_secs(60))),
+            1.0
+        );
+
+        let later = start + Duration::from_secs(600);
+        assert_eq!(
+            escalation.record_type_at("user", slur, Some(later)),
+            Action::Warn
+        );
+        assert_eq!(escalation.infractions("user"), 3);
+    }
+}
@@ -0,0 +1,263 @@
+//! Named moderation profiles
+
+use std::sync::Arc;
+
+use rustrict::{Trie, Type};
+
+use crate::censor::CensorTypes;
+use crate::error::Error;
+use crate::phrase::Phrase;
+use crate::{words, Vulgar};
+
+/// Rules, threshold and word list used for a group of messages, e.g. a single channel
+///
+/// Register it with [`crate::censor::Censor::with_profile`] and select it with
+/// [`crate::censor::Censor::censor_with_profile`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{Censor, CensorTypes};
+/// use little_censor::profile::Profile;
+/// use little_censor::Type;
+///
+/// let censor = Censor::new()
+///     .with_profile(
+///         "general",
+///         Profile::new()
+///             .with_types([CensorTypes::Link, CensorTypes::Email])
+///             .with_threshold(Type::MILD_OR_HIGHER),
+///     )
+///     .with_profile("nsfw", Profile::new().with_threshold(Type::SEVERE));
+///
+/// let censored = censor
+///     .censor_with_profile("general", String::from("see https://example.net"))
+///     .unwrap();
+///
+/// assert_eq!(censored.censored(), "see *******************");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    pub(crate) types: Vec<CensorTypes>,
+    pub(crate) arg: Option<String>,
+    pub(crate) threshold: Option<Type>,
+    pub(crate) words: Option<WordList>,
+}
+
+impl Profile {
+    /// Creates profile without additional rules which uses the censor's threshold
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets additional types of censoring
+    pub fn with_types(mut self, types: impl IntoIterator<Item = CensorTypes>) -> Self {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    /// Sets argument for `CensorTypes::Custom`
+    pub fn with_arg(mut self, arg: String) -> Self {
+        self.arg = Some(arg);
+        self
+    }
+
+    /// Censors only vulgar words at or above this threshold
+    pub fn with_threshold(mut self, threshold: Type) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Uses default word list extended with given words
+    ///
+    /// Building the word list copies the default one, so profiles should be created
+    /// at startup rather than per message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` if any of the words is empty.
+    pub fn with_words(mut self, vulgars: Vec<Vulgar>) -> Result<Self, Error> {
+        self.words = Some(WordList::new(vulgars)?);
+        Ok(self)
+    }
+}
+
+/// Word list replacing the global one, shared by clones of a censor or profile and
+/// freed with the last of them
+#[derive(Debug, Clone)]
+pub(crate) struct WordList {
+    pub(crate) trie: Arc<Trie>,
+    pub(crate) phrases: Arc<[Phrase]>,
+}
+
+impl WordList {
+    /// Builds default word list extended with `vulgars`
+    pub(crate) fn new(vulgars: impl IntoIterator<Item = Vulgar>) -> Result<Self, Error> {
+        let mut trie = {
+            let _trie = words::read_trie();
+            Trie::default()
+        };
+        let mut phrases = Vec::new();
+        for (index, vulgar) in vulgars.into_iter().enumerate() {
+            if vulgar.word.is_empty() {
+                return Err(Error::EmptyWord { index });
+            }
+            match Phrase::parse(&vulgar.word, vulgar.word_type)
+                .map_err(|_| Error::EmptyWord { index })?
+            {
+                Some(phrase) => phrases.push(phrase),
+                None => trie.set(&vulgar.word, vulgar.word_type),
+            }
+        }
+        Ok(Self {
+            trie: Arc::new(trie),
+            phrases: phrases.into(),
+        })
+    }
+
+    /// Adds a word or phrase in place, the trie is only copied if it's shared
+    #[cfg(feature = "wasm")]
+    pub(crate) fn add(&mut self, word: &str, word_type: Type, phrase: Option<Phrase>) {
+        match phrase {
+            Some(phrase) => {
+                let mut phrases: Vec<_> = self.phrases.iter().cloned().collect();
+                phrases.retain(|other| !other.same_terms(&phrase));
+                phrases.push(phrase);
+                self.phrases = phrases.into();
+            }
+            None => Arc::make_mut(&mut self.trie).set(word, word_type),
+        }
+    }
+
+    /// Removes a word or phrase in place, including the default ones
+    #[cfg(feature = "wasm")]
+    pub(crate) fn remove(&mut self, word: &str) {
+        match Phrase::parse(word, Type::NONE) {
+            Ok(Some(phrase)) => {
+                let mut phrases: Vec<_> = self.phrases.iter().cloned().collect();
+                phrases.retain(|other| !other.same_terms(&phrase));
+                self.phrases = phrases.into();
+            }
+            _ => {
+                Arc::make_mut(&mut self.trie).remove(word);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::Censor;
+
+    #[test]
+    fn profiles_have_own_words() {
+        let censor = Censor::new()
+            .with_profile(
+                "gaming",
+                Profile::new()
+                    .with_words(vec![Vulgar::new(String::from("noob"), None)])
+                    .unwrap(),
+            )
+            .with_profile("general", Profile::new());
+
+        let gaming = censor
+            .censor_with_profile("gaming", String::from("you noob"))
+            .unwrap();
+        let general = censor
+            .censor_with_profile("general", String::from("you noob"))
+            .unwrap();
+
+        assert_eq!(gaming.censored, "you n***");
+        assert_eq!(general.censored, "you noob");
+    }
+
+    #[test]
+    fn profile_phrases() {
+        let censor = Censor::new().with_profile(
+            "kids",
+            Profile::new()
+                .with_words(vec![Vulgar::near("shut", "up", 1, None)])
+                .unwrap(),
+        );
+
+        let censored = censor
+            .censor_with_profile("kids", String::from("shut your mouth, shut it up"))
+            .unwrap();
+
+        assert_eq!(censored.censored, "shut your mouth, s*** it u*");
+    }
+
+    #[test]
+    fn profile_rules() {
+        let censor = Censor::new().with_profile(
+            "support",
+            Profile::new()
+                .with_types([CensorTypes::Custom])
+                .with_arg(String::from(r"#[0-9]+")),
+        );
+
+        let censored = censor
+            .censor_with_profile("support", String::from("order #1234"))
+            .unwrap();
+
+        assert_eq!(censored.censored, "order *****");
+    }
+
+    #[test]
+    fn unknown_profile() {
+        let censored = Censor::new().censor_with_profile("missing", String::from("text"));
+
+        assert!(matches!(censored, Err(Error::UnknownProfile)));
+    }
+
+    #[test]
+    fn empty_profile_word() {
+        let profile = Profile::new().with_words(vec![Vulgar::new(String::new(), None)]);
+
+        assert!(matches!(profile, Err(Error::EmptyWord { index: 0 })));
+    }
+
+    #[test]
+    fn word_lists_are_freed() {
+        let profile = Profile::new()
+            .with_words(vec![Vulgar::new(String::from("freed"), None)])
+            .unwrap();
+        let trie = Arc::downgrade(&profile.words.as_ref().unwrap().trie);
+        let censor = Censor::new().with_profile("freed", profile);
+        let clone = censor.clone();
+
+        drop(censor);
+        assert!(trie.upgrade().is_some());
+        drop(clone);
+        assert!(trie.upgrade().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "wasm")]
+    fn word_lists_are_edited_in_place() {
+        let mut censor = Censor::new();
+        let list = censor.word_list_mut();
+        let trie = Arc::as_ptr(&list.trie);
+        list.add("inplace", Type::INAPPROPRIATE, None);
+        list.add(
+            "in place",
+            Type::INAPPROPRIATE,
+            Phrase::parse("in place", Type::INAPPROPRIATE).unwrap(),
+        );
+        assert_eq!(Arc::as_ptr(&censor.word_list_mut().trie), trie);
+
+        let censored = censor
+            .censor(String::from("inplace in place"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "i****** i* p****");
+
+        let list = censor.word_list_mut();
+        list.remove("inplace");
+        list.remove("in place");
+        let censored = censor
+            .censor(String::from("inplace in place"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "inplace in place");
+    }
+}
@@ -0,0 +1,361 @@
+//! Word list shared by every instance through Redis
+//!
+//! Words are kept in a hash mapping each word to its type written like `"SEXUAL|SEVERE"`,
+//! followed by `;` and the weight for weighted words, e.g. `"SEVERE;5"`. Changes are
+//! published on a channel as JSON, `{"word": "...", "type": "...", "weight": 5}` for added
+//! words, without `weight` if they have none, and `{"word": "...", "type": null}` for
+//! removed ones, so every subscribed instance applies them to its own word list.
+
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use ::redis::{Client, Commands, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::store::{blocking, StoreFuture, WordStore};
+use crate::{add_words, remove_words, type_name, Type, Vulgar};
+
+/// Hash and channel used unless configured otherwise
+pub const DEFAULT_KEY: &str = "little-censor:words";
+
+/// Delay before reconnecting after the subscription was lost
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Change of the word list published to other instances
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Update {
+    word: String,
+    /// Type name of an added word, `None` for a removed one
+    #[serde(rename = "type")]
+    typ: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight: Option<u32>,
+}
+
+impl Update {
+    fn added(vulgar: &Vulgar) -> Self {
+        Self {
+            word: vulgar.word.clone(),
+            typ: Some(type_name::format(vulgar.word_type)),
+            weight: vulgar.weight,
+        }
+    }
+
+    fn removed(word: &str) -> Self {
+        Self {
+            word: word.to_owned(),
+            typ: None,
+            weight: None,
+        }
+    }
+}
+
+/// Value of a word in the hash, its type name and weight
+fn encode(typ: &str, weight: Option<u32>) -> String {
+    match weight {
+        Some(weight) => format!("{typ};{weight}"),
+        None => typ.to_owned(),
+    }
+}
+
+/// Type and weight of a word from its value in the hash, plain type names have no weight
+fn decode(value: &str) -> Result<(Type, Option<u32>), Error> {
+    let (typ, weight) = match value.split_once(';') {
+        Some((typ, weight)) => (typ, Some(weight.parse().map_err(|_| Error::Storage)?)),
+        None => (value, None),
+    };
+    Ok((type_name::parse(typ).map_err(|_| Error::Storage)?, weight))
+}
+
+/// Connection to the shared word list
+///
+/// # Examples
+///
+/// ```no_run
+/// use little_censor::redis_store::RedisWords;
+/// use little_censor::{Type, Vulgar};
+///
+/// let words = RedisWords::new("redis://127.0.0.1/").unwrap();
+/// words.load().unwrap();
+/// words.subscribe();
+///
+/// // Banned on every instance within moments
+/// words.ban(Vulgar::new(String::from("noob"), Some(Type::MEAN & Type::MILD))).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RedisWords {
+    client: Client,
+    key: String,
+    channel: String,
+}
+
+impl RedisWords {
+    /// Connects to the Redis server at `url`, e.g. `redis://127.0.0.1/`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Storage` if the URL is malformed.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        Ok(Self {
+            client: Client::open(url).map_err(|_| Error::Storage)?,
+            key: DEFAULT_KEY.to_owned(),
+            channel: DEFAULT_KEY.to_owned(),
+        })
+    }
+
+    /// Keeps words in the hash at `key` instead of `little-censor:words`
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Publishes changes on `channel` instead of `little-censor:words`
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = channel.into();
+        self
+    }
+
+    fn connection(&self) -> Result<Connection, Error> {
+        self.client.get_connection().map_err(|_| Error::Storage)
+    }
+
+    /// Every stored word
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Storage` if Redis can't be reached or a stored type is malformed.
+    pub fn words(&self) -> Result<Vec<Vulgar>, Error> {
+        let stored: Vec<(String, String)> = self
+            .connection()?
+            .hgetall(&self.key)
+            .map_err(|_| Error::Storage)?;
+        stored
+            .into_iter()
+            .map(|(word, value)| {
+                let (word_type, weight) = decode(&value)?;
+                Ok(Vulgar {
+                    word,
+                    word_type,
+                    weight,
+                })
+            })
+            .collect()
+    }
+
+    /// Adds every stored word to the word list, returns their number
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Storage` if Redis can't be reached or a stored type is malformed.
+    pub fn load(&self) -> Result<usize, Error> {
+        let vulgars = self.words()?;
+        let count = vulgars.len();
+        add_words(vulgars)?;
+        Ok(count)
+    }
+
+    /// Stores the word and publishes it to every instance, including this one
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` if the word is empty or `Error::Storage` if Redis
+    /// can't be reached.
+    pub fn ban(&self, vulgar: Vulgar) -> Result<(), Error> {
+        self.publish(&[Update::added(&vulgar)])?;
+        add_words(vec![vulgar])?;
+        Ok(())
+    }
+
+    /// Removes the word from storage and word lists of every instance
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Storage` if Redis can't be reached.
+    pub fn unban(&self, word: &str) -> Result<(), Error> {
+        self.publish(&[Update::removed(word)])?;
+        remove_words(vec![word.to_owned()]);
+        Ok(())
+    }
+
+    /// Stores added words and removes removed ones, then publishes the changes
+    ///
+    /// Returns number of removed words which were stored before.
+    fn publish(&self, updates: &[Update]) -> Result<usize, Error> {
+        if let Some(index) = updates.iter().position(|update| update.word.is_empty()) {
+            return Err(Error::EmptyWord { index });
+        }
+        let mut pipe = ::redis::pipe();
+        pipe.atomic();
+        for update in updates {
+            match &update.typ {
+                Some(typ) => pipe
+                    .hset(&self.key, &update.word, encode(typ, update.weight))
+                    .ignore(),
+                None => pipe.hdel(&self.key, &update.word),
+            };
+            pipe.publish(
+                &self.channel,
+                serde_json::to_string(update).expect("serializable"),
+            )
+            .ignore();
+        }
+        let removed: Vec<usize> = pipe
+            .query(&mut self.connection()?)
+            .map_err(|_| Error::Storage)?;
+        Ok(removed.into_iter().sum())
+    }
+
+    /// Applies changes published by other instances on a background thread
+    ///
+    /// After a lost connection the thread reconnects and loads the whole word list
+    /// again, so changes published in the meantime aren't missed.
+    pub fn subscribe(&self) -> JoinHandle<()> {
+        let words = self.clone();
+        self.listen_in_background(move |payload| match payload {
+            Some(payload) => apply(payload),
+            None => words.load().map(drop),
+        })
+    }
+
+    /// Calls `changed` on a background thread whenever other instances publish a change,
+    /// instead of applying it to the global word list
+    ///
+    /// Meant for programs with their own word lists built from [`RedisWords::words`].
+    /// `changed` is also called after a lost connection was restored.
+    pub fn subscribe_with(&self, changed: impl Fn() + Send + 'static) -> JoinHandle<()> {
+        self.listen_in_background(move |_| {
+            changed();
+            Ok(())
+        })
+    }
+
+    /// Listens on a background thread, reconnecting whenever the connection is lost
+    fn listen_in_background(
+        &self,
+        handle: impl Fn(Option<&str>) -> Result<(), Error> + Send + 'static,
+    ) -> JoinHandle<()> {
+        let words = self.clone();
+        thread::spawn(move || {
+            let mut reconnected = false;
+            loop {
+                if let Err(error) = words.listen(reconnected, &handle) {
+                    log::warn!("Word list subscription lost: {error}");
+                }
+                reconnected = true;
+                thread::sleep(RECONNECT_DELAY);
+            }
+        })
+    }
+
+    /// Hands published changes to `handle` until the connection fails, `None` stands for
+    /// every change missed while reconnecting
+    fn listen(
+        &self,
+        reconnected: bool,
+        handle: &impl Fn(Option<&str>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut connection = self.connection()?;
+        let mut pubsub = connection.as_pubsub();
+        pubsub
+            .subscribe(&self.channel)
+            .map_err(|_| Error::Storage)?;
+        if reconnected {
+            handle(None)?;
+        }
+        loop {
+            let message = pubsub.get_message().map_err(|_| Error::Storage)?;
+            let payload: String = message.get_payload().map_err(|_| Error::Storage)?;
+            if let Err(error) = handle(Some(&payload)) {
+                log::warn!("Ignoring word list update {payload}: {error}");
+            }
+        }
+    }
+}
+
+impl WordStore for RedisWords {
+    fn load(&self) -> StoreFuture<'_, Vec<Vulgar>> {
+        let store = self.clone();
+        Box::pin(blocking(move || store.words()))
+    }
+
+    /// Stores and publishes words, subscribed instances add them to their word lists
+    fn save(&self, vulgars: Vec<Vulgar>) -> StoreFuture<'_, ()> {
+        let store = self.clone();
+        Box::pin(blocking(move || {
+            let updates: Vec<Update> = vulgars.iter().map(Update::added).collect();
+            store.publish(&updates).map(drop)
+        }))
+    }
+
+    fn delete(&self, words: Vec<String>) -> StoreFuture<'_, usize> {
+        let store = self.clone();
+        Box::pin(blocking(move || {
+            let updates: Vec<Update> = words.iter().map(|word| Update::removed(word)).collect();
+            store.publish(&updates)
+        }))
+    }
+}
+
+/// Applies a published change to the word list, the trie is locked against censoring threads
+fn apply(payload: &str) -> Result<(), Error> {
+    let update: Update = serde_json::from_str(payload).map_err(|_| Error::Storage)?;
+    match update.typ {
+        Some(typ) => {
+            add_words(vec![Vulgar {
+                word: update.word,
+                word_type: type_name::parse(&typ)?,
+                weight: update.weight,
+            }])?;
+            Ok(())
+        }
+        None => {
+            remove_words(vec![update.word]);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::Censor;
+
+    #[test]
+    fn applies_updates() {
+        let censor = |text: &str| {
+            Censor::new()
+                .censor(text.to_owned(), Box::new([]), None)
+                .unwrap()
+                .censored
+        };
+
+        apply(r#"{"word": "redisbanned", "type": "PROFANE|SEVERE"}"#).unwrap();
+        assert_eq!(censor("redisbanned"), "r**********");
+
+        apply(r#"{"word": "redisbanned", "type": null}"#).unwrap();
+        assert_eq!(censor("redisbanned"), "redisbanned");
+
+        assert!(matches!(apply("redisbanned"), Err(Error::Storage)));
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let vulgar = Vulgar::new(String::from("redisweighted"), Some(Type::SEVERE)).with_weight(5);
+        let update = Update::added(&vulgar);
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"word":"redisweighted","type":"SEVERE","weight":5}"#
+        );
+        assert_eq!(
+            decode(&encode(update.typ.as_deref().unwrap(), update.weight)).unwrap(),
+            (Type::SEVERE, Some(5))
+        );
+        assert_eq!(decode("SEVERE").unwrap(), (Type::SEVERE, None));
+        assert!(matches!(decode("SEVERE;heavy"), Err(Error::Storage)));
+
+        apply(&serde_json::to_string(&update).unwrap()).unwrap();
+        assert_eq!(crate::words::weight("redisweighted"), Some(5));
+        apply(&serde_json::to_string(&Update::removed("redisweighted")).unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! Machine readable moderation reports
+
+use rustrict::Type;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::Decision;
+use crate::censor::{CensorTypes, Censored};
+use crate::span::Span;
+
+/// Decision about the message, ordered from the most lenient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// Message can be delivered as is
+    Pass,
+    /// Message can be delivered after censoring
+    Censor,
+    /// Message should be checked by a moderator before delivering
+    Review,
+    /// Message shouldn't be delivered
+    Block,
+}
+
+/// Verdicts for messages of given types
+///
+/// Messages matching none of the types pass or are censored depending on whether
+/// anything was masked. When several types match the strictest verdict wins.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{Censor, CensorTypes};
+/// use little_censor::report::{Thresholds, Verdict};
+/// use little_censor::Type;
+///
+/// let censor = Censor::new().with_thresholds(
+///     Thresholds::new()
+///         .with(Type::SEVERE, Verdict::Block)
+///         .with(Type::SEXUAL & Type::MODERATE_OR_HIGHER, Verdict::Review),
+/// );
+///
+/// let censored = censor.censor(String::from("hello"), Box::new([]), None).unwrap();
+/// assert_eq!(censored.verdict(), Verdict::Pass);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Thresholds {
+    pub(crate) rules: Vec<(Type, Verdict)>,
+    pub(crate) categories: Vec<(String, Verdict)>,
+}
+
+impl Thresholds {
+    /// Creates thresholds without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives the verdict to messages of this type
+    pub fn with(mut self, typ: Type, verdict: Verdict) -> Self {
+        self.rules.push((typ, verdict));
+        self
+    }
+
+    /// Gives the verdict to messages of this custom category, see
+    /// [`crate::category::CategoryRegistry`]
+    pub fn with_category(mut self, name: impl Into<String>, verdict: Verdict) -> Self {
+        self.categories.push((name.into(), verdict));
+        self
+    }
+
+    /// Strictest verdict of the rules matching the type, `Verdict::Pass` if none match
+    pub fn verdict(&self, typ: Type) -> Verdict {
+        if typ.is(Type::SAFE) {
+            return Verdict::Pass;
+        }
+        self.rules
+            .iter()
+            .filter(|(threshold, _)| typ.is(*threshold))
+            .map(|&(_, verdict)| verdict)
+            .max()
+            .unwrap_or(Verdict::Pass)
+    }
+
+    /// Verdict for the censored message
+    ///
+    /// Moderator overrides and blocks of the user's context take precedence over the rules.
+    pub(crate) fn judge(&self, censored: &Censored) -> Verdict {
+        if censored.overridden == Some(Decision::Allow) {
+            Verdict::Pass
+        } else if censored.blocked.is_some() || censored.overridden == Some(Decision::Deny) {
+            Verdict::Block
+        } else {
+            let verdict = self
+                .categories
+                .iter()
+                .filter(|(name, _)| censored.categories.contains(name))
+                .map(|&(_, verdict)| verdict)
+                .fold(self.verdict(censored.typ), Verdict::max);
+            if censored.valid {
+                verdict
+            } else {
+                verdict.max(Verdict::Censor)
+            }
+        }
+    }
+}
+
+/// Highest severity of vulgar words in the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    None,
+    Mild,
+    Moderate,
+    Severe,
+}
+
+impl From<Type> for Severity {
+    fn from(typ: Type) -> Self {
+        if typ.is(Type::SAFE) {
+            Self::None
+        } else if typ.is(Type::SEVERE) {
+            Self::Severe
+        } else if typ.is(Type::MODERATE) {
+            Self::Moderate
+        } else if typ.is(Type::MILD) {
+            Self::Mild
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Why the message was flagged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Profane,
+    Offensive,
+    Sexual,
+    Mean,
+    Evasive,
+    Spam,
+    /// Blocked by the user's context
+    Blocked,
+}
+
+impl Reason {
+    const CATEGORIES: [(Type, Reason); 6] = [
+        (Type::PROFANE, Reason::Profane),
+        (Type::OFFENSIVE, Reason::Offensive),
+        (Type::SEXUAL, Reason::Sexual),
+        (Type::MEAN, Reason::Mean),
+        (Type::EVASIVE, Reason::Evasive),
+        (Type::SPAM, Reason::Spam),
+    ];
+
+    /// Reasons of flagging the censored message
+    fn of(censored: &Censored) -> Vec<Reason> {
+        let mut reasons: Vec<Reason> = Self::CATEGORIES
+            .iter()
+            .filter(|(typ, _)| !censored.typ.is(Type::SAFE) && censored.typ.is(*typ))
+            .map(|&(_, reason)| reason)
+            .collect();
+        if censored.blocked.is_some() {
+            reasons.push(Reason::Blocked);
+        }
+        reasons
+    }
+}
+
+/// Analysis of a message without its text, see [`crate::censor::Censor::inspect`]
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{Censor, CensorTypes};
+/// use little_censor::report::Verdict;
+/// use little_censor::span::SpanKind;
+///
+/// let report = Censor::new()
+///     .inspect("fuck 127.0.0.1", &[CensorTypes::IP], None)
+///     .unwrap();
+///
+/// assert!(!report.valid);
+/// assert_eq!(report.verdict, Verdict::Censor);
+/// assert_eq!(report.spans[1].kind, SpanKind::Rule(CensorTypes::IP));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    /// Whether nothing would be censored, see [`Censored::valid`]
+    pub valid: bool,
+    /// Whether analysis stopped early because of the budget
+    pub truncated: bool,
+    pub verdict: Verdict,
+    pub severity: Severity,
+    /// Categories of vulgar words and other reasons of flagging the message
+    pub reasons: Vec<Reason>,
+    /// Regex rules which matched the message
+    pub rules: Vec<CensorTypes>,
+    /// Regions which would be censored, in bytes of the message
+    pub spans: Vec<Span>,
+    /// Custom categories of the censored regions
+    pub categories: Vec<String>,
+    /// Sum of weights of censored vulgar words, see [`Censored::score`]
+    pub score: u32,
+}
+
+impl From<&Censored> for Report {
+    fn from(censored: &Censored) -> Self {
+        Self {
+            valid: censored.valid,
+            truncated: censored.truncated,
+            verdict: censored.verdict,
+            severity: censored.typ.into(),
+            reasons: Reason::of(censored),
+            rules: censored.rules.clone(),
+            spans: censored.spans.clone(),
+            categories: censored.categories.clone(),
+            score: censored.score,
+        }
+    }
+}
+
+/// Report about a single censored message, suitable for storing in an audit log
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{censor, CensorTypes};
+/// use little_censor::report::Verdict;
+///
+/// let censored = censor(String::from("ip 127.0.0.1"), Box::new([CensorTypes::IP]), None).unwrap();
+/// let report = censored.report().with_user("1234").with_channel("general");
+///
+/// assert_eq!(report.verdict, Verdict::Censor);
+/// assert_eq!(report.rules, vec![CensorTypes::IP]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModerationReport {
+    /// Author of the message
+    pub user_id: Option<String>,
+    /// Channel the message was sent to
+    pub channel_id: Option<String>,
+    pub verdict: Verdict,
+    pub severity: Severity,
+    /// Categories of vulgar words and other reasons of flagging the message
+    pub reasons: Vec<Reason>,
+    /// Regex rules which matched the message
+    pub rules: Vec<CensorTypes>,
+    /// Custom categories of the censored regions
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Human readable reason of blocking the message
+    pub blocked: Option<String>,
+    pub original: String,
+    /// Censored sentence, in shadow mode the one which would have been delivered
+    pub censored: String,
+}
+
+impl ModerationReport {
+    /// Creates report about the censored message
+    pub fn new(censored: &Censored) -> Self {
+        Self {
+            user_id: None,
+            channel_id: None,
+            verdict: censored.verdict,
+            severity: censored.typ.into(),
+            reasons: Reason::of(censored),
+            rules: censored.rules.clone(),
+            categories: censored.categories.clone(),
+            blocked: censored
+                .blocked
+                .as_ref()
+                .map(|reason| reason.contextual_string()),
+            original: censored.original.clone(),
+            censored: censored
+                .shadow
+                .clone()
+                .unwrap_or_else(|| censored.censored.clone()),
+        }
+    }
+
+    /// Sets id of the message author
+    pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Sets id of the channel the message was sent to
+    pub fn with_channel(mut self, channel_id: impl Into<String>) -> Self {
+        self.channel_id = Some(channel_id.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::censor;
+
+    #[test]
+    fn clean_message() {
+        let censored = censor(String::from("hello"), Box::new([]), None).unwrap();
+        let report = ModerationReport::new(&censored);
+
+        assert_eq!(report.verdict, Verdict::Pass);
+        assert_eq!(report.severity, Severity::None);
+        assert!(report.reasons.is_empty());
+    }
+
+    #[test]
+    fn vulgar_message() {
+        let censored = censor(String::from("fuck"), Box::new([]), None).unwrap();
+        let report = censored.report();
+
+        assert_eq!(report.verdict, Verdict::Censor);
+        assert!(report.reasons.contains(&Reason::Profane));
+        assert!(report.severity >= Severity::Moderate);
+    }
+
+    #[test]
+    fn strictest_threshold_wins() {
+        let thresholds = Thresholds::new()
+            .with(Type::PROFANE & Type::MODERATE_OR_HIGHER, Verdict::Review)
+            .with(Type::PROFANE & Type::SEVERE, Verdict::Block);
+
+        assert_eq!(
+            thresholds.verdict(Type::PROFANE & Type::SEVERE),
+            Verdict::Block
+        );
+        assert_eq!(
+            thresholds.verdict(Type::PROFANE & Type::MODERATE),
+            Verdict::Review
+        );
+        assert_eq!(thresholds.verdict(Type::SAFE), Verdict::Pass);
+    }
+
+    #[test]
+    fn serialization() {
+        let censored = censor(String::from("fuck"), Box::new([]), None).unwrap();
+        let report = censored.report().with_user("1");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["verdict"], "censor");
+        assert_eq!(json["user_id"], "1");
+        assert_eq!(
+            serde_json::from_value::<ModerationReport>(json).unwrap(),
+            report
+        );
+    }
+}
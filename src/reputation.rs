@@ -0,0 +1,154 @@
+//! Reputation of users based on their flagged messages
+
+use std::collections::HashMap;
+
+use rustrict::Type;
+use serde::{Deserialize, Serialize};
+
+use crate::censor::Censored;
+
+/// Penalty points added for messages of every severity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weights {
+    /// Added for `Type::MILD` messages
+    pub mild: f64,
+    /// Added for `Type::MODERATE` messages
+    pub moderate: f64,
+    /// Added for `Type::SEVERE` messages
+    pub severe: f64,
+    /// Fraction of the score forgiven for every clean message
+    pub recovery: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            mild: 1.0,
+            moderate: 3.0,
+            severe: 10.0,
+            recovery: 0.05,
+        }
+    }
+}
+
+/// Tracks penalty score of every user, higher score means worse behaviour
+///
+/// Flagged messages increase the score according to their severity, clean messages
+/// slowly decrease it. State can be saved and restored with serde.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::reputation::Reputation;
+/// use little_censor::Type;
+///
+/// let mut reputation = Reputation::new();
+/// reputation.record("user", Type::PROFANE & Type::SEVERE);
+///
+/// assert_eq!(reputation.score("user"), 10.0);
+/// assert_eq!(reputation.score("stranger"), 0.0);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reputation {
+    weights: Weights,
+    scores: HashMap<String, f64>,
+}
+
+impl Reputation {
+    /// Creates tracker with default weights
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates tracker with custom weights
+    pub fn with_weights(weights: Weights) -> Self {
+        Self {
+            weights,
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Accounts for a message of given type, returns new score of the user
+    pub fn record(&mut self, user: &str, typ: Type) -> f64 {
+        let penalty = if typ.is(Type::SAFE) {
+            None
+        } else if typ.is(Type::SEVERE) {
+            Some(self.weights.severe)
+        } else if typ.is(Type::MODERATE) {
+            Some(self.weights.moderate)
+        } else if typ.is(Type::MILD) {
+            Some(self.weights.mild)
+        } else {
+            None
+        };
+
+        let score = self.scores.entry(user.to_owned()).or_default();
+        match penalty {
+            Some(penalty) => *score += penalty,
+            None => *score *= 1.0 - self.weights.recovery,
+        }
+        *score
+    }
+
+    /// Accounts for a censored message, returns new score of the user
+    pub fn record_censored(&mut self, user: &str, censored: &Censored) -> f64 {
+        self.record(user, censored.typ)
+    }
+
+    /// Current score of the user, `0.0` for unknown users
+    pub fn score(&self, user: &str) -> f64 {
+        self.scores.get(user).copied().unwrap_or_default()
+    }
+
+    /// Users with their scores
+    pub fn scores(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.scores
+            .iter()
+            .map(|(user, score)| (user.as_str(), *score))
+    }
+
+    /// Forgets everything about the user
+    pub fn forget(&mut self, user: &str) {
+        self.scores.remove(user);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_weights() {
+        let mut reputation = Reputation::new();
+
+        assert_eq!(reputation.record("a", Type::MEAN & Type::MILD), 1.0);
+        assert_eq!(reputation.record("a", Type::SEXUAL & Type::MODERATE), 4.0);
+        assert_eq!(reputation.record("b", Type::OFFENSIVE & Type::SEVERE), 10.0);
+    }
+
+    #[test]
+    fn clean_messages_recover() {
+        let mut reputation = Reputation::with_weights(Weights {
+            recovery: 0.5,
+            ..Default::default()
+        });
+        reputation.record("user", Type::PROFANE & Type::SEVERE);
+
+        assert_eq!(reputation.record("user", Type::SAFE), 5.0);
+        assert_eq!(reputation.record("user", Type::NONE), 2.5);
+
+        reputation.forget("user");
+        assert_eq!(reputation.scores().count(), 0);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let mut reputation = Reputation::new();
+        reputation.record("user", Type::PROFANE & Type::MODERATE);
+
+        let json = serde_json::to_string(&reputation).unwrap();
+        let restored: Reputation = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, reputation);
+    }
+}
@@ -0,0 +1,104 @@
+//! Helpers censoring fields while they are deserialized
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::Deserialize;
+//! use little_censor::censor::{Censor, CensorTypes};
+//! use little_censor::serde_helpers;
+//!
+//! #[derive(Deserialize)]
+//! struct Comment {
+//!     #[serde(deserialize_with = "serde_helpers::censored_string")]
+//!     text: String,
+//! }
+//!
+//! serde_helpers::set_censor(Censor::new(), vec![CensorTypes::Link], None);
+//! let comment: Comment = serde_json::from_str(r#"{"text": "fuck https://example.net"}"#).unwrap();
+//!
+//! assert_eq!(comment.text, "f*** *******************");
+//! ```
+
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::censor::{Censor, CensorTypes};
+
+/// Censor used by the helpers together with its rules
+struct Configured {
+    censor: Censor,
+    types: Vec<CensorTypes>,
+    arg: Option<String>,
+}
+
+static CENSOR: Lazy<RwLock<Configured>> = Lazy::new(|| {
+    RwLock::new(Configured {
+        censor: Censor::new(),
+        types: Vec::new(),
+        arg: None,
+    })
+});
+
+/// Sets censor and rules used by the helpers, a default censor without rules is used otherwise
+pub fn set_censor(censor: Censor, types: Vec<CensorTypes>, arg: Option<String>) {
+    *CENSOR.write().unwrap_or_else(|e| e.into_inner()) = Configured { censor, types, arg };
+}
+
+fn censor_text(text: String) -> Result<String, crate::Error> {
+    let configured = CENSOR.read().unwrap_or_else(|e| e.into_inner());
+    let censored = configured.censor.censor(
+        text,
+        configured.types.clone().into_boxed_slice(),
+        configured.arg.clone(),
+    )?;
+    Ok(censored.censored)
+}
+
+/// Deserializes a string and censors it, for `#[serde(deserialize_with = "...")]`
+///
+/// # Errors
+///
+/// Fails if the string can't be deserialized or censoring failed, e.g. because of an
+/// invalid custom pattern.
+pub fn censored_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    censor_text(text).map_err(D::Error::custom)
+}
+
+/// Like [`censored_string`] for optional fields
+///
+/// # Errors
+///
+/// Fails for the same reasons as [`censored_string`].
+pub fn censored_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(text) => censor_text(text).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Message {
+        #[serde(default, deserialize_with = "censored_option")]
+        title: Option<String>,
+        #[serde(deserialize_with = "censored_string")]
+        text: String,
+    }
+
+    #[test]
+    fn censor_fields() {
+        let message: Message = serde_json::from_str(r#"{"text": "fuck you"}"#).unwrap();
+
+        assert_eq!(message.title, None);
+        assert_eq!(message.text, "f*** you");
+    }
+}
@@ -0,0 +1,183 @@
+//! Snapshots of the active configuration, proving which policy processed a message
+
+use serde::{Deserialize, Serialize};
+
+use crate::censor::CensorTypes;
+use crate::report::Verdict;
+use crate::words;
+
+/// Serializable copy of a censor's configuration and of the word list, see
+/// [`crate::censor::Censor::snapshot`]
+///
+/// Rules are given with every call rather than configured, so only rules of profiles
+/// are part of the snapshot unless they're added with [`ConfigSnapshot::with_rules`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    /// Version of little-censor
+    pub version: String,
+    /// Whether words are replaced by their fingerprints, see
+    /// [`ConfigSnapshot::with_hashed_words`]
+    pub hashed: bool,
+    /// Words added to the global word list, sorted by word
+    pub words: Vec<SnapshotWord>,
+    /// Words of the censor's own word list, see [`crate::censor::Censor::with_words`]
+    pub censor_words: Vec<SnapshotWord>,
+    /// Word packs of the global word list and of the censor's own one, in order
+    pub packs: Vec<PackInfo>,
+    /// Rules given with calls, see [`ConfigSnapshot::with_rules`]
+    pub rules: Vec<CensorTypes>,
+    /// Threshold for censoring vulgar words, e.g. `"INAPPROPRIATE"`
+    pub threshold: String,
+    /// Verdicts for messages of given types or custom categories
+    pub thresholds: Vec<SnapshotThreshold>,
+    /// Names of custom categories
+    pub categories: Vec<String>,
+    /// Registered profiles, sorted by name
+    pub profiles: Vec<SnapshotProfile>,
+    /// Domains kept by the link rule
+    pub allowed_domains: Vec<String>,
+    /// Character replacing censored ones
+    pub replacement: char,
+    /// Maximum length of sentences in bytes
+    pub max_length: Option<usize>,
+    /// Whether censoring happens in shadow mode
+    pub shadow: bool,
+    /// Whether invalid sentences are rejected
+    pub strict: bool,
+}
+
+impl ConfigSnapshot {
+    /// Records rules the censor is called with
+    pub fn with_rules(mut self, rules: impl IntoIterator<Item = CensorTypes>) -> Self {
+        self.rules = rules.into_iter().collect();
+        self
+    }
+
+    /// Replaces words by their fingerprints, so the snapshot can be shared without the
+    /// word list
+    ///
+    /// Fingerprints are FNV-1a hashes of the words, which identify a word list but don't
+    /// keep short words secret from anyone hashing candidates.
+    pub fn with_hashed_words(mut self) -> Self {
+        if !self.hashed {
+            for word in self.words.iter_mut().chain(&mut self.censor_words) {
+                word.word = fingerprint(word.word.as_bytes());
+            }
+            self.hashed = true;
+        }
+        self
+    }
+}
+
+/// Word of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotWord {
+    /// The word, or its fingerprint when the snapshot is hashed
+    pub word: String,
+    /// Type of the word, e.g. `"SEXUAL|SEVERE"`
+    #[serde(rename = "type")]
+    pub word_type: String,
+    /// Weight of the word, see [`crate::Vulgar::with_weight`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+}
+
+/// Verdict given to messages of a type or a custom category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotThreshold {
+    /// Type like `"SEVERE"`, or name of the category
+    pub on: String,
+    /// Whether `on` is a custom category
+    pub category: bool,
+    /// Verdict given to the messages
+    pub verdict: Verdict,
+}
+
+/// Profile of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotProfile {
+    /// Name the profile is registered under
+    pub name: String,
+    /// Rules of the profile
+    pub rules: Vec<CensorTypes>,
+    /// Pattern of `CensorTypes::Custom`
+    pub pattern: Option<String>,
+    /// Threshold of the profile, `None` if it uses the censor's one
+    pub threshold: Option<String>,
+    /// Whether the profile has its own word list
+    pub own_words: bool,
+}
+
+/// Word pack loaded with [`crate::pack::load_pack`] or from the configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackInfo {
+    /// Version of the binary format, `None` for JSON packs
+    pub format: Option<u8>,
+    /// Fingerprint of the pack's bytes, different for every version of the pack
+    pub fingerprint: String,
+    /// Number of words in the pack
+    pub words: usize,
+}
+
+/// Hex encoded FNV-1a hash of the bytes, stable across platforms and releases
+pub(crate) fn fingerprint(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// Words added to the global word list
+pub(crate) fn global_words() -> Vec<SnapshotWord> {
+    crate::list_words()
+        .into_iter()
+        .map(|vulgar| SnapshotWord {
+            word_type: crate::type_name::format(vulgar.word_type),
+            weight: words::weight(&vulgar.word),
+            word: vulgar.word,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::Censor;
+    use crate::profile::Profile;
+    use crate::report::Thresholds;
+    use crate::{Type, Vulgar};
+
+    #[test]
+    fn snapshot_of_censor() {
+        let censor = Censor::new()
+            .with_words(vec![
+                Vulgar::new(String::from("snapshotted"), None).with_weight(3)
+            ])
+            .unwrap()
+            .with_thresholds(Thresholds::new().with(Type::SEVERE, Verdict::Block))
+            .with_profile("links", Profile::new().with_types([CensorTypes::Link]))
+            .with_strict(true);
+
+        let snapshot = censor.snapshot().with_rules([CensorTypes::Email]);
+        assert_eq!(
+            snapshot.censor_words,
+            [SnapshotWord {
+                word: String::from("snapshotted"),
+                word_type: String::from("INAPPROPRIATE"),
+                weight: Some(3),
+            }]
+        );
+        assert_eq!(snapshot.thresholds[0].on, "SEVERE");
+        assert_eq!(snapshot.profiles[0].rules, [CensorTypes::Link]);
+        assert!(snapshot.strict);
+
+        let hashed = snapshot.with_hashed_words();
+        assert_eq!(hashed.censor_words[0].word, fingerprint(b"snapshotted"));
+        let json = serde_json::to_string(&hashed).unwrap();
+        assert!(!json.contains("snapshotted"));
+        assert_eq!(
+            serde_json::from_str::<ConfigSnapshot>(&json).unwrap(),
+            hashed
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Regions of a sentence which were censored
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::censor::CensorTypes;
+
+/// What caused a region to be censored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpanKind {
+    /// Match of a regex rule
+    Rule(CensorTypes),
+    /// Vulgar word or phrase
+    Profanity,
+    /// Mention above the limit of [`crate::censor::Censor::with_mention_limit`]
+    Mention,
+}
+
+impl SpanKind {
+    /// Lowercase name of the kind, e.g. `"profanity"` or `"link"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rule(CensorTypes::Link) => "link",
+            Self::Rule(CensorTypes::IP) => "ip",
+            Self::Rule(CensorTypes::Email) => "email",
+            Self::Rule(CensorTypes::Custom) => "custom",
+            Self::Profanity => "profanity",
+            Self::Mention => "mention",
+        }
+    }
+}
+
+/// Censored region of the original sentence, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SpanKind,
+}
+
+impl Span {
+    pub(crate) fn new(range: Range<usize>, kind: SpanKind) -> Self {
+        Self {
+            start: range.start,
+            end: range.end,
+            kind,
+        }
+    }
+
+    /// Byte range of the region
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// Changed region of a sentence, see [`crate::censor::Censored::diff`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hunk {
+    /// Byte range of the region in the original sentence
+    pub range: Range<usize>,
+    /// Text of the region before censoring
+    pub original: String,
+    /// Text which replaced it
+    pub replacement: String,
+}
+
+/// Changed regions of `original` covered by spans, overlapping and adjacent spans are
+/// joined
+///
+/// Censoring keeps the number of characters, so regions are found in `censored` at the
+/// same character positions.
+pub(crate) fn hunks(original: &str, censored: &str, spans: &[Span]) -> Vec<Hunk> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for span in spans {
+        match ranges.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => ranges.push(span.range()),
+        }
+    }
+
+    let offsets: Vec<usize> = censored
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([censored.len()])
+        .collect();
+    let at = |chars: usize| offsets[chars.min(offsets.len() - 1)];
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let text = original.get(range.clone())?;
+            let start = original[..range.start].chars().count();
+            let replacement = &censored[at(start)..at(start + text.chars().count())];
+            (replacement != text).then(|| Hunk {
+                range,
+                original: text.to_owned(),
+                replacement: replacement.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Regions of `before` whose characters were masked in `after`
+///
+/// Both sentences have to consist of the same number of characters.
+pub(crate) fn masked_spans(before: &str, after: &str, kind: SpanKind, mask: char) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut chars = before.char_indices().zip(after.chars());
+
+    loop {
+        let next = chars.next();
+        let masked = matches!(next, Some(((_, old), new)) if new == mask && old != mask);
+        let offset = next.map_or(before.len(), |((offset, _), _)| offset);
+        match (start, masked) {
+            (None, true) => start = Some(offset),
+            (Some(from), false) => {
+                spans.push(Span::new(from..offset, kind));
+                start = None;
+            }
+            _ => {}
+        }
+        if next.is_none() {
+            return spans;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_masked_spans() {
+        let spans = masked_spans("fuck *** shit", "f*** *** s***", SpanKind::Profanity, '*');
+
+        assert_eq!(
+            spans.iter().map(Span::range).collect::<Vec<_>>(),
+            vec![1..4, 10..13]
+        );
+    }
+
+    #[test]
+    fn joins_hunks() {
+        let spans = [
+            Span::new(0..2, SpanKind::Profanity),
+            Span::new(2..6, SpanKind::Rule(CensorTypes::Email)),
+            Span::new(8..9, SpanKind::Profanity),
+            Span::new(12..13, SpanKind::Profanity),
+        ];
+
+        let hunks = hunks("żółw ok ab", "***w ok a*", &spans);
+
+        assert_eq!(
+            hunks,
+            vec![
+                Hunk {
+                    range: 0..6,
+                    original: String::from("żół"),
+                    replacement: String::from("***"),
+                },
+                Hunk {
+                    range: 12..13,
+                    original: String::from("b"),
+                    replacement: String::from("*"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! Durable state kept in a single SQLite database
+//!
+//! [`SqliteStore`] keeps custom words, saved state like [`crate::reputation::Reputation`],
+//! moderator overrides of the [`crate::audit::AuditLog`] and moderation reports, so small
+//! deployments survive restarts without any other infrastructure.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::audit::{Override, OverrideStore};
+use crate::error::Error;
+use crate::hook::Hook;
+use crate::report::ModerationReport;
+use crate::store::{blocking, StateStore, StoreFuture, WordStore};
+use crate::{add_words, Type, Vulgar};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS words (word TEXT PRIMARY KEY, type INTEGER NOT NULL, weight INTEGER);
+CREATE TABLE IF NOT EXISTS state (name TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS overrides (content TEXT PRIMARY KEY, entry TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT,
+    report TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS reports_user_id ON reports (user_id);
+";
+
+/// SQLite database with words, state, overrides and reports
+///
+/// Clones share the connection, e.g. one can be given to [`crate::audit::AuditLog::with_store`]
+/// and another to [`crate::censor::Censor::with_hook`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::Censor;
+/// use little_censor::reputation::Reputation;
+/// use little_censor::sqlite::SqliteStore;
+/// use little_censor::Type;
+///
+/// let store = SqliteStore::in_memory().unwrap();
+/// let censor = Censor::new().with_hook(Type::MODERATE_OR_HIGHER, store.clone());
+/// censor.censor_user("user", String::from("fuck"), Box::new([]), None).unwrap();
+///
+/// let mut reputation = Reputation::new();
+/// reputation.record("user", Type::PROFANE & Type::SEVERE);
+/// store.save_state("reputation", &reputation).unwrap();
+///
+/// assert_eq!(store.reports(Some("user")).unwrap().len(), 1);
+/// assert!(store.load_state::<Reputation>("reputation").unwrap().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Opens the database file, creating it and its tables when missing
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if the file can't be opened or isn't a database.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::with_connection(Connection::open(path).map_err(Error::from)?)
+    }
+
+    /// Opens a database kept in memory, e.g. for tests
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if SQLite fails to allocate it.
+    pub fn in_memory() -> Result<Self, Error> {
+        Self::with_connection(Connection::open_in_memory().map_err(Error::from)?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, Error> {
+        connection.execute_batch(SCHEMA).map_err(Error::from)?;
+        // Databases created before words had weights lack the column
+        let weighted: bool = connection
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('words') WHERE name = 'weight'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(Error::from)?;
+        if !weighted {
+            connection
+                .execute("ALTER TABLE words ADD COLUMN weight INTEGER", [])
+                .map_err(Error::from)?;
+        }
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Stores words, replacing types and weights of the ones already stored
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` if any of the words is empty or `Error::Backend` if
+    /// the database failed, in which case none of the words are stored.
+    pub fn save_words(&self, vulgars: &[Vulgar]) -> Result<(), Error> {
+        if let Some(index) = vulgars.iter().position(|vulgar| vulgar.word.is_empty()) {
+            return Err(Error::EmptyWord { index });
+        }
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(Error::from)?;
+        for vulgar in vulgars {
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO words (word, type, weight) VALUES (?1, ?2, ?3)",
+                    params![vulgar.word, vulgar.word_type.bits(), vulgar.weight],
+                )
+                .map_err(Error::from)?;
+        }
+        transaction.commit().map_err(Error::from)
+    }
+
+    /// Removes stored words, returns number of words which were stored
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if the database failed.
+    pub fn delete_words(&self, words: &[String]) -> Result<usize, Error> {
+        let connection = self.connection();
+        words.iter().try_fold(0, |removed, word| {
+            connection
+                .execute("DELETE FROM words WHERE word = ?1", params![word])
+                .map(|count| removed + count)
+                .map_err(Error::from)
+        })
+    }
+
+    /// Every stored word, sorted by word
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if the database failed.
+    pub fn words(&self) -> Result<Vec<Vulgar>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT word, type, weight FROM words ORDER BY word")
+            .map_err(Error::from)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(Vulgar {
+                    word: row.get(0)?,
+                    word_type: Type::from_bits_truncate(row.get(1)?),
+                    weight: row.get(2)?,
+                })
+            })
+            .map_err(Error::from)?;
+        rows.collect::<Result<_, _>>().map_err(Error::from)
+    }
+
+    /// Adds every stored word to the word list, returns their number
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if the database failed.
+    pub fn load_words(&self) -> Result<usize, Error> {
+        let vulgars = self.words()?;
+        let count = vulgars.len();
+        add_words(vulgars)?;
+        Ok(count)
+    }
+
+    /// Stores state under the name as JSON, see [`crate::state::save_state`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Parse` if the state can't be serialized or `Error::Backend` if the
+    /// database failed.
+    pub fn save_state<T: Serialize>(&self, name: &str, state: &T) -> Result<(), Error> {
+        let json = serde_json::to_string(state).map_err(Error::json)?;
+        self.save_json(name, &json)
+    }
+
+    /// Reads state stored under the name, `None` if nothing was stored
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Parse` if the stored state is malformed or `Error::Backend` if the
+    /// database failed.
+    pub fn load_state<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, Error> {
+        self.load_json(name)?
+            .map(|json| serde_json::from_str(&json).map_err(Error::json))
+            .transpose()
+    }
+
+    fn save_json(&self, name: &str, json: &str) -> Result<(), Error> {
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO state (name, value) VALUES (?1, ?2)",
+                params![name, json],
+            )
+            .map(drop)
+            .map_err(Error::from)
+    }
+
+    fn load_json(&self, name: &str) -> Result<Option<String>, Error> {
+        self.connection()
+            .query_row(
+                "SELECT value FROM state WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(Error::from)
+    }
+
+    /// Stores a moderation report
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Backend` if the database failed.
+    pub fn save_report(&self, report: &ModerationReport) -> Result<(), Error> {
+        let value = serde_json::to_string(report).map_err(Error::json)?;
+        self.connection()
+            .execute(
+                "INSERT INTO reports (user_id, report) VALUES (?1, ?2)",
+                params![report.user_id, value],
+            )
+            .map(drop)
+            .map_err(Error::from)
+    }
+
+    /// Stored reports in the order they were saved, only the user's ones if given
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Parse` if a stored report is malformed or `Error::Backend` if the
+    /// database failed.
+    pub fn reports(&self, user_id: Option<&str>) -> Result<Vec<ModerationReport>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT report FROM reports WHERE ?1 IS NULL OR user_id = ?1 ORDER BY id")
+            .map_err(Error::from)?;
+        let rows = statement
+            .query_map(params![user_id], |row| row.get::<_, String>(0))
+            .map_err(Error::from)?;
+        rows.map(|row| {
+            let value = row.map_err(Error::from)?;
+            serde_json::from_str(&value).map_err(Error::json)
+        })
+        .collect()
+    }
+}
+
+impl OverrideStore for SqliteStore {
+    fn load(&self, content: &str) -> Result<Option<Override>, Error> {
+        let entry: Option<String> = self
+            .connection()
+            .query_row(
+                "SELECT entry FROM overrides WHERE content = ?1",
+                params![content],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(Error::from)?;
+        entry
+            .map(|entry| serde_json::from_str(&entry).map_err(Error::json))
+            .transpose()
+    }
+
+    fn save(&self, content: &str, entry: Override) -> Result<(), Error> {
+        let entry = serde_json::to_string(&entry).map_err(Error::json)?;
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO overrides (content, entry) VALUES (?1, ?2)",
+                params![content, entry],
+            )
+            .map(drop)
+            .map_err(Error::from)
+    }
+
+    fn delete(&self, content: &str) -> Result<(), Error> {
+        self.connection()
+            .execute("DELETE FROM overrides WHERE content = ?1", params![content])
+            .map(drop)
+            .map_err(Error::from)
+    }
+
+    fn entries(&self) -> Result<Vec<(String, Override)>, Error> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT content, entry FROM overrides")
+            .map_err(Error::from)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(Error::from)?;
+        rows.map(|row| {
+            let (content, entry) = row.map_err(Error::from)?;
+            let entry = serde_json::from_str(&entry).map_err(Error::json)?;
+            Ok((content, entry))
+        })
+        .collect()
+    }
+}
+
+impl WordStore for SqliteStore {
+    fn load(&self) -> StoreFuture<'_, Vec<Vulgar>> {
+        let store = self.clone();
+        Box::pin(blocking(move || store.words()))
+    }
+
+    fn save(&self, vulgars: Vec<Vulgar>) -> StoreFuture<'_, ()> {
+        let store = self.clone();
+        Box::pin(blocking(move || store.save_words(&vulgars)))
+    }
+
+    fn delete(&self, words: Vec<String>) -> StoreFuture<'_, usize> {
+        let store = self.clone();
+        Box::pin(blocking(move || store.delete_words(&words)))
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn load(&self, name: &str) -> StoreFuture<'_, Option<String>> {
+        let (store, name) = (self.clone(), name.to_owned());
+        Box::pin(blocking(move || store.load_json(&name)))
+    }
+
+    fn save(&self, name: &str, json: String) -> StoreFuture<'_, ()> {
+        let (store, name) = (self.clone(), name.to_owned());
+        Box::pin(blocking(move || store.save_json(&name, &json)))
+    }
+}
+
+impl Hook for SqliteStore {
+    fn notify(&self, report: &ModerationReport) {
+        if self.save_report(report).is_err() {
+            log::warn!("Moderation report couldn't be stored");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditLog, Decision};
+    use crate::error::Position;
+    use std::collections::HashMap;
+
+    #[test]
+    fn words_survive_reopening() {
+        let path = std::env::temp_dir().join(format!("little-censor-{}.db", std::process::id()));
+        let store = SqliteStore::open(&path).unwrap();
+        store
+            .save_words(&[
+                Vulgar {
+                    word: String::from("sqlitebanned"),
+                    word_type: Type::SEVERE,
+                    weight: Some(4),
+                },
+                Vulgar::new(String::from("sqliteremoved"), None),
+            ])
+            .unwrap();
+        assert_eq!(
+            store
+                .delete_words(&[String::from("sqliteremoved")])
+                .unwrap(),
+            1
+        );
+        drop(store);
+
+        let store = SqliteStore::open(&path).unwrap();
+        assert_eq!(
+            store.words().unwrap(),
+            [Vulgar {
+                word: String::from("sqlitebanned"),
+                word_type: Type::SEVERE,
+                weight: Some(4),
+            }]
+        );
+        assert!(matches!(
+            store.save_words(&[Vulgar::new(String::new(), None)]),
+            Err(Error::EmptyWord { index: 0 })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn weights_added_to_old_databases() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE words (word TEXT PRIMARY KEY, type INTEGER NOT NULL);
+                 INSERT INTO words VALUES ('sqliteold', 4);",
+            )
+            .unwrap();
+        let store = SqliteStore::with_connection(connection).unwrap();
+        store
+            .save_words(&[Vulgar::new(String::from("sqliteweighted"), None).with_weight(9)])
+            .unwrap();
+
+        let weights: Vec<_> = store
+            .words()
+            .unwrap()
+            .into_iter()
+            .map(|vulgar| vulgar.weight)
+            .collect();
+        assert_eq!(weights, [None, Some(9)]);
+    }
+
+    #[test]
+    fn errors_keep_their_source() {
+        let store = SqliteStore::in_memory().unwrap();
+        store.save_json("broken", "{\n\"score\": ").unwrap();
+        assert!(matches!(
+            store.load_state::<HashMap<String, u32>>("broken"),
+            Err(Error::Parse {
+                at: Position::Line(2)
+            })
+        ));
+
+        store
+            .connection()
+            .execute_batch("DROP TABLE state")
+            .unwrap();
+        let error = store.load_json("broken").unwrap_err();
+        assert_eq!(error.code(), "STORAGE");
+        assert!(std::error::Error::source(&error)
+            .unwrap()
+            .is::<rusqlite::Error>());
+    }
+
+    #[test]
+    fn stores_overrides() {
+        let store = SqliteStore::in_memory().unwrap();
+        let log = AuditLog::with_store(store.clone());
+        log.record("my surname is Dick", Decision::Allow, "moderator", None)
+            .unwrap();
+
+        assert_eq!(store.entries().unwrap().len(), 1);
+        assert_eq!(
+            log.lookup("my surname is Dick").unwrap().unwrap().decision,
+            Decision::Allow
+        );
+    }
+
+    #[tokio::test]
+    async fn store_futures_run_off_the_runtime() {
+        let store = SqliteStore::in_memory().unwrap();
+        WordStore::save(
+            &store,
+            vec![Vulgar::new(String::from("sqliteawaited"), None)],
+        )
+        .await
+        .unwrap();
+        StateStore::save(&store, "reputation", String::from("{}"))
+            .await
+            .unwrap();
+
+        assert_eq!(WordStore::load(&store).await.unwrap().len(), 1);
+        assert_eq!(
+            StateStore::load(&store, "reputation")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("{}")
+        );
+        assert_eq!(
+            WordStore::delete(&store, vec![String::from("sqliteawaited")])
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
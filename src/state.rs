@@ -0,0 +1,63 @@
+//! Saving and restoring moderation state across restarts
+
+use std::io::{Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Error;
+
+/// Writes state, e.g. [`crate::context::UserContext`] or [`crate::reputation::Reputation`],
+/// as JSON
+///
+/// # Errors
+///
+/// Returns `Error::Io` if the state couldn't be written.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::reputation::Reputation;
+/// use little_censor::state::{load_state, save_state};
+/// use little_censor::Type;
+///
+/// let mut reputation = Reputation::new();
+/// reputation.record("user", Type::PROFANE & Type::SEVERE);
+///
+/// let mut saved = Vec::new();
+/// save_state(&reputation, &mut saved).unwrap();
+/// let restored: Reputation = load_state(saved.as_slice()).unwrap();
+///
+/// assert_eq!(restored.score("user"), reputation.score("user"));
+/// ```
+pub fn save_state<T: Serialize>(state: &T, writer: impl Write) -> Result<(), Error> {
+    serde_json::to_writer(writer, state).map_err(Error::json)
+}
+
+/// Reads state written with [`save_state`]
+///
+/// # Errors
+///
+/// Returns `Error::Io` if the state couldn't be read or `Error::Parse` if it's malformed.
+pub fn load_state<T: DeserializeOwned>(reader: impl Read) -> Result<T, Error> {
+    serde_json::from_reader(reader).map_err(Error::json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Position;
+    use crate::flood::FloodGuard;
+
+    #[test]
+    fn malformed_state() {
+        let restored = load_state::<FloodGuard>(&b"{\n\"limit\": 1,\n\"window\": }"[..]);
+
+        assert!(matches!(
+            restored,
+            Err(Error::Parse {
+                at: Position::Line(3)
+            })
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! Throughput statistics of a censor instance
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::censor::{CensorTypes, Pass};
+
+/// Number of most recent latencies used to compute percentiles
+const LATENCY_WINDOW: usize = 1024;
+
+/// Snapshot of statistics collected by [`crate::censor::Censor`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// Successfully censored messages
+    pub messages: u64,
+    /// Masked matches of every regex rule
+    pub matches: HashMap<CensorTypes, u64>,
+    /// Vulgar words found
+    pub profanity_matches: u64,
+    /// Custom patterns reused from the cache
+    pub cache_hits: u64,
+    /// Custom patterns that had to be compiled
+    pub cache_misses: u64,
+    /// 99th percentile of the latency of recent messages
+    pub p99_latency: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    messages: u64,
+    matches: HashMap<CensorTypes, u64>,
+    profanity_matches: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    latencies: VecDeque<Duration>,
+}
+
+/// Thread safe statistics shared between clones of a censor
+#[derive(Debug, Default)]
+pub(crate) struct StatsCollector {
+    counters: Mutex<Counters>,
+}
+
+impl StatsCollector {
+    /// Accounts for a single censored message
+    pub(crate) fn record(&self, pass: &Pass, cache_hit: Option<bool>, latency: Duration) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+
+        counters.messages += 1;
+        for &(typ, matches) in &pass.rule_matches {
+            *counters.matches.entry(typ).or_default() += matches as u64;
+        }
+        counters.profanity_matches += pass.profanity_matches as u64;
+        match cache_hit {
+            Some(true) => counters.cache_hits += 1,
+            Some(false) => counters.cache_misses += 1,
+            None => {}
+        }
+
+        if counters.latencies.len() == LATENCY_WINDOW {
+            counters.latencies.pop_front();
+        }
+        counters.latencies.push_back(latency);
+    }
+
+    pub(crate) fn snapshot(&self) -> Stats {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut latencies: Vec<Duration> = counters.latencies.iter().copied().collect();
+        latencies.sort_unstable();
+        let p99_latency = latencies
+            .len()
+            .checked_sub(1)
+            .map(|last| latencies[last * 99 / 100]);
+
+        Stats {
+            messages: counters.messages,
+            matches: counters.matches.clone(),
+            profanity_matches: counters.profanity_matches,
+            cache_hits: counters.cache_hits,
+            cache_misses: counters.cache_misses,
+            p99_latency,
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        *self.counters.lock().unwrap_or_else(|e| e.into_inner()) = Counters::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass() -> Pass {
+        Pass {
+            censored: String::new(),
+            typ: rustrict::Type::NONE,
+            rule_matches: vec![(CensorTypes::Link, 1)],
+            profanity_matches: 0,
+            spans: vec![],
+        }
+    }
+
+    #[test]
+    fn p99_latency() {
+        let stats = StatsCollector::default();
+        for millis in 1..=100 {
+            stats.record(&pass(), None, Duration::from_millis(millis));
+        }
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.messages, 100);
+        assert_eq!(snapshot.matches.get(&CensorTypes::Link), Some(&100));
+        assert_eq!(snapshot.p99_latency, Some(Duration::from_millis(99)));
+    }
+
+    #[test]
+    fn latency_window() {
+        let stats = StatsCollector::default();
+        for _ in 0..LATENCY_WINDOW + 10 {
+            stats.record(&pass(), None, Duration::ZERO);
+        }
+
+        assert_eq!(
+            stats.counters.lock().unwrap().latencies.len(),
+            LATENCY_WINDOW
+        );
+        stats.reset();
+        assert_eq!(stats.snapshot(), Stats::default());
+    }
+}
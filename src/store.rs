@@ -0,0 +1,349 @@
+//! Pluggable persistence of custom words and moderation state
+//!
+//! [`WordStore`] and [`StateStore`] are object safe, so backends can be chosen at runtime
+//! and kept as `Arc<dyn WordStore>`. Methods return boxed futures, a backend for another
+//! database implements them with `Box::pin(async move { ... })`.
+//!
+//! Implemented by [`MemoryStore`], [`FileStore`], `SqliteStore` with the `sqlite` feature
+//! and `RedisWords` with the `redis` feature. Futures of the last two run their blocking
+//! queries on the blocking thread pool of tokio, so they have to be awaited within a
+//! tokio runtime.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::pack::{read_pack, write_pack};
+use crate::{add_words, Type, Vulgar};
+
+/// Future returned by methods of the stores
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// Runs blocking queries of a store without blocking other tasks of the runtime
+#[cfg(any(feature = "redis", feature = "sqlite"))]
+pub(crate) async fn blocking<T: Send + 'static>(
+    query: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(query)
+        .await
+        .map_err(|_| Error::Storage)?
+}
+
+/// Storage of custom words
+pub trait WordStore: Send + Sync {
+    /// Returns every stored word
+    fn load(&self) -> StoreFuture<'_, Vec<Vulgar>>;
+
+    /// Stores words, replacing types of the ones already stored
+    fn save(&self, vulgars: Vec<Vulgar>) -> StoreFuture<'_, ()>;
+
+    /// Removes words, returns number of words which were stored
+    fn delete(&self, words: Vec<String>) -> StoreFuture<'_, usize>;
+}
+
+/// Storage of serialized state like [`crate::reputation::Reputation`], keyed by name
+pub trait StateStore: Send + Sync {
+    /// Returns JSON stored under the name
+    fn load(&self, name: &str) -> StoreFuture<'_, Option<String>>;
+
+    /// Stores JSON under the name, replacing the previous one
+    fn save(&self, name: &str, json: String) -> StoreFuture<'_, ()>;
+}
+
+/// Adds every stored word to the word list, returns their number
+///
+/// # Errors
+///
+/// Returns errors of the store or `Error::EmptyWord` if any stored word is empty.
+pub async fn load_words(store: &(impl WordStore + ?Sized)) -> Result<usize, Error> {
+    let vulgars = store.load().await?;
+    let count = vulgars.len();
+    add_words(vulgars)?;
+    Ok(count)
+}
+
+/// Stores state under the name
+///
+/// # Errors
+///
+/// Returns `Error::Storage` if the state can't be serialized, or errors of the store.
+pub async fn save_state<T: Serialize>(
+    store: &(impl StateStore + ?Sized),
+    name: &str,
+    state: &T,
+) -> Result<(), Error> {
+    let json = serde_json::to_string(state).map_err(|_| Error::Storage)?;
+    store.save(name, json).await
+}
+
+/// Reads state stored under the name, `None` if nothing was stored
+///
+/// # Errors
+///
+/// Returns `Error::Parse` if the stored state is malformed, or errors of the store.
+pub async fn load_state<T: DeserializeOwned>(
+    store: &(impl StateStore + ?Sized),
+    name: &str,
+) -> Result<Option<T>, Error> {
+    match store.load(name).await? {
+        Some(json) => serde_json::from_str(&json).map(Some).map_err(Error::json),
+        None => Ok(None),
+    }
+}
+
+/// Stored word keyed by the word
+fn entry(vulgar: Vulgar) -> (String, (Type, Option<u32>)) {
+    (vulgar.word, (vulgar.word_type, vulgar.weight))
+}
+
+/// Store kept in memory of the process, lost on restart
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    words: Mutex<BTreeMap<String, (Type, Option<u32>)>>,
+    state: Mutex<HashMap<String, String>>,
+}
+
+impl WordStore for MemoryStore {
+    fn load(&self) -> StoreFuture<'_, Vec<Vulgar>> {
+        let words = self.words.lock().unwrap_or_else(|e| e.into_inner());
+        let vulgars = words
+            .iter()
+            .map(|(word, &(word_type, weight))| Vulgar {
+                word: word.clone(),
+                word_type,
+                weight,
+            })
+            .collect();
+        Box::pin(async move { Ok(vulgars) })
+    }
+
+    fn save(&self, vulgars: Vec<Vulgar>) -> StoreFuture<'_, ()> {
+        let mut words = self.words.lock().unwrap_or_else(|e| e.into_inner());
+        let empty = vulgars.iter().position(|vulgar| vulgar.word.is_empty());
+        let result = if let Some(index) = empty {
+            Err(Error::EmptyWord { index })
+        } else {
+            words.extend(vulgars.into_iter().map(entry));
+            Ok(())
+        };
+        Box::pin(async move { result })
+    }
+
+    fn delete(&self, removed: Vec<String>) -> StoreFuture<'_, usize> {
+        let mut words = self.words.lock().unwrap_or_else(|e| e.into_inner());
+        let count = removed
+            .iter()
+            .filter(|word| words.remove(*word).is_some())
+            .count();
+        Box::pin(async move { Ok(count) })
+    }
+}
+
+impl StateStore for MemoryStore {
+    fn load(&self, name: &str) -> StoreFuture<'_, Option<String>> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let json = state.get(name).cloned();
+        Box::pin(async move { Ok(json) })
+    }
+
+    fn save(&self, name: &str, json: String) -> StoreFuture<'_, ()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.insert(name.to_owned(), json);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// Store in a directory, words are kept in the `words.lcwp` pack and state in
+/// `<name>.json` files
+///
+/// Files are small and written whole, so they're read and written without a blocking
+/// thread pool.
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    /// Serializes read-modify-write of the word pack
+    words: Mutex<()>,
+}
+
+impl FileStore {
+    /// Creates store in the directory, which is created on the first write
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            words: Mutex::new(()),
+        }
+    }
+
+    fn read_words(&self) -> Result<Vec<Vulgar>, Error> {
+        match fs::read(self.dir.join("words.lcwp")) {
+            Ok(bytes) => read_pack(&bytes),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(_) => Err(Error::Storage),
+        }
+    }
+
+    /// Writes the file next to its destination first, so it's replaced as a whole
+    fn write(&self, name: &str, bytes: &[u8]) -> Result<(), Error> {
+        fs::create_dir_all(&self.dir).map_err(|_| Error::Storage)?;
+        let temporary = self.dir.join(format!(".{name}.tmp"));
+        fs::write(&temporary, bytes).map_err(|_| Error::Storage)?;
+        fs::rename(temporary, self.dir.join(name)).map_err(|_| Error::Storage)
+    }
+
+    /// Replaces words of the pack with the result of `update`
+    fn update_words<T>(
+        &self,
+        update: impl FnOnce(&mut BTreeMap<String, (Type, Option<u32>)>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let _lock = self.words.lock().unwrap_or_else(|e| e.into_inner());
+        let mut words: BTreeMap<_, _> = self.read_words()?.into_iter().map(entry).collect();
+        let result = update(&mut words)?;
+        let vulgars: Vec<Vulgar> = words
+            .into_iter()
+            .map(|(word, (word_type, weight))| Vulgar {
+                word,
+                word_type,
+                weight,
+            })
+            .collect();
+        self.write("words.lcwp", &write_pack(&vulgars))?;
+        Ok(result)
+    }
+
+    /// File of the state, names are restricted so they can't escape the directory
+    fn state_file(name: &str) -> Result<String, Error> {
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if name.is_empty() || !name.chars().all(valid) {
+            return Err(Error::Storage);
+        }
+        Ok(format!("{name}.json"))
+    }
+}
+
+impl WordStore for FileStore {
+    fn load(&self) -> StoreFuture<'_, Vec<Vulgar>> {
+        Box::pin(async move { self.read_words() })
+    }
+
+    fn save(&self, vulgars: Vec<Vulgar>) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            if let Some(index) = vulgars.iter().position(|vulgar| vulgar.word.is_empty()) {
+                return Err(Error::EmptyWord { index });
+            }
+            self.update_words(|words| {
+                words.extend(vulgars.into_iter().map(entry));
+                Ok(())
+            })
+        })
+    }
+
+    fn delete(&self, removed: Vec<String>) -> StoreFuture<'_, usize> {
+        Box::pin(async move {
+            self.update_words(|words| {
+                Ok(removed
+                    .iter()
+                    .filter(|word| words.remove(*word).is_some())
+                    .count())
+            })
+        })
+    }
+}
+
+impl StateStore for FileStore {
+    fn load(&self, name: &str) -> StoreFuture<'_, Option<String>> {
+        let file = Self::state_file(name);
+        Box::pin(async move {
+            match fs::read_to_string(self.dir.join(file?)) {
+                Ok(json) => Ok(Some(json)),
+                Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+                Err(_) => Err(Error::Storage),
+            }
+        })
+    }
+
+    fn save(&self, name: &str, json: String) -> StoreFuture<'_, ()> {
+        let file = Self::state_file(name);
+        Box::pin(async move { self.write(&file?, json.as_bytes()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+    use crate::reputation::Reputation;
+
+    /// Runs a future of the stores, which never wait
+    fn block_on<T>(future: impl Future<Output = T>) -> T {
+        let mut future = std::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("store future is pending"),
+        }
+    }
+
+    fn round_trip(store: &(impl WordStore + StateStore)) {
+        block_on(WordStore::save(
+            store,
+            vec![
+                Vulgar {
+                    word: String::from("stored"),
+                    word_type: Type::SEVERE,
+                    weight: Some(6),
+                },
+                Vulgar::new(String::from("deleted"), None),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(
+            block_on(store.delete(vec![String::from("deleted")])).unwrap(),
+            1
+        );
+        assert_eq!(
+            block_on(WordStore::load(store)).unwrap(),
+            [Vulgar {
+                word: String::from("stored"),
+                word_type: Type::SEVERE,
+                weight: Some(6),
+            }]
+        );
+
+        let mut reputation = Reputation::new();
+        reputation.record("user", Type::PROFANE & Type::SEVERE);
+        block_on(save_state(store, "reputation", &reputation)).unwrap();
+        let restored: Reputation = block_on(load_state(store, "reputation")).unwrap().unwrap();
+        assert_eq!(restored.score("user"), reputation.score("user"));
+        assert!(block_on(load_state::<Reputation>(store, "missing"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn memory_store() {
+        round_trip(&MemoryStore::default());
+    }
+
+    #[test]
+    fn file_store() {
+        let dir = std::env::temp_dir().join(format!("little-censor-store-{}", std::process::id()));
+        round_trip(&FileStore::new(&dir));
+
+        assert!(matches!(
+            block_on(StateStore::load(&FileStore::new(&dir), "../escape")),
+            Err(Error::Storage)
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
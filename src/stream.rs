@@ -0,0 +1,254 @@
+//! Censoring long or incrementally arriving text in pieces
+
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::censor::{Censor, CensorTypes, Censored};
+use crate::error::Error;
+#[cfg(feature = "wasm-min")]
+use crate::error::WasmError;
+
+/// Default number of bytes censored at once by `censor_async`
+#[cfg(feature = "wasm")]
+const DEFAULT_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Censor for text arriving in chunks, e.g. over a WebSocket
+///
+/// Text is censored up to the last whitespace of every chunk, the unfinished word
+/// after it waits for the next chunk, so words split between chunks are still matched.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::stream::CensorStream;
+///
+/// let mut stream = CensorStream::new(Box::new([]), None);
+///
+/// assert_eq!(stream.push("what the fu").unwrap(), "what the ");
+/// assert_eq!(stream.push("ck is this").unwrap(), "f*** is ");
+/// assert_eq!(stream.finish().unwrap(), "this");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "wasm-min", wasm_bindgen)]
+pub struct CensorStream {
+    censor: Censor,
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+    pending: String,
+    result: Option<Censored>,
+}
+
+impl CensorStream {
+    /// Creates empty stream for the given censor types and argument
+    pub fn new(types: Box<[CensorTypes]>, arg: Option<String>) -> Self {
+        Self {
+            censor: Censor::new(),
+            types,
+            arg,
+            pending: String::new(),
+            result: None,
+        }
+    }
+
+    /// Censors the stream with given configuration instead of the default one
+    pub fn with_censor(mut self, censor: Censor) -> Self {
+        self.censor = censor;
+        self
+    }
+
+    /// Adds next chunk, returns censored text which later chunks can't change
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    pub fn push(&mut self, chunk: &str) -> Result<String, Error> {
+        self.pending.push_str(chunk);
+        let end = match self.pending.rfind(char::is_whitespace) {
+            Some(i) => i + self.pending[i..].chars().next().map_or(1, char::len_utf8),
+            None => return Ok(String::new()),
+        };
+        let ready: String = self.pending.drain(..end).collect();
+        self.censor_ready(ready)
+    }
+
+    /// Censors the rest of the stream, returns text which wasn't returned by [`Self::push`]
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Censor::censor`].
+    pub fn finish(&mut self) -> Result<String, Error> {
+        let rest = std::mem::take(&mut self.pending);
+        if rest.is_empty() {
+            return Ok(rest);
+        }
+        self.censor_ready(rest)
+    }
+
+    /// Result for all text censored so far
+    pub fn result(&self) -> Option<&Censored> {
+        self.result.as_ref()
+    }
+
+    fn censor_ready(&mut self, text: String) -> Result<String, Error> {
+        let censored = self
+            .censor
+            .censor(text, self.types.clone(), self.arg.clone())?;
+        let output = censored.censored.clone();
+        match &mut self.result {
+            Some(result) => result.append(censored),
+            None => self.result = Some(censored),
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen]
+impl CensorStream {
+    #[wasm_bindgen(constructor)]
+    pub fn wasm_new(types: Box<[CensorTypes]>, arg: Option<String>) -> Self {
+        Self::new(types, arg)
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn wasm_push(&mut self, chunk: &str) -> Result<String, WasmError> {
+        self.push(chunk).map_err(WasmError::from)
+    }
+
+    #[wasm_bindgen(js_name = finish)]
+    pub fn wasm_finish(&mut self) -> Result<String, WasmError> {
+        self.finish().map_err(WasmError::from)
+    }
+
+    /// Result for all text censored so far, `undefined` before anything was censored
+    #[wasm_bindgen(getter = result)]
+    pub fn wasm_result(&self) -> Option<Censored> {
+        self.result.clone()
+    }
+}
+
+/// Splits text into pieces of at least `size` bytes, every one but the last ending
+/// with whitespace so words are never cut in half
+#[cfg(feature = "wasm")]
+pub(crate) fn split_chunks(text: &str, size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > size {
+        let from = (size..rest.len())
+            .find(|&i| rest.is_char_boundary(i))
+            .unwrap_or(rest.len());
+        let end = match rest[from..].char_indices().find(|(_, c)| c.is_whitespace()) {
+            Some((i, c)) => from + i + c.len_utf8(),
+            None => break,
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &js_sys::Function, millis: i32);
+}
+
+/// Lets the browser handle pending events before continuing
+#[cfg(feature = "wasm")]
+async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| set_timeout(&resolve, 0));
+    // The promise never rejects
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Censors long text without blocking the main thread
+///
+/// Text is censored in pieces of about `chunk_size` bytes split at whitespace,
+/// yielding to the event loop after each of them. Phrases cut by a split aren't
+/// matched.
+///
+/// # Errors
+///
+/// Rejects with the same errors as `censor`.
+///
+/// # Examples
+///
+/// const censored = await censor_async(pastedDocument, [CensorTypes.Link], undefined);
+#[cfg(feature = "wasm")]
+#[wasm_bindgen(js_name = "censor_async")]
+pub async fn censor_async(
+    sentence: String,
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+    chunk_size: Option<usize>,
+) -> Result<Censored, WasmError> {
+    let censor = Censor::new();
+    let size = chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE).max(1);
+    let censor_chunk = |chunk: &str| {
+        censor
+            .censor(chunk.to_owned(), types.clone(), arg.clone())
+            .map_err(WasmError::from)
+    };
+    let mut chunks = split_chunks(&sentence, size).into_iter();
+    let mut result = censor_chunk(chunks.next().unwrap_or_default())?;
+    for chunk in chunks {
+        yield_now().await;
+        result.append(censor_chunk(chunk)?);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_matches_full_censor() {
+        let sentence = "fuck this https://example.net and shit, see you";
+        let mut stream = CensorStream::new(Box::new([CensorTypes::Link]), None);
+        let mut output = String::new();
+        for chunk in sentence.as_bytes().chunks(3) {
+            output += &stream.push(std::str::from_utf8(chunk).unwrap()).unwrap();
+        }
+        output += &stream.finish().unwrap();
+
+        let whole = Censor::new()
+            .censor(sentence.to_owned(), Box::new([CensorTypes::Link]), None)
+            .unwrap();
+        assert_eq!(output, whole.censored);
+        assert_eq!(stream.result().unwrap().spans, whole.spans);
+    }
+
+    #[test]
+    #[cfg(feature = "wasm")]
+    fn chunks_end_with_whitespace() {
+        assert_eq!(
+            split_chunks("one two three four", 5),
+            vec!["one two ", "three ", "four"]
+        );
+        assert_eq!(split_chunks("", 5), vec![""]);
+        assert_eq!(split_chunks("ąąąąą", 3), vec!["ąąąąą"]);
+    }
+
+    #[test]
+    #[cfg(feature = "wasm")]
+    fn joined_chunks() {
+        let censor = Censor::new();
+        let sentence = "fuck this https://example.net and shit";
+        let mut chunks = split_chunks(sentence, 10).into_iter().map(|chunk| {
+            censor
+                .censor(chunk.to_owned(), Box::new([CensorTypes::Link]), None)
+                .unwrap()
+        });
+        let mut joined = chunks.next().unwrap();
+        chunks.for_each(|censored| joined.append(censored));
+        let whole = censor
+            .censor(sentence.to_owned(), Box::new([CensorTypes::Link]), None)
+            .unwrap();
+
+        assert_eq!(joined, whole);
+    }
+}
@@ -0,0 +1,174 @@
+//! Censoring cue text of SRT and WebVTT subtitles, e.g. user generated captions
+//!
+//! Cues are found by their timing line containing `-->`, only the lines after it are
+//! censored. Indices, identifiers, timestamps, headers, `NOTE` and `STYLE` blocks are
+//! kept as they are, and so are formatting tags like `<i>`, `<c.yellow>` or `{\an8}`.
+
+use crate::error::Error;
+use crate::json::JsonOptions;
+
+/// Censors text of every cue, returns number of modified cues
+///
+/// Works for both SRT and WebVTT, key patterns of the options aren't used.
+///
+/// # Errors
+///
+/// Returns the same errors as [`crate::censor::Censor::censor`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::json::JsonOptions;
+/// use little_censor::subtitles::censor_subtitles;
+///
+/// let mut captions = String::from("1\n00:00:01,000 --> 00:00:02,000\n<i>fuck</i> me\n");
+///
+/// censor_subtitles(&mut captions, &JsonOptions::new()).unwrap();
+///
+/// assert_eq!(captions, "1\n00:00:01,000 --> 00:00:02,000\n<i>f***</i> me\n");
+/// ```
+pub fn censor_subtitles(text: &mut String, options: &JsonOptions) -> Result<usize, Error> {
+    let mut censored = String::with_capacity(text.len());
+    let mut cue: Vec<&str> = Vec::new();
+    let mut in_cue = false;
+    let mut modified = 0;
+
+    for line in text.split_inclusive('\n') {
+        let content = line
+            .trim_end_matches(['\r', '\n'])
+            .trim_start_matches('\u{feff}');
+        if content.trim().is_empty() {
+            modified += censor_cue(&mut censored, &cue, options)?;
+            cue.clear();
+            in_cue = false;
+            censored.push_str(line);
+        } else if in_cue {
+            cue.push(line);
+        } else {
+            in_cue = content.contains("-->");
+            censored.push_str(line);
+        }
+    }
+    modified += censor_cue(&mut censored, &cue, options)?;
+
+    *text = censored;
+    Ok(modified)
+}
+
+/// Part of a cue line
+enum Part<'a> {
+    Text(&'a str),
+    /// Formatting tag, never censored
+    Tag(&'a str),
+}
+
+/// Splits text of a line into text and formatting tags
+fn parts(line: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        let start = rest.find(['<', '{']).unwrap_or(rest.len());
+        let end = match rest[start..].chars().next() {
+            Some('<') => rest[start..].find('>'),
+            Some('{') if rest[start..].starts_with("{\\") => rest[start..].find('}'),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                if start > 0 {
+                    parts.push(Part::Text(&rest[..start]));
+                }
+                parts.push(Part::Tag(&rest[start..=start + end]));
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                // Brace or bracket which doesn't open a tag is text
+                let next = rest[start..]
+                    .char_indices()
+                    .nth(1)
+                    .map_or(rest.len(), |(index, _)| start + index);
+                parts.push(Part::Text(&rest[..next]));
+                rest = &rest[next..];
+            }
+        }
+    }
+    parts
+}
+
+/// Writes censored lines of a cue, returns 1 if any of them changed
+///
+/// Visible text of the whole cue is censored at once, so words split by tags or
+/// lines are found as well.
+fn censor_cue(output: &mut String, lines: &[&str], options: &JsonOptions) -> Result<usize, Error> {
+    let split: Vec<(&str, Vec<Part>)> = lines
+        .iter()
+        .map(|line| {
+            let content = line.trim_end_matches(['\r', '\n']);
+            (&line[content.len()..], parts(content))
+        })
+        .collect();
+    let visible: Vec<String> = split
+        .iter()
+        .map(|(_, parts)| {
+            parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::Text(text) => Some(*text),
+                    Part::Tag(_) => None,
+                })
+                .collect()
+        })
+        .collect();
+    let visible = visible.join("\n");
+
+    let result = options.censor(visible.clone())?;
+    if result.valid {
+        for line in lines {
+            output.push_str(line);
+        }
+        return Ok(0);
+    }
+
+    // Censoring keeps the number of characters, so they're put back piece by piece
+    let mut censored = result.censored.chars();
+    let same_length = result.censored.chars().count() == visible.chars().count();
+    for (index, (ending, parts)) in split.into_iter().enumerate() {
+        if index > 0 && same_length {
+            // Line break between the lines
+            censored.next();
+        }
+        for part in parts {
+            match part {
+                Part::Tag(tag) => output.push_str(tag),
+                Part::Text(text) if same_length => {
+                    output.extend(censored.by_ref().take(text.chars().count()));
+                }
+                Part::Text(text) => output.push_str(&options.censor(text.to_owned())?.censored),
+            }
+        }
+        output.push_str(ending);
+    }
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::censor::CensorTypes;
+
+    #[test]
+    fn webvtt_cues_only() {
+        let mut captions = String::from(
+            "WEBVTT\r\n\r\nNOTE fuck stays in notes\r\n\r\nintro\r\n00:01.000 --> 00:02.000 align:start\r\n<v Roger>fu<b>ck</b> {\\an8}there\r\nme@example.net\r\n\r\n00:03.000 --> 00:04.000\r\nfine\r\n",
+        );
+        let options = JsonOptions::new().with_types([CensorTypes::Email]);
+
+        let modified = censor_subtitles(&mut captions, &options).unwrap();
+
+        assert_eq!(modified, 1);
+        assert_eq!(
+            captions,
+            "WEBVTT\r\n\r\nNOTE fuck stays in notes\r\n\r\nintro\r\n00:01.000 --> 00:02.000 align:start\r\n<v Roger>f*<b>**</b> {\\an8}there\r\n**************\r\n\r\n00:03.000 --> 00:04.000\r\nfine\r\n"
+        );
+    }
+}
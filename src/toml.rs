@@ -0,0 +1,131 @@
+//! Censoring strings of TOML documents, e.g. config files sent to support
+//!
+//! Comments, whitespace and ordering of the document are kept, only censored strings
+//! are written again as basic strings.
+
+use toml_edit::{DocumentMut, Formatted, Item, Table, Value};
+
+use crate::error::{Error, Position};
+use crate::json::JsonOptions;
+
+/// Censors string values of the document, returns number of modified ones
+///
+/// Keys are never censored, key patterns of the options apply to tables and their keys.
+///
+/// # Errors
+///
+/// Returns `Error::Parse` if the document isn't valid TOML, otherwise the same
+/// errors as [`crate::censor::Censor::censor`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::json::JsonOptions;
+/// use little_censor::toml::censor_toml;
+///
+/// let mut config = String::from("[owner]\nname = \"fuck\" # nickname\n");
+///
+/// censor_toml(&mut config, &JsonOptions::new()).unwrap();
+///
+/// assert_eq!(config, "[owner]\nname = \"f***\" # nickname\n");
+/// ```
+pub fn censor_toml(text: &mut String, options: &JsonOptions) -> Result<usize, Error> {
+    let mut document: DocumentMut = text.parse().map_err(|error: toml_edit::TomlError| {
+        let offset = error.span().map_or(text.len(), |span| span.start);
+        Error::Parse {
+            at: Position::Line(text[..offset].matches('\n').count() + 1),
+        }
+    })?;
+    let modified = censor_table(document.as_table_mut(), options, options.selects_all())?;
+    if modified > 0 {
+        *text = document.to_string();
+    }
+    Ok(modified)
+}
+
+fn censor_table(table: &mut Table, options: &JsonOptions, selected: bool) -> Result<usize, Error> {
+    let mut modified = 0;
+    for (key, item) in table.iter_mut() {
+        let selected = selected || options.selects(key.get());
+        modified += censor_item(item, options, selected)?;
+    }
+    Ok(modified)
+}
+
+fn censor_item(item: &mut Item, options: &JsonOptions, selected: bool) -> Result<usize, Error> {
+    match item {
+        Item::Value(value) => censor_value(value, options, selected),
+        Item::Table(table) => censor_table(table, options, selected),
+        Item::ArrayOfTables(tables) => {
+            let mut modified = 0;
+            for table in tables.iter_mut() {
+                modified += censor_table(table, options, selected)?;
+            }
+            Ok(modified)
+        }
+        Item::None => Ok(0),
+    }
+}
+
+fn censor_value(value: &mut Value, options: &JsonOptions, selected: bool) -> Result<usize, Error> {
+    match value {
+        Value::String(text) if selected => {
+            let censored = options.censor(text.value().clone())?;
+            if censored.valid {
+                return Ok(0);
+            }
+            let decor = text.decor().clone();
+            *text = Formatted::new(censored.censored);
+            *text.decor_mut() = decor;
+            Ok(1)
+        }
+        Value::Array(items) => {
+            let mut modified = 0;
+            for item in items.iter_mut() {
+                modified += censor_value(item, options, selected)?;
+            }
+            Ok(modified)
+        }
+        Value::InlineTable(table) => {
+            let mut modified = 0;
+            for (key, item) in table.iter_mut() {
+                let selected = selected || options.selects(key.get());
+                modified += censor_value(item, options, selected)?;
+            }
+            Ok(modified)
+        }
+        _ => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_formatting() {
+        let mut config = String::from(
+            "# users\n[[users]]\nname = 'fuck'\ntags = [ \"fine\", \"fuck\" ]\n\n[contact]\nemail = { main = \"fuck\" }\n",
+        );
+
+        let modified = censor_toml(&mut config, &JsonOptions::new().with_keys(["users"])).unwrap();
+
+        assert_eq!(modified, 2);
+        assert_eq!(
+            config,
+            "# users\n[[users]]\nname = \"f***\"\ntags = [ \"fine\", \"f***\" ]\n\n[contact]\nemail = { main = \"fuck\" }\n"
+        );
+    }
+
+    #[test]
+    fn invalid_document() {
+        let mut config = String::from("[owner]\nname = ");
+
+        assert!(matches!(
+            censor_toml(&mut config, &JsonOptions::new()),
+            Err(Error::Parse {
+                at: Position::Line(2)
+            })
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+//! Censoring bodies of HTTP requests and responses with a `tower` layer, e.g. for axum
+//!
+//! JSON bodies are censored with [`crate::json::censor_json`] and `text/*` bodies as a whole, bodies
+//! with other content types are passed through without being read.
+
+use std::error::Error as StdError;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::body::{censor_body, BodyError, Content};
+use crate::json::JsonOptions;
+
+/// Layer censoring request bodies, and optionally response bodies, with a shared censor
+///
+/// Requests whose body can't be read or parsed are answered with `400 Bad Request`,
+/// bodies over the limit with `413 Payload Too Large` and failed censoring with
+/// `500 Internal Server Error`, the inner service isn't called for them.
+///
+/// # Examples
+///
+/// The layer is added to an axum router with `Router::layer`:
+///
+/// ```
+/// use little_censor::json::JsonOptions;
+/// use little_censor::tower::CensorLayer;
+///
+/// let layer = CensorLayer::new(JsonOptions::new().with_keys(["text", "*message*"]))
+///     .with_responses(true)
+///     .with_limit(64 * 1024);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CensorLayer {
+    options: Arc<JsonOptions>,
+    responses: bool,
+    limit: usize,
+}
+
+impl CensorLayer {
+    /// Censors request bodies up to 1 MiB, key patterns apply to JSON bodies only
+    pub fn new(options: JsonOptions) -> Self {
+        Self {
+            options: Arc::new(options),
+            responses: false,
+            limit: 1024 * 1024,
+        }
+    }
+
+    /// Whether response bodies are censored too
+    pub fn with_responses(mut self, responses: bool) -> Self {
+        self.responses = responses;
+        self
+    }
+
+    /// Sets maximum size of censored bodies in bytes
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl<S> Layer<S> for CensorLayer {
+    type Service = CensorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CensorService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Service created by [`CensorLayer`]
+#[derive(Debug, Clone)]
+pub struct CensorService<S> {
+    inner: S,
+    layer: CensorLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for CensorService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Body + From<Bytes> + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<Box<dyn StdError + Send + Sync>>,
+    ResBody: Body + From<Bytes> + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        // The clone may not be ready, so the ready service is taken instead
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let body = match collect_body(&mut parts.headers, body, &layer).await {
+                Ok(body) => body,
+                Err(status) => return Ok(rejection(status)),
+            };
+            let response = inner.call(Request::from_parts(parts, body)).await?;
+            if !layer.responses {
+                return Ok(response);
+            }
+
+            let (mut parts, body) = response.into_parts();
+            match collect_body(&mut parts.headers, body, &layer).await {
+                Ok(body) => Ok(Response::from_parts(parts, body)),
+                Err(_) => Ok(rejection(StatusCode::INTERNAL_SERVER_ERROR)),
+            }
+        })
+    }
+}
+
+async fn collect_body<B>(
+    headers: &mut HeaderMap,
+    body: B,
+    layer: &CensorLayer,
+) -> Result<B, StatusCode>
+where
+    B: Body + From<Bytes>,
+    B::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    let content = match headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Content::of)
+    {
+        Some(content) => content,
+        None => return Ok(body),
+    };
+    let bytes = match Limited::new(body, layer.limit).collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(error) if error.is::<LengthLimitError>() => return Err(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let bytes = match censor_body(content, &bytes, &layer.options) {
+        Ok(Some(censored)) => Bytes::from(censored),
+        Ok(None) => bytes,
+        Err(BodyError::Invalid) => return Err(StatusCode::BAD_REQUEST),
+        Err(BodyError::Censor) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+    Ok(B::from(bytes))
+}
+
+fn rejection<B: From<Bytes>>(status: StatusCode) -> Response<B> {
+    let mut response = Response::new(B::from(Bytes::new()));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::task::Waker;
+
+    use http_body_util::Full;
+
+    use super::*;
+
+    /// Responds with the request body
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<Request<Full<Bytes>>> for Echo {
+        type Response = Response<Full<Bytes>>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<Full<Bytes>>) -> Self::Future {
+            let (parts, body) = request.into_parts();
+            let mut response = Response::new(body);
+            *response.headers_mut() = parts.headers;
+            std::future::ready(Ok(response))
+        }
+    }
+
+    fn send(content_type: &str, body: &'static str) -> Response<Full<Bytes>> {
+        let request = Request::builder()
+            .header(CONTENT_TYPE, content_type)
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+        let mut service = CensorLayer::new(JsonOptions::new().with_keys(["text"])).layer(Echo);
+        let mut future = service.call(request);
+        // Every future is ready at once, there's nothing to wake
+        let mut cx = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(response) => response.unwrap(),
+            Poll::Pending => panic!("censoring wasn't ready"),
+        }
+    }
+
+    fn body(response: Response<Full<Bytes>>) -> String {
+        let mut body = response.into_body();
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut body).poll_frame(&mut cx) {
+            Poll::Ready(Some(Ok(frame))) => {
+                String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap()
+            }
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn censors_by_content_type() {
+        let json = send("application/json", r#"{"text":"fuck","id":"fuck"}"#);
+        assert_eq!(body(json), r#"{"id":"fuck","text":"f***"}"#);
+
+        let text = send("text/plain; charset=utf-8", "fuck");
+        assert_eq!(text.headers()[CONTENT_LENGTH], "4");
+        assert_eq!(body(text), "f***");
+
+        assert_eq!(body(send("application/octet-stream", "fuck")), "fuck");
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert_eq!(
+            send("application/json", "{").status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Scrubbing fields of `tracing` events and spans before they are written
+//!
+//! [`CensoredFields`] formats fields like the default formatter of `tracing_subscriber`,
+//! but every value including the message is censored first, so log scrubbing is done
+//! in one place at the telemetry boundary.
+
+use std::fmt::{self, Debug};
+
+use tracing::field::{Field, Visit};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::FormatFields;
+
+use crate::censor::{Censor, CensorTypes};
+
+/// Field formatter censoring every recorded value
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::censor::{Censor, CensorTypes};
+/// use little_censor::tracing_layer::{layer, CensoredFields};
+/// use tracing_subscriber::prelude::*;
+///
+/// let fields = CensoredFields::new(Censor::new()).with_types([CensorTypes::Email]);
+///
+/// tracing_subscriber::registry().with(layer(fields)).init();
+/// tracing::info!(email = "me@example.net", "signed up");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CensoredFields {
+    censor: Censor,
+    types: Vec<CensorTypes>,
+    arg: Option<String>,
+}
+
+impl CensoredFields {
+    /// Censors fields with this censor without additional types
+    pub fn new(censor: Censor) -> Self {
+        Self {
+            censor,
+            types: Vec::new(),
+            arg: None,
+        }
+    }
+
+    /// Sets additional types of censoring, e.g. `CensorTypes::Email` for PII
+    pub fn with_types(mut self, types: impl IntoIterator<Item = CensorTypes>) -> Self {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    /// Sets argument for `CensorTypes::Custom`
+    pub fn with_arg(mut self, arg: String) -> Self {
+        self.arg = Some(arg);
+        self
+    }
+
+    /// Censored value, values which couldn't be censored are replaced whole
+    fn censor(&self, value: String) -> String {
+        let len = value.len();
+        match self.censor.censor(
+            value,
+            self.types.clone().into_boxed_slice(),
+            self.arg.clone(),
+        ) {
+            Ok(censored) => censored.censored,
+            Err(_) => "*".repeat(len),
+        }
+    }
+}
+
+/// Formatting layer writing events with censored fields to stdout
+///
+/// The writer and event format are changed on the returned layer as usual, e.g. with
+/// `.with_writer(std::io::stderr)`.
+pub fn layer<S>(fields: CensoredFields) -> tracing_subscriber::fmt::Layer<S, CensoredFields>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer().fmt_fields(fields)
+}
+
+impl<'writer> FormatFields<'writer> for CensoredFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        let mut visitor = Visitor {
+            fields: self,
+            writer: &mut writer,
+            first: true,
+            result: Ok(()),
+        };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct Visitor<'a, 'writer> {
+    fields: &'a CensoredFields,
+    writer: &'a mut Writer<'writer>,
+    first: bool,
+    result: fmt::Result,
+}
+
+impl Visitor<'_, '_> {
+    fn write(&mut self, field: &Field, value: String, quoted: bool) {
+        if self.result.is_err() {
+            return;
+        }
+        let value = self.fields.censor(value);
+        let separator = if self.first { "" } else { " " };
+        self.first = false;
+        self.result = match field.name() {
+            "message" => write!(self.writer, "{separator}{value}"),
+            name if quoted => write!(self.writer, "{separator}{name}={value:?}"),
+            name => write!(self.writer, "{separator}{name}={value}"),
+        };
+    }
+}
+
+impl Visit for Visitor<'_, '_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        // Quoted like the default formatter after censoring
+        self.write(field, value.to_owned(), true);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.write(field, format!("{value:?}"), false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn scrubs_fields() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(CensoredFields::new(Censor::new()).with_types([CensorTypes::Email]))
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(email = "me@example.net", id = 4, "fuck");
+        });
+
+        let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(log.contains("f*** email=\"**************\" id=4"), "{log}");
+    }
+}
@@ -0,0 +1,209 @@
+//! Stable string representation of [`Type`], e.g. `"SEXUAL|SEVERE"`
+//!
+//! Flags joined with `|` are combined like `Vulgar.with_types` does: categories and
+//! severities are joined separately and then intersected, a missing part means any
+//! category or severity. Groups separated with `,` are joined, so every type can be
+//! written, e.g. `"SEXUAL|SEVERE,MEAN|MILD"`. Named constants like `"INAPPROPRIATE"`
+//! are written as they are.
+//!
+//! With the `serde` feature the module can be used as `#[serde(with = "little_censor::type_name")]`,
+//! numbers are accepted as raw bits when deserializing.
+
+use rustrict::Type;
+
+use crate::error::Error;
+
+/// Constants written by their name
+const NAMED: [(&str, Type); 15] = [
+    ("NONE", Type::NONE),
+    ("ANY", Type::ANY),
+    ("INAPPROPRIATE", Type::INAPPROPRIATE),
+    ("MILD_OR_HIGHER", Type::MILD_OR_HIGHER),
+    ("MODERATE_OR_HIGHER", Type::MODERATE_OR_HIGHER),
+    ("SAFE", Type::SAFE),
+    ("PROFANE", Type::PROFANE),
+    ("OFFENSIVE", Type::OFFENSIVE),
+    ("SEXUAL", Type::SEXUAL),
+    ("MEAN", Type::MEAN),
+    ("EVASIVE", Type::EVASIVE),
+    ("SPAM", Type::SPAM),
+    ("MILD", Type::MILD),
+    ("MODERATE", Type::MODERATE),
+    ("SEVERE", Type::SEVERE),
+];
+
+pub(crate) const CATEGORIES: [(&str, Type); 6] = [
+    ("PROFANE", Type::PROFANE),
+    ("OFFENSIVE", Type::OFFENSIVE),
+    ("SEXUAL", Type::SEXUAL),
+    ("MEAN", Type::MEAN),
+    ("EVASIVE", Type::EVASIVE),
+    ("SPAM", Type::SPAM),
+];
+
+pub(crate) const SEVERITIES: [(&str, Type); 3] = [
+    ("MILD", Type::MILD),
+    ("MODERATE", Type::MODERATE),
+    ("SEVERE", Type::SEVERE),
+];
+
+/// Writes the type, e.g. `"SEXUAL|SEVERE"` for `Type::SEXUAL & Type::SEVERE`
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::type_name;
+/// use little_censor::Type;
+///
+/// let typ = (Type::SEXUAL & Type::SEVERE) | (Type::MEAN & Type::MILD);
+///
+/// assert_eq!(type_name::format(typ), "SEXUAL|SEVERE,MEAN|MILD");
+/// assert_eq!(type_name::parse("SEXUAL|SEVERE,MEAN|MILD").unwrap(), typ);
+/// ```
+pub fn format(typ: Type) -> String {
+    if let Some((name, _)) = NAMED.iter().find(|(_, named)| *named == typ) {
+        return (*name).to_owned();
+    }
+
+    // Categories grouped by the severities they have
+    let mut groups: Vec<(Type, Vec<&str>)> = Vec::new();
+    for (category, flag) in CATEGORIES {
+        let severity = SEVERITIES
+            .iter()
+            .filter(|(_, severity)| typ & flag & *severity != Type::NONE)
+            .fold(Type::NONE, |all, &(_, severity)| all | severity);
+        if severity == Type::NONE {
+            continue;
+        }
+        match groups.iter_mut().find(|(other, _)| *other == severity) {
+            Some((_, categories)) => categories.push(category),
+            None => groups.push((severity, vec![category])),
+        }
+    }
+
+    let mut written: Vec<String> = groups
+        .into_iter()
+        .map(|(severity, mut names)| {
+            if names.len() == CATEGORIES.len() {
+                names.clear();
+            }
+            if severity != Type::MILD_OR_HIGHER {
+                names.extend(
+                    SEVERITIES
+                        .iter()
+                        .filter(|(_, flag)| severity.is(*flag))
+                        .map(|(name, _)| *name),
+                );
+            }
+            if names.is_empty() {
+                names.push("MILD_OR_HIGHER");
+            }
+            names.join("|")
+        })
+        .collect();
+    if typ.is(Type::SAFE) {
+        written.push(String::from("SAFE"));
+    }
+    written.join(",")
+}
+
+/// Reads the type written by [`format`], names are case insensitive
+///
+/// # Errors
+///
+/// Returns `Error::UnknownType` if any of the names doesn't exist.
+pub fn parse(text: &str) -> Result<Type, Error> {
+    let text = text.trim();
+    if let Ok(bits) = text.parse() {
+        return Ok(Type::from_bits_truncate(bits));
+    }
+    text.split(',')
+        .map(parse_group)
+        .try_fold(Type::NONE, |all, group| Ok(all | group?))
+}
+
+/// Reads flags joined with `|`
+fn parse_group(group: &str) -> Result<Type, Error> {
+    let mut categories = None;
+    let mut severities = None;
+    let mut safe = Type::NONE;
+    for name in group.split('|') {
+        let name = name.trim().to_uppercase();
+        let flag = NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|&(_, flag)| flag)
+            .ok_or(Error::UnknownType)?;
+        let part = match name.as_str() {
+            "SAFE" => &mut safe,
+            "MILD" | "MODERATE" | "SEVERE" | "MILD_OR_HIGHER" | "MODERATE_OR_HIGHER" => {
+                severities.get_or_insert(Type::NONE)
+            }
+            _ => categories.get_or_insert(Type::NONE),
+        };
+        *part |= flag;
+    }
+
+    if categories.is_none() && severities.is_none() {
+        return Ok(safe);
+    }
+    Ok(safe | (categories.unwrap_or(Type::ANY) & severities.unwrap_or(Type::ANY)))
+}
+
+/// Serializes type as its name, for `#[serde(with = "little_censor::type_name")]`
+#[cfg(feature = "serde")]
+pub fn serialize<S: serde::Serializer>(typ: &Type, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format(*typ))
+}
+
+/// Deserializes type from its name or raw bits
+#[cfg(feature = "serde")]
+pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+    use serde::de::{Unexpected, Visitor};
+
+    struct TypeVisitor;
+
+    impl Visitor<'_> for TypeVisitor {
+        type Value = Type;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("name of a type like \"SEXUAL|SEVERE\" or its bits")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Type, E> {
+            parse(text).map_err(|_| E::invalid_value(Unexpected::Str(text), &self))
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, bits: u64) -> Result<Type, E> {
+            u32::try_from(bits)
+                .map(Type::from_bits_truncate)
+                .map_err(|_| E::invalid_value(Unexpected::Unsigned(bits), &self))
+        }
+    }
+
+    deserializer.deserialize_any(TypeVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let types = [
+            Type::NONE,
+            Type::INAPPROPRIATE,
+            Type::SEXUAL & Type::SEVERE,
+            (Type::PROFANE | Type::MEAN) & Type::MODERATE_OR_HIGHER,
+            Type::SEVERE | (Type::SPAM & Type::MILD) | Type::SAFE,
+        ];
+        for typ in types {
+            assert_eq!(parse(&format(typ)).unwrap(), typ, "{}", format(typ));
+        }
+    }
+
+    #[test]
+    fn unknown_name() {
+        assert!(matches!(parse("SEXUAL|HUGE"), Err(Error::UnknownType)));
+    }
+}
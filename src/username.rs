@@ -0,0 +1,382 @@
+//! Validation of usernames and cleaning of display names
+
+use rustrict::Type;
+use thiserror::Error as this_error;
+
+use crate::words;
+
+#[cfg(feature = "wasm-min")]
+use crate::error::WasmError;
+#[cfg(feature = "wasm-min")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Words reserved for staff accounts
+const RESERVED: [&str; 8] = [
+    "admin",
+    "administrator",
+    "moderator",
+    "staff",
+    "official",
+    "support",
+    "system",
+    "owner",
+];
+
+#[derive(Debug, PartialEq, Eq, this_error)]
+pub enum UsernameError {
+    #[error("Username must have at least {0} characters")]
+    TooShort(usize),
+
+    #[error("Username can have at most {0} characters")]
+    TooLong(usize),
+
+    #[error("Username can't contain '{0}'")]
+    InvalidCharacter(char),
+
+    #[error("Username is inappropriate")]
+    Inappropriate,
+
+    #[error("Username impersonates '{0}'")]
+    Impersonation(String),
+}
+
+impl UsernameError {
+    /// Machine readable code of the error, e.g. `"TOO_SHORT"`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TooShort(_) => "TOO_SHORT",
+            Self::TooLong(_) => "TOO_LONG",
+            Self::InvalidCharacter(_) => "INVALID_CHARACTER",
+            Self::Inappropriate => "INAPPROPRIATE",
+            Self::Impersonation(_) => "IMPERSONATION",
+        }
+    }
+}
+
+/// Rules usernames have to follow
+///
+/// Stricter than censoring chat: any vulgar word, even mild one, rejects the name.
+/// Words are also searched for after folding look-alike characters, so `"4dm1n"`
+/// is treated as `"admin"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsernameRules {
+    /// Minimal length in characters
+    pub min_length: usize,
+    /// Maximal length in characters
+    pub max_length: usize,
+    /// Allowed characters besides ASCII letters and digits
+    pub extra_chars: Vec<char>,
+    /// Words which can't be a part of the username
+    pub reserved: Vec<String>,
+}
+
+impl Default for UsernameRules {
+    fn default() -> Self {
+        Self {
+            min_length: 3,
+            max_length: 32,
+            extra_chars: vec!['_', '-', '.'],
+            reserved: RESERVED.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+}
+
+impl UsernameRules {
+    /// Validates the name, returns it without surrounding whitespace
+    ///
+    /// # Errors
+    ///
+    /// Returns `UsernameError` describing the first broken rule.
+    pub fn validate(&self, name: &str) -> Result<String, UsernameError> {
+        let name = name.trim();
+        let length = name.chars().count();
+        if length < self.min_length {
+            return Err(UsernameError::TooShort(self.min_length));
+        }
+        if length > self.max_length {
+            return Err(UsernameError::TooLong(self.max_length));
+        }
+        if let Some(invalid) = name
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !self.extra_chars.contains(c))
+        {
+            return Err(UsernameError::InvalidCharacter(invalid));
+        }
+
+        let folded = fold(name);
+        if let Some(reserved) = self
+            .reserved
+            .iter()
+            .find(|reserved| folded.contains(&fold(reserved)))
+        {
+            return Err(UsernameError::Impersonation(reserved.clone()));
+        }
+
+        let inappropriate = [name.to_lowercase(), folded].iter().any(|variant| {
+            let _trie = words::read_trie();
+            rustrict::Censor::from_str(variant)
+                .analyze()
+                .is(Type::MILD_OR_HIGHER)
+        });
+        if inappropriate {
+            return Err(UsernameError::Inappropriate);
+        }
+
+        Ok(name.to_owned())
+    }
+}
+
+/// Validates username with default [`UsernameRules`]
+///
+/// # Errors
+///
+/// Returns `UsernameError` describing the first broken rule.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::username::{validate_username, UsernameError};
+///
+/// assert_eq!(validate_username(" player_one ").unwrap(), "player_one");
+/// assert_eq!(validate_username("ab"), Err(UsernameError::TooShort(3)));
+/// assert!(matches!(validate_username("Real_4dm1n"), Err(UsernameError::Impersonation(_))));
+/// ```
+pub fn validate_username(name: &str) -> Result<String, UsernameError> {
+    UsernameRules::default().validate(name)
+}
+
+/// Validates username with default rules
+///
+/// # Errors
+///
+/// Throws an Error describing the first broken rule, with its `code`, e.g. `TOO_SHORT`,
+/// and the name as `input`.
+///
+/// # Examples
+///
+/// validate_username("player_one");
+#[cfg(feature = "wasm-min")]
+#[wasm_bindgen(js_name = "validate_username")]
+pub fn validate_username_w(name: String) -> Result<String, WasmError> {
+    validate_username(&name).map_err(|error| WasmError::new(error.code(), error).with_input(name))
+}
+
+/// Modification made to a display name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameChange {
+    /// Surrounding whitespace was removed or inner whitespace collapsed
+    Whitespace,
+    /// Control and invisible characters were removed
+    Invisible,
+    /// Stacked combining marks (zalgo) were removed
+    Marks,
+    /// Long runs of symbols were shortened
+    Symbols,
+    /// Vulgar words were censored
+    Profanity,
+}
+
+/// Display name after [`sanitize_display_name`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizedName {
+    pub name: String,
+    /// Kinds of modifications in order of applying, empty if the name was clean
+    pub changes: Vec<NameChange>,
+}
+
+/// Combining marks kept on a single character, enough for any real script
+const MAX_MARKS: usize = 2;
+
+/// Symbols kept in a run of decoration
+const MAX_SYMBOLS: usize = 3;
+
+/// Cleans display name for showing it to other users
+///
+/// Unlike [`validate_username`] it never rejects the name, it only removes
+/// the parts which shouldn't be displayed.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::username::{sanitize_display_name, NameChange};
+///
+/// let sanitized = sanitize_display_name("  \u{200B}Jo\u{0301}\u{0301}\u{0301}\u{0301}hn ★★★★★★ ");
+///
+/// assert_eq!(sanitized.name, "Jo\u{0301}\u{0301}hn ★★★");
+/// assert_eq!(
+///     sanitized.changes,
+///     vec![NameChange::Invisible, NameChange::Whitespace, NameChange::Marks, NameChange::Symbols]
+/// );
+/// ```
+pub fn sanitize_display_name(name: &str) -> SanitizedName {
+    let mut changes = Vec::new();
+    let mut note = |changed: bool, change: NameChange| {
+        if changed && !changes.contains(&change) {
+            changes.push(change);
+        }
+    };
+
+    let visible: String = name.chars().filter(|&c| !is_invisible(c)).collect();
+    note(visible.len() != name.len(), NameChange::Invisible);
+    let collapsed = visible.split_whitespace().collect::<Vec<_>>().join(" ");
+    note(collapsed != visible, NameChange::Whitespace);
+
+    let mut cleaned = String::with_capacity(collapsed.len());
+    let mut marks = 0;
+    let mut symbols: Option<(char, usize)> = None;
+    for c in collapsed.chars() {
+        if is_combining_mark(c) {
+            marks += 1;
+            if marks > MAX_MARKS {
+                note(true, NameChange::Marks);
+                continue;
+            }
+        } else {
+            marks = 0;
+        }
+
+        if !c.is_alphanumeric() && !c.is_whitespace() && !is_combining_mark(c) {
+            let run = match symbols {
+                Some((symbol, run)) if symbol == c => run + 1,
+                _ => 1,
+            };
+            symbols = Some((c, run));
+            if run > MAX_SYMBOLS {
+                note(true, NameChange::Symbols);
+                continue;
+            }
+        } else if !is_combining_mark(c) {
+            symbols = None;
+        }
+
+        cleaned.push(c);
+    }
+
+    let censored = {
+        let _trie = words::read_trie();
+        rustrict::Censor::from_str(&cleaned).censor()
+    };
+    note(censored != cleaned, NameChange::Profanity);
+
+    SanitizedName {
+        name: censored,
+        changes,
+    }
+}
+
+/// Control characters, zero width characters and direction overrides
+fn is_invisible(c: char) -> bool {
+    (c.is_control() && !c.is_whitespace())
+        || matches!(
+            c,
+            '\u{00AD}'
+                | '\u{034F}'
+                | '\u{180E}'
+                | '\u{200B}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2069}'
+                | '\u{FEFF}'
+        )
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+/// Lowercase letters with look-alike characters replaced and separators removed
+fn fold(name: &str) -> String {
+    name.chars()
+        .filter_map(|c| {
+            let folded = match c.to_lowercase().next().unwrap_or(c) {
+                '0' | 'о' | 'ο' => 'o',
+                '1' | '!' | '|' | 'і' | 'ι' => 'i',
+                '3' | 'е' | 'ε' => 'e',
+                '4' | '@' | 'а' | 'α' => 'a',
+                '5' | '$' | 'ѕ' => 's',
+                '7' | 'т' | 'τ' => 't',
+                '8' => 'b',
+                '9' => 'g',
+                'р' | 'ρ' => 'p',
+                'с' => 'c',
+                'у' | 'γ' => 'y',
+                'х' | 'χ' => 'x',
+                'к' | 'κ' => 'k',
+                'м' => 'm',
+                'н' => 'h',
+                'ν' => 'v',
+                c if c.is_alphanumeric() => c,
+                _ => return None,
+            };
+            Some(folded)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_limits() {
+        let rules = UsernameRules {
+            max_length: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(rules.validate("abcdef"), Err(UsernameError::TooLong(5)));
+        assert_eq!(rules.validate("abcde").unwrap(), "abcde");
+    }
+
+    #[test]
+    fn charset() {
+        assert_eq!(
+            validate_username("hello world"),
+            Err(UsernameError::InvalidCharacter(' '))
+        );
+    }
+
+    #[test]
+    fn confusables() {
+        assert_eq!(fold("М0d-3r.@tоr"), "moderator");
+        assert_eq!(
+            validate_username("the_m0derat0r"),
+            Err(UsernameError::Impersonation(String::from("moderator")))
+        );
+    }
+
+    #[test]
+    fn profanity() {
+        assert_eq!(
+            validate_username("fuck_you"),
+            Err(UsernameError::Inappropriate)
+        );
+        assert_eq!(validate_username("sh1t"), Err(UsernameError::Inappropriate));
+    }
+
+    #[test]
+    fn clean_display_name() {
+        let sanitized = sanitize_display_name("John Smith");
+
+        assert_eq!(sanitized.name, "John Smith");
+        assert!(sanitized.changes.is_empty());
+    }
+
+    #[test]
+    fn display_name_profanity() {
+        let sanitized = sanitize_display_name("Big\tfuck");
+
+        assert_eq!(sanitized.name, "Big f***");
+        assert_eq!(
+            sanitized.changes,
+            vec![NameChange::Whitespace, NameChange::Profanity]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Checks of words before they are added to the word list, e.g. uploads of moderators
+
+use crate::error::Error;
+use crate::{add_words_with, AddSummary, DuplicatePolicy, Vulgar};
+
+/// Characters with a meaning in regular expressions
+const METACHARACTERS: [char; 14] = [
+    '.', '*', '+', '?', '(', ')', '[', ']', '{', '}', '|', '^', '$', '\\',
+];
+
+/// Kind of characters a word may consist of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    /// Letters of any script, with their combining marks
+    Letter,
+    /// Digits of any script
+    Digit,
+    /// Spaces between words of a phrase
+    Whitespace,
+    /// ASCII punctuation, e.g. `'` or `-`
+    Punctuation,
+    /// Everything else except control characters, e.g. emoji
+    Other,
+}
+
+impl CharClass {
+    fn of(c: char) -> Option<Self> {
+        if c.is_alphabetic() || is_combining_mark(c) {
+            Some(Self::Letter)
+        } else if c.is_numeric() {
+            Some(Self::Digit)
+        } else if c.is_whitespace() {
+            Some(Self::Whitespace)
+        } else if c.is_ascii_punctuation() {
+            Some(Self::Punctuation)
+        } else if c.is_control() {
+            None
+        } else {
+            Some(Self::Other)
+        }
+    }
+}
+
+/// Combining diacritical marks, which aren't alphabetic on their own
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36F}' | '\u{1AB0}'..='\u{1AFF}' | '\u{1DC0}'..='\u{1DFF}')
+}
+
+/// Rules words have to follow before they're added to the word list
+///
+/// Whitespace-only words are rejected unless allowed, everything else is opt-in.
+/// Proximity markers of phrases like `~2` aren't checked against the classes.
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::validation::{CharClass, WordValidation};
+/// use little_censor::{DuplicatePolicy, Vulgar};
+///
+/// let validation = WordValidation::new()
+///     .with_max_length(32)
+///     .with_classes([CharClass::Letter, CharClass::Whitespace])
+///     .with_metacharacters(false);
+/// let upload = vec![
+///     Vulgar::new(String::from("moron"), None),
+///     Vulgar::new(String::from("m.r.n"), None),
+///     Vulgar::new(String::from("   "), None),
+/// ];
+///
+/// let errors = validation.check(&upload);
+/// assert_eq!(errors.len(), 2);
+///
+/// assert!(validation.add_words(upload, DuplicatePolicy::Overwrite).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct WordValidation {
+    max_length: Option<usize>,
+    classes: Option<Vec<CharClass>>,
+    metacharacters: bool,
+    blank: bool,
+}
+
+impl Default for WordValidation {
+    fn default() -> Self {
+        Self {
+            max_length: None,
+            classes: None,
+            metacharacters: true,
+            blank: false,
+        }
+    }
+}
+
+impl WordValidation {
+    /// Rejects only whitespace-only words
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects words longer than this many characters
+    pub fn with_max_length(mut self, characters: usize) -> Self {
+        self.max_length = Some(characters);
+        self
+    }
+
+    /// Rejects words with characters outside of these classes
+    pub fn with_classes(mut self, classes: impl IntoIterator<Item = CharClass>) -> Self {
+        self.classes = Some(classes.into_iter().collect());
+        self
+    }
+
+    /// Whether regex metacharacters like `*` or `$` are allowed, they are by default
+    ///
+    /// Words are matched literally, so metacharacters usually mean a regex was uploaded
+    /// by mistake.
+    pub fn with_metacharacters(mut self, allowed: bool) -> Self {
+        self.metacharacters = allowed;
+        self
+    }
+
+    /// Whether words of only whitespace are allowed, they aren't by default
+    pub fn with_blank(mut self, allowed: bool) -> Self {
+        self.blank = allowed;
+        self
+    }
+
+    /// Checks the word at `index` of the given words
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::EmptyWord` if the word is empty or `Error::RejectedWord` with
+    /// the broken rule.
+    pub fn validate(&self, index: usize, vulgar: &Vulgar) -> Result<(), Error> {
+        let word = vulgar.word.as_str();
+        let rejected = |reason: String| Error::RejectedWord { index, reason };
+        if word.is_empty() {
+            return Err(Error::EmptyWord { index });
+        }
+        if !self.blank && word.trim().is_empty() {
+            return Err(rejected(String::from("word is only whitespace")));
+        }
+        if let Some(max) = self.max_length {
+            let length = word.chars().count();
+            if length > max {
+                return Err(rejected(format!(
+                    "word has {length} characters, at most {max} are allowed"
+                )));
+            }
+        }
+        if !self.metacharacters {
+            if let Some(c) = word.chars().find(|c| METACHARACTERS.contains(c)) {
+                return Err(rejected(format!("regex metacharacter `{c}` isn't allowed")));
+            }
+        }
+        if let Some(classes) = &self.classes {
+            let disallowed = word
+                .split_whitespace()
+                .filter(|part| !is_proximity(part))
+                .flat_map(str::chars)
+                .chain(word.chars().filter(|c| c.is_whitespace()))
+                .find(|&c| CharClass::of(c).is_none_or(|class| !classes.contains(&class)));
+            if let Some(c) = disallowed {
+                return Err(rejected(format!(
+                    "character {c:?} isn't of an allowed class"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Errors of every word which breaks the rules, in order
+    pub fn check(&self, vulgars: &[Vulgar]) -> Vec<Error> {
+        vulgars
+            .iter()
+            .enumerate()
+            .filter_map(|(index, vulgar)| self.validate(index, vulgar).err())
+            .collect()
+    }
+
+    /// Adds the words with [`add_words_with`] if every one of them follows the rules
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first word breaking the rules, then no word is added,
+    /// otherwise the same errors as [`add_words_with`].
+    pub fn add_words(
+        &self,
+        vulgars: Vec<Vulgar>,
+        policy: DuplicatePolicy,
+    ) -> Result<AddSummary, Error> {
+        for (index, vulgar) in vulgars.iter().enumerate() {
+            self.validate(index, vulgar)?;
+        }
+        add_words_with(vulgars, policy)
+    }
+}
+
+/// Whether a part of a phrase is a proximity marker like `~2`
+fn is_proximity(part: &str) -> bool {
+    part.strip_prefix('~')
+        .is_some_and(|distance| distance.parse::<usize>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_broken_rules() {
+        let validation = WordValidation::new()
+            .with_max_length(16)
+            .with_classes([CharClass::Letter, CharClass::Whitespace])
+            .with_metacharacters(false);
+        let vulgars = vec![
+            Vulgar::new(String::from("kill ~2 yourself"), None),
+            Vulgar::new(String::from("cafe\u{301}"), None),
+            Vulgar::new(String::from("badword$"), None),
+            Vulgar::new(String::from("a_very_long_word_indeed"), None),
+            Vulgar::new(String::from("b4dword"), None),
+            Vulgar::new(String::from(" \t"), None),
+        ];
+
+        let errors: Vec<String> = validation
+            .check(&vulgars)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            errors,
+            [
+                "Word at index 2 was rejected: regex metacharacter `$` isn't allowed",
+                "Word at index 3 was rejected: word has 23 characters, at most 16 are allowed",
+                "Word at index 4 was rejected: character '4' isn't of an allowed class",
+                "Word at index 5 was rejected: word is only whitespace",
+            ]
+        );
+        assert!(matches!(
+            validation.add_words(vulgars, DuplicatePolicy::Overwrite),
+            Err(Error::RejectedWord { index: 2, .. })
+        ));
+    }
+}
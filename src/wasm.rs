@@ -0,0 +1,460 @@
+//! Bindings exchanging plain JavaScript objects instead of wasm-bindgen classes
+
+use std::collections::BTreeMap;
+
+use rustrict::Type;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+use crate::censor::{censor, utf16_offset, Censor, CensorTypes, Censored};
+use crate::error::{Error, WasmError};
+use crate::pack::read_pack;
+use crate::phrase::Phrase;
+use crate::report::Verdict;
+use crate::{add_words, JsType, Vulgar};
+
+#[wasm_bindgen(typescript_custom_section)]
+const CENSOR_RULES: &str = r#"
+/** Regex rules applied in addition to vulgar words, each with its own options */
+export interface CensorRules {
+    /** Masks links, except the allowed domains and their subdomains */
+    link?: boolean | { allowedDomains?: string[] };
+    /** Masks IP addresses */
+    ip?: boolean;
+    /** Masks email addresses */
+    email?: boolean;
+    /** Masks matches of the regex pattern */
+    custom?: string;
+}
+
+/** Name of a flag of the vulgar word type, e.g. `"mean"` */
+export type TypeName =
+    | "profane" | "offensive" | "sexual" | "mean" | "evasive" | "spam" | "safe"
+    | "mild" | "moderate" | "severe" | "mild_or_higher" | "moderate_or_higher"
+    | "inappropriate" | "any";
+
+/** Configuration of a censor */
+export interface CensorOptions {
+    /** Single character masking censored text, `"*"` by default */
+    replacement?: string;
+    /** Censors only vulgar words of this type, flags of an array are combined */
+    threshold?: TypeName | TypeName[];
+    /** Regex rules applied in addition to vulgar words */
+    rules?: CensorRules;
+    /** Domains whose links aren't masked, together with their subdomains */
+    allowedDomains?: string[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "CensorRules")]
+    pub type CensorRules;
+
+    #[wasm_bindgen(typescript_type = "CensorOptions")]
+    pub type CensorOptions;
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PlainRules {
+    link: Option<LinkRule>,
+    ip: bool,
+    email: bool,
+    custom: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LinkRule {
+    Enabled(bool),
+    #[serde(rename_all = "camelCase")]
+    Options {
+        #[serde(default)]
+        allowed_domains: Vec<String>,
+    },
+}
+
+/// Options given as `CensorOptions`
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct PlainOptions {
+    replacement: Option<String>,
+    threshold: Option<PlainType>,
+    rules: PlainRules,
+    allowed_domains: Vec<String>,
+}
+
+/// Censor configured by options together with the rules it applies
+pub(crate) struct Configured {
+    pub(crate) censor: Censor,
+    pub(crate) types: Box<[CensorTypes]>,
+    pub(crate) arg: Option<String>,
+}
+
+impl Configured {
+    /// Reads `CensorOptions`, throws Error with code `INVALID_INPUT` if they are malformed
+    pub(crate) fn from_options(options: CensorOptions) -> Result<Self, WasmError> {
+        Self::new(from_js(options.into())?)
+    }
+
+    fn new(options: PlainOptions) -> Result<Self, WasmError> {
+        let mut censor = Censor::new();
+
+        if let Some(replacement) = options.replacement {
+            let mut chars = replacement.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => censor = censor.with_replacement(c),
+                _ => {
+                    return Err(WasmError::new(
+                        "INVALID_INPUT",
+                        "Replacement has to be a single character",
+                    )
+                    .with_input(replacement))
+                }
+            }
+        }
+        match options.threshold {
+            Some(PlainType::Single(typ)) => censor = censor.with_threshold(typ.into()),
+            Some(PlainType::Combined(types)) => {
+                censor = censor.with_threshold(JsType::combine(&types))
+            }
+            None => {}
+        }
+
+        let mut allowed_domains = options.allowed_domains;
+        let mut types = Vec::new();
+        match options.rules.link {
+            Some(LinkRule::Enabled(true)) => types.push(CensorTypes::Link),
+            Some(LinkRule::Options {
+                allowed_domains: domains,
+            }) => {
+                types.push(CensorTypes::Link);
+                allowed_domains.extend(domains);
+            }
+            Some(LinkRule::Enabled(false)) | None => {}
+        }
+        if options.rules.ip {
+            types.push(CensorTypes::IP);
+        }
+        if options.rules.email {
+            types.push(CensorTypes::Email);
+        }
+        if options.rules.custom.is_some() {
+            types.push(CensorTypes::Custom);
+        }
+
+        Ok(Self {
+            censor: censor.with_allowed_domains(allowed_domains),
+            types: types.into_boxed_slice(),
+            arg: options.rules.custom,
+        })
+    }
+
+    /// Censors sentence with the configured rules
+    pub(crate) fn censor(&self, sentence: String) -> Result<Censored, WasmError> {
+        self.censor
+            .censor(sentence, self.types.clone(), self.arg.clone())
+            .map_err(WasmError::from)
+    }
+}
+
+/// Vulgar word given as `{word, type, weight}`, type can be an array of flags
+#[derive(Deserialize)]
+struct PlainVulgar {
+    word: String,
+    #[serde(default, rename = "type")]
+    typ: Option<PlainType>,
+    #[serde(default)]
+    weight: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PlainType {
+    Single(JsType),
+    Combined(Vec<JsType>),
+}
+
+/// Censored region with UTF-16 offsets
+#[derive(Serialize)]
+struct PlainSpan {
+    start: u32,
+    end: u32,
+    kind: &'static str,
+}
+
+/// Result of censoring given as a plain object
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlainCensored<'a> {
+    original: &'a str,
+    censored: &'a str,
+    valid: bool,
+    truncated: bool,
+    pii_redacted: bool,
+    profanity_censored: bool,
+    score: u32,
+    types: Vec<JsType>,
+    spans: Vec<PlainSpan>,
+    verdict: Verdict,
+}
+
+impl<'a> From<&'a Censored> for PlainCensored<'a> {
+    fn from(censored: &'a Censored) -> Self {
+        Self {
+            original: &censored.original,
+            censored: &censored.censored,
+            valid: censored.valid,
+            truncated: censored.truncated,
+            pii_redacted: censored.pii_redacted(),
+            profanity_censored: censored.profanity_censored(),
+            score: censored.score,
+            types: JsType::flags(censored.typ),
+            spans: censored
+                .spans
+                .iter()
+                .map(|span| PlainSpan {
+                    start: utf16_offset(&censored.original, span.start),
+                    end: utf16_offset(&censored.original, span.end),
+                    kind: span.kind.name(),
+                })
+                .collect(),
+            verdict: censored.verdict,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Censored {
+    /// Result as a plain object, used by `JSON.stringify` and safe to `postMessage`
+    ///
+    /// # Examples
+    ///
+    /// const { original, censored, valid, spans, types } = censor(text, [], undefined).toJSON();
+    #[wasm_bindgen(js_name = "toJSON")]
+    pub fn to_json(&self) -> Result<JsValue, WasmError> {
+        to_js(&PlainCensored::from(self))
+    }
+}
+
+pub(crate) fn to_js<T: Serialize>(value: &T) -> Result<JsValue, WasmError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| WasmError::new("INVALID_INPUT", e))
+}
+
+/// Reads plain object, throws Error with code `INVALID_INPUT` if it is malformed
+pub(crate) fn from_js<T: for<'de> Deserialize<'de>>(value: JsValue) -> Result<T, WasmError> {
+    serde_wasm_bindgen::from_value(value).map_err(|e| WasmError::new("INVALID_INPUT", e))
+}
+
+/// Adds words given as plain objects
+///
+/// # Errors
+///
+/// Throws an Error with code `INVALID_INPUT` if the objects are malformed or
+/// `EMPTY_WORD` with the word as `input` if any of the words is empty.
+///
+/// # Examples
+///
+/// add_plain_words([{ word: "moron", type: "mean", weight: 2 }, { word: "xxx", type: ["sexual", "severe"] }]);
+#[wasm_bindgen(js_name = "add_plain_words")]
+pub fn add_plain_words(words: JsValue) -> Result<(), WasmError> {
+    let words: Vec<PlainVulgar> = from_js(words)?;
+    for vulgar in words {
+        let word = vulgar.word.clone();
+        let weight = vulgar.weight;
+        let mut vulgar = match vulgar.typ {
+            Some(PlainType::Combined(types)) => Vulgar::with_types(vulgar.word, types.into()),
+            Some(PlainType::Single(typ)) => Vulgar::new(vulgar.word, Some(typ)),
+            None => Vulgar::new(vulgar.word, None),
+        };
+        vulgar.weight = weight;
+        add_words(vec![vulgar]).map_err(|error| WasmError::from(error).with_input(word))?;
+    }
+    Ok(())
+}
+
+/// Censors given string, returns result as a plain object
+///
+/// # Errors
+///
+/// Throws the same errors as `censor`.
+///
+/// # Examples
+///
+/// const { censored, spans, verdict } = censor_plain("Some sentence", [], undefined);
+#[wasm_bindgen(js_name = "censor_plain")]
+pub fn censor_plain(
+    sentence: String,
+    types: Box<[CensorTypes]>,
+    arg: Option<String>,
+) -> Result<JsValue, WasmError> {
+    let censored = censor(sentence, types, arg).map_err(WasmError::from)?;
+    to_js(&PlainCensored::from(&censored))
+}
+
+/// Censors given string with rules configured separately
+///
+/// # Errors
+///
+/// Throws an Error with code `INVALID_INPUT` if the rules are malformed or
+/// `INVALID_REGEX` with the pattern as `input` if the custom pattern is invalid.
+///
+/// # Examples
+///
+/// censor_with_rules(sentence, { custom: "#\\d+", link: { allowedDomains: ["example.net"] } });
+#[wasm_bindgen(js_name = "censor_with_rules")]
+pub fn censor_with_rules(sentence: String, rules: CensorRules) -> Result<Censored, WasmError> {
+    let options = PlainOptions {
+        rules: from_js(rules.into())?,
+        ..Default::default()
+    };
+    Configured::new(options)?.censor(sentence)
+}
+
+/// Censors given string with a replacement character, threshold, rules and allowed domains
+///
+/// # Errors
+///
+/// Throws an Error with code `INVALID_INPUT` if the options are malformed or
+/// `INVALID_REGEX` with the pattern as `input` if the custom pattern is invalid.
+///
+/// # Examples
+///
+/// censor_with_options(sentence, { replacement: "#", threshold: "severe", rules: { link: true } });
+#[wasm_bindgen(js_name = "censor_with_options")]
+pub fn censor_with_options(
+    sentence: String,
+    options: CensorOptions,
+) -> Result<Censored, WasmError> {
+    Configured::from_options(options)?.censor(sentence)
+}
+
+/// Censor with its own options and word list, independent of the module functions
+///
+/// The word list starts as the default one and is rebuilt whenever it changes, old
+/// ones are never released, so words should be added at startup rather than per message.
+///
+/// # Examples
+///
+/// const chat = new Censor({ rules: { link: true } });
+/// chat.add_words([new Vulgar("noob", Type.Mild)]);
+/// const { censored } = chat.censor("you noob");
+#[wasm_bindgen(js_name = "Censor")]
+pub struct WasmCensor {
+    configured: Configured,
+    words: BTreeMap<String, Type>,
+}
+
+#[wasm_bindgen(js_class = "Censor")]
+impl WasmCensor {
+    /// Creates censor configured by the options
+    ///
+    /// # Errors
+    ///
+    /// Throws an Error with code `INVALID_INPUT` if the options are malformed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Option<CensorOptions>) -> Result<WasmCensor, WasmError> {
+        let configured = match options {
+            Some(options) => Configured::from_options(options)?,
+            None => Configured::new(PlainOptions::default())?,
+        };
+        Ok(Self {
+            configured,
+            words: BTreeMap::new(),
+        })
+    }
+
+    /// Adds words to the word list of this censor
+    ///
+    /// # Errors
+    ///
+    /// Throws an Error with code `EMPTY_WORD` and the word as `input` if any of the
+    /// words is empty, the word list isn't changed then.
+    pub fn add_words(&mut self, vulgars: Box<[Vulgar]>) -> Result<(), WasmError> {
+        let mut parsed = Vec::with_capacity(vulgars.len());
+        for (index, vulgar) in vulgars.into_vec().into_iter().enumerate() {
+            match Phrase::parse(&vulgar.word, vulgar.word_type) {
+                Ok(phrase) if !vulgar.word.is_empty() => parsed.push((vulgar, phrase)),
+                _ => {
+                    return Err(WasmError::from(Error::EmptyWord { index }).with_input(vulgar.word))
+                }
+            }
+        }
+        let list = self.configured.censor.word_list_mut();
+        for (vulgar, phrase) in parsed {
+            list.add(&vulgar.word, vulgar.word_type, phrase);
+            self.words.insert(vulgar.word, vulgar.word_type);
+        }
+        Ok(())
+    }
+
+    /// Marks words as safe for this censor
+    ///
+    /// # Errors
+    ///
+    /// Throws the same errors as `add_words`.
+    pub fn add_safe_words(&mut self, words: Box<[String]>) -> Result<(), WasmError> {
+        let vulgars: Vec<_> = words
+            .into_vec()
+            .into_iter()
+            .map(|word| Vulgar {
+                word,
+                word_type: Type::SAFE,
+                weight: None,
+            })
+            .collect();
+        self.add_words(vulgars.into())
+    }
+
+    /// Removes words from the word list of this censor, including the default ones
+    ///
+    /// Returns number of removed words which were added to this censor.
+    pub fn remove_words(&mut self, words: Box<[String]>) -> usize {
+        let list = self.configured.censor.word_list_mut();
+        let mut count = 0;
+        for word in words.into_vec() {
+            list.remove(&word);
+            if self.words.remove(&word).is_some() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Adds words of a pack given as `Uint8Array` to this censor, returns their number
+    ///
+    /// # Errors
+    ///
+    /// Throws an Error with code `PARSE` if the pack is malformed, otherwise the
+    /// same errors as `add_words`.
+    pub fn load_word_pack(&mut self, bytes: &[u8]) -> Result<usize, WasmError> {
+        let vulgars = read_pack(bytes)?;
+        let count = vulgars.len();
+        self.add_words(vulgars.into())?;
+        Ok(count)
+    }
+
+    /// Words and phrases added to this censor
+    pub fn list_words(&self) -> Box<[Vulgar]> {
+        self.words
+            .iter()
+            .map(|(word, &word_type)| Vulgar {
+                word: word.clone(),
+                word_type,
+                weight: None,
+            })
+            .collect()
+    }
+
+    /// Censors given string with the options and word list of this censor
+    ///
+    /// # Errors
+    ///
+    /// Throws an Error with code `INVALID_REGEX` and the pattern as `input` if the
+    /// custom pattern is invalid.
+    pub fn censor(&self, sentence: String) -> Result<Censored, WasmError> {
+        self.configured.censor(sentence)
+    }
+}
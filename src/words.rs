@@ -0,0 +1,124 @@
+//! Bookkeeping of changes made to the default word list
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
+
+use once_cell::sync::Lazy;
+use rustrict::{Trie, Type};
+
+/// Guards the default trie of rustrict, which [`crate::add_words`] and similar functions
+/// change in place while other threads censor with it
+static TRIE: RwLock<()> = RwLock::new(());
+
+/// Words changed with [`crate::add_words`] and similar functions
+pub(crate) static CHANGES: Lazy<Mutex<BTreeMap<String, Change>>> = Lazy::new(Default::default);
+
+/// Weights of words added with [`crate::Vulgar::with_weight`], by [`weight_key`]
+pub(crate) static WEIGHTS: Lazy<RwLock<HashMap<String, u32>>> = Lazy::new(Default::default);
+
+/// Type a word had before the first change and the one it has now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub(crate) original: Option<Type>,
+    pub(crate) current: Option<Type>,
+}
+
+/// Keeps the default trie from changing while the guard lives
+///
+/// Every use of rustrict without an own trie must hold it, the guard must not be
+/// taken again by the same thread.
+pub(crate) fn read_trie() -> RwLockReadGuard<'static, ()> {
+    TRIE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Changes the default trie while no other thread uses it
+pub(crate) fn write_trie<R>(change: impl FnOnce(&mut Trie) -> R) -> R {
+    let _guard = TRIE.write().unwrap_or_else(|e| e.into_inner());
+    // SAFETY: every reader of the default trie holds `read_trie`
+    change(unsafe { Trie::customize_default() })
+}
+
+/// Remembers new type of the word, `previous` is kept only for its first change
+pub(crate) fn record(word: &str, previous: Option<Type>, current: Option<Type>) {
+    CHANGES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(word.to_owned())
+        .or_insert(Change {
+            original: previous,
+            current,
+        })
+        .current = current;
+}
+
+/// Sets or clears weight of the word
+pub(crate) fn set_weight(word: &str, weight: Option<u32>) {
+    let mut weights = WEIGHTS.write().unwrap_or_else(|e| e.into_inner());
+    match weight {
+        Some(weight) => weights.insert(weight_key(word), weight),
+        None => weights.remove(&weight_key(word)),
+    };
+}
+
+/// Weight of the word, `None` if it has none
+pub(crate) fn weight(word: &str) -> Option<u32> {
+    WEIGHTS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&weight_key(word))
+        .copied()
+}
+
+/// Key of a weighted word or phrase, same for any case and whitespace
+pub(crate) fn weight_key(word: &str) -> String {
+    phrase_key(word).to_lowercase()
+}
+
+/// Key of a phrase, same for any whitespace between its words
+pub(crate) fn phrase_key(word: &str) -> String {
+    word.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_original_type() {
+        record("test_record_word", Some(Type::MILD), Some(Type::SEVERE));
+        record("test_record_word", Some(Type::SEVERE), None);
+
+        let changes = CHANGES.lock().unwrap();
+        assert_eq!(
+            changes["test_record_word"],
+            Change {
+                original: Some(Type::MILD),
+                current: None
+            }
+        );
+    }
+
+    #[test]
+    fn words_change_while_censoring() {
+        use crate::censor::Censor;
+        use crate::{add_words, remove_words, Vulgar};
+
+        let writer = std::thread::spawn(|| {
+            for _ in 0..200 {
+                add_words(vec![Vulgar::new(String::from("racing_word"), None)]).unwrap();
+                remove_words(vec![String::from("racing_word")]);
+            }
+        });
+        for _ in 0..200 {
+            Censor::new()
+                .censor(String::from("a racing_word"), Box::new([]), None)
+                .unwrap();
+        }
+        writer.join().unwrap();
+
+        let censored = Censor::new()
+            .censor(String::from("a racing_word"), Box::new([]), None)
+            .unwrap();
+        assert_eq!(censored.censored, "a racing_word");
+    }
+}
@@ -0,0 +1,231 @@
+//! Censoring string scalars of YAML documents, e.g. config files sent to support
+//!
+//! Documents are edited line by line, so comments, ordering and formatting are kept.
+//! Plain, quoted and block scalars are censored, flow collections like `[a, b]`
+//! and scalars spanning several lines without a block indicator are left as they are.
+
+use crate::error::Error;
+use crate::json::JsonOptions;
+
+/// Censors string scalars of the document, returns number of modified ones
+///
+/// Keys are never censored, key patterns of the options apply to nested keys as well.
+///
+/// # Errors
+///
+/// Returns the same errors as [`crate::censor::Censor::censor`].
+///
+/// # Examples
+///
+/// ```
+/// use little_censor::json::JsonOptions;
+/// use little_censor::yaml::censor_yaml;
+///
+/// let mut config = String::from("# owner\nname: fuck # nickname\n");
+///
+/// censor_yaml(&mut config, &JsonOptions::new()).unwrap();
+///
+/// assert_eq!(config, "# owner\nname: f*** # nickname\n");
+/// ```
+pub fn censor_yaml(text: &mut String, options: &JsonOptions) -> Result<usize, Error> {
+    let mut censored = String::with_capacity(text.len());
+    // Indentation of keys above the current line and whether their values are censored
+    let mut keys: Vec<(usize, bool)> = Vec::new();
+    // Indentation of the key owning the block scalar and whether it is censored
+    let mut block: Option<(usize, bool)> = None;
+    let mut modified = 0;
+
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[content.len()..];
+        let indent = content.len() - content.trim_start_matches(' ').len();
+
+        if let Some((owner, selected)) = block {
+            if content.trim().is_empty() {
+                censored.push_str(line);
+                continue;
+            }
+            if indent > owner {
+                let (text, changed) = censor_scalar(&content[indent..], selected, options)?;
+                modified += usize::from(changed);
+                censored.push_str(&content[..indent]);
+                censored.push_str(&text);
+                censored.push_str(ending);
+                continue;
+            }
+            block = None;
+        }
+
+        let rest = &content[indent..];
+        if rest.is_empty() || rest.starts_with(['#', '%']) || rest.starts_with("---") {
+            censored.push_str(line);
+            continue;
+        }
+
+        // Skip markers of sequence items
+        let mut start = indent;
+        while let Some(item) = content[start..]
+            .strip_prefix("- ")
+            .or_else(|| content[start..].strip_prefix('-').filter(|r| r.is_empty()))
+        {
+            start = content.len() - item.trim_start_matches(' ').len();
+        }
+
+        let (value_start, selected, owner) = match split_key(&content[start..]) {
+            Some((key, value)) => {
+                keys.retain(|&(other, _)| other < start);
+                let parent = keys.last().map_or(options.selects_all(), |&(_, s)| s);
+                let selected = parent || options.selects(key.trim_matches(['"', '\'']));
+                keys.push((start, selected));
+                (start + value, selected, start)
+            }
+            None => {
+                keys.retain(|&(other, _)| other <= start);
+                let selected = keys.last().map_or(options.selects_all(), |&(_, s)| s);
+                (start, selected, indent)
+            }
+        };
+
+        let value = &content[value_start..];
+        if value.starts_with(['|', '>']) {
+            block = Some((owner, selected));
+            censored.push_str(line);
+            continue;
+        }
+        let (text, changed) = censor_value(value, selected, options)?;
+        modified += usize::from(changed);
+        censored.push_str(&content[..value_start]);
+        censored.push_str(&text);
+        censored.push_str(ending);
+    }
+
+    *text = censored;
+    Ok(modified)
+}
+
+/// Splits `key: value`, returns the key and offset of the value
+fn split_key(text: &str) -> Option<(&str, usize)> {
+    let key_end = match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => text[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let colon = key_end
+        + text[key_end..]
+            .match_indices(':')
+            .map(|(i, _)| i)
+            .find(|&i| matches!(text[key_end + i + 1..].chars().next(), None | Some(' ')))?;
+    let key = &text[..colon];
+    if key.is_empty() || key.contains(" #") {
+        return None;
+    }
+    let value = &text[colon + 1..];
+    Some((
+        key,
+        colon + 1 + value.len() - value.trim_start_matches(' ').len(),
+    ))
+}
+
+/// Censors scalar which starts a value, keeps its quotes and trailing comment
+fn censor_value(
+    value: &str,
+    selected: bool,
+    options: &JsonOptions,
+) -> Result<(String, bool), Error> {
+    let (quote, body, tail) = match value.chars().next() {
+        Some('"') => match closing_quote(&value[1..], '"') {
+            Some(end) => ("\"", &value[1..end + 1], &value[end + 2..]),
+            None => return Ok((value.to_owned(), false)),
+        },
+        Some('\'') => match closing_quote(&value[1..], '\'') {
+            Some(end) => ("'", &value[1..end + 1], &value[end + 2..]),
+            None => return Ok((value.to_owned(), false)),
+        },
+        // Comments, anchors, aliases, tags and flow collections
+        None | Some('#' | '&' | '*' | '!' | '[' | '{' | '@' | '`') => {
+            return Ok((value.to_owned(), false))
+        }
+        Some(_) => {
+            let end = value.find(" #").unwrap_or(value.len());
+            let body = value[..end].trim_end();
+            ("", body, &value[body.len()..])
+        }
+    };
+
+    let (mut text, changed) = match quote {
+        "'" => {
+            let (text, changed) = censor_scalar(&body.replace("''", "'"), selected, options)?;
+            (text.replace('\'', "''"), changed)
+        }
+        _ => censor_scalar(body, selected, options)?,
+    };
+    // Plain scalars starting with `*` would become aliases
+    if quote.is_empty() && changed && text.starts_with('*') {
+        text = format!("'{}'", text.replace('\'', "''"));
+    } else {
+        text = format!("{quote}{text}{quote}");
+    }
+    text.push_str(tail);
+    Ok((text, changed))
+}
+
+/// Offset of the quote closing a scalar, skipping escaped quotes
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            c if c == quote => {
+                if quote == '\'' && chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                    chars.next();
+                } else {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn censor_scalar(
+    text: &str,
+    selected: bool,
+    options: &JsonOptions,
+) -> Result<(String, bool), Error> {
+    if !selected || text.is_empty() {
+        return Ok((text.to_owned(), false));
+    }
+    let censored = options.censor(text.to_owned())?;
+    Ok((censored.censored, !censored.valid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars_and_blocks() {
+        let mut config = String::from(
+            "users:\n  - name: \"fuck\"\n    bio: |\n      fuck\n    alias: 'it''s fuck'\n  - fuck\nkey: *anchor\n",
+        );
+
+        let modified = censor_yaml(&mut config, &JsonOptions::new()).unwrap();
+
+        assert_eq!(modified, 4);
+        assert_eq!(
+            config,
+            "users:\n  - name: \"f***\"\n    bio: |\n      f***\n    alias: 'it''s f***'\n  - f***\nkey: *anchor\n"
+        );
+    }
+
+    #[test]
+    fn filtered_keys() {
+        let mut config = String::from("contact:\n  email: fuck\nname: fuck\n");
+
+        censor_yaml(&mut config, &JsonOptions::new().with_keys(["contact"])).unwrap();
+
+        assert_eq!(config, "contact:\n  email: f***\nname: fuck\n");
+    }
+}
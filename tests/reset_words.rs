@@ -0,0 +1,22 @@
+//! Resetting the global word list, in its own process so it doesn't remove words other
+//! tests added
+
+use std::thread;
+
+use little_censor::{add_words, list_words, reset_custom_words, Vulgar};
+
+#[test]
+fn reset_while_adding() {
+    let adder = thread::spawn(|| {
+        for _ in 0..10_000 {
+            add_words(vec![Vulgar::new(String::from("resetracer"), None)]).unwrap();
+        }
+    });
+    for _ in 0..10_000 {
+        reset_custom_words();
+    }
+    adder.join().unwrap();
+
+    reset_custom_words();
+    assert!(list_words().is_empty());
+}